@@ -12,6 +12,9 @@ const TRAIT_NAME: &str = "Aggregate";
 /// Name of the attribute, used by [`cqrs::Aggregate`].
 const ATTR_NAME: &str = "aggregate";
 
+/// Names of the arguments allowed in the attribute on a struct.
+const VALID_STRUCT_ARGS: &[&str] = &["name", "snapshot_version"];
+
 /// Implements [`crate::aggregate_derive`] macro expansion.
 pub fn derive(input: syn::DeriveInput) -> Result<TokenStream> {
     util::derive(input, TRAIT_NAME, derive_struct, derive_enum)
@@ -29,6 +32,12 @@ fn derive_struct(input: syn::DeriveInput) -> Result<TokenStream> {
     let const_val = parse_aggregate_type(&meta)?;
     let const_doc = format!("Type name of [`{}`] aggregate", input.ident);
 
+    let snapshot_version = parse_snapshot_version(&meta)?.map(|ver| {
+        quote! {
+            const SNAPSHOT_VERSION: ::cqrs::SnapshotVersion = #ver;
+        }
+    });
+
     let (id_type, id_field) = get_id_field(&data.fields)?;
 
     let type_name = &input.ident;
@@ -51,6 +60,8 @@ fn derive_struct(input: syn::DeriveInput) -> Result<TokenStream> {
         impl#impl_generics ::cqrs::Aggregate for #type_name#ty_generics #extended_where_clause {
             type Id = #id_type;
 
+            #snapshot_version
+
             #[inline(always)]
             fn aggregate_type(&self) -> ::cqrs::AggregateType {
                 Self::AGGREGATE_TYPE
@@ -77,11 +88,25 @@ fn derive_enum(input: syn::DeriveInput) -> Result<TokenStream> {
 
 /// Parses type of [`cqrs::Aggregate`] from `#[aggregate(...)]` attribute.
 fn parse_aggregate_type(meta: &util::Meta) -> Result<String> {
-    let lit: &syn::LitStr = util::parse_lit(meta, "name", &["name"], ATTR_NAME, "= \"...\"")?;
+    let lit: &syn::LitStr =
+        util::parse_lit(meta, "name", VALID_STRUCT_ARGS, ATTR_NAME, "= \"...\"")?;
 
     Ok(lit.value())
 }
 
+/// Parses version of [`cqrs::Aggregate`]'s snapshots format from
+/// `#[aggregate(...)]` attribute, if it's specified.
+fn parse_snapshot_version(meta: &util::Meta) -> Result<Option<u32>> {
+    let lit: Option<&syn::LitInt> = util::parse_lit_opt(
+        meta,
+        "snapshot_version",
+        VALID_STRUCT_ARGS,
+        ATTR_NAME,
+        "= <unsigned integer>",
+    )?;
+    lit.map(syn::LitInt::base10_parse).transpose()
+}
+
 /// Infers or finds via `#[aggregate(id)]` attribute an `id` field
 /// of this aggregate.
 fn get_id_field(fields: &syn::Fields) -> Result<(&syn::Type, TokenStream)> {
@@ -146,4 +171,44 @@ mod spec {
 
         assert_eq!(derive(input).unwrap().to_string(), output.to_string());
     }
+
+    #[test]
+    fn derives_struct_impl_with_snapshot_version() {
+        let input = syn::parse_quote! {
+            #[aggregate(name = "aggregate", snapshot_version = 3)]
+            struct Aggregate {
+                id: AggregateId,
+            }
+        };
+
+        let output = quote! {
+            #[automatically_derived]
+            impl Aggregate {
+                #[doc = "Type name of [`Aggregate`] aggregate"]
+                pub const AGGREGATE_TYPE: ::cqrs::AggregateType = "aggregate";
+            }
+
+            #[automatically_derived]
+            impl ::cqrs::Aggregate for Aggregate
+            where
+                Self: ::core::default::Default
+            {
+                type Id = AggregateId;
+
+                const SNAPSHOT_VERSION: ::cqrs::SnapshotVersion = 3u32;
+
+                #[inline(always)]
+                fn aggregate_type(&self) -> ::cqrs::AggregateType {
+                    Self::AGGREGATE_TYPE
+                }
+
+                #[inline(always)]
+                fn id(&self) -> &Self::Id {
+                    &self.id
+                }
+            }
+        };
+
+        assert_eq!(derive(input).unwrap().to_string(), output.to_string());
+    }
 }
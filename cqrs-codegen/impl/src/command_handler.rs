@@ -0,0 +1,324 @@
+//! Codegen for [`cqrs::CommandHandler`].
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{spanned::Spanned as _, Error, Result};
+
+use crate::util;
+
+/// Name of the implemented trait.
+const TRAIT_NAME: &str = "CommandHandler";
+
+/// Name of the attribute, used by [`cqrs::CommandHandler`].
+const ATTR_NAME: &str = "command_handler";
+
+/// Names of the `#[command_handler(...)]` attribute's arguments, used on
+/// methods by [`cqrs::CommandHandler`].
+const VALID_METHOD_ARGS: &[&str] = &["event"];
+
+/// Implements [`crate::command_handler_attribute`] macro expansion.
+pub fn attribute(args: TokenStream, mut input: syn::ItemImpl) -> Result<TokenStream> {
+    if !args.is_empty() {
+        return Err(Error::new(
+            args.span(),
+            format!("#[{}] attribute accepts no arguments", ATTR_NAME),
+        ));
+    }
+    if let Some((_, path, _)) = &input.trait_ {
+        return Err(Error::new(
+            path.span(),
+            format!(
+                "Trait implementations are not supported for implementing {}",
+                TRAIT_NAME,
+            ),
+        ));
+    }
+
+    let mut handlers = Vec::new();
+    for item in &mut input.items {
+        if let syn::ImplItem::Fn(method) = item {
+            let meta = util::find_nested_meta(&method.attrs, ATTR_NAME)?;
+            method.attrs.retain(|attr| !attr.path().is_ident(ATTR_NAME));
+            handlers.push(Handler::parse(method, meta.as_ref())?);
+        }
+    }
+
+    let self_ty = &input.self_ty;
+    let (impl_generics, _, where_clause) = input.generics.split_for_impl();
+
+    let impls = handlers.iter().map(|handler| {
+        let Handler {
+            method,
+            command,
+            context,
+            event,
+            err,
+            ok,
+            with_context,
+            is_async,
+        } = handler;
+
+        let ctx = if *with_context {
+            quote!(, ctx)
+        } else {
+            quote!()
+        };
+        let await_ = if *is_async { quote!(.await) } else { quote!() };
+
+        quote! {
+            ::cqrs::__async_trait_impl! {
+                #[automatically_derived]
+                impl#impl_generics ::cqrs::CommandHandler<#command> for #self_ty #where_clause {
+                    type Context = #context;
+                    type Event = #event;
+                    type Err = #err;
+                    type Ok = #ok;
+
+                    #[inline]
+                    #[allow(unused_variables)]
+                    async fn handle(
+                        &self,
+                        cmd: #command,
+                        ctx: &Self::Context,
+                    ) -> ::core::result::Result<Self::Ok, Self::Err> {
+                        self.#method(cmd #ctx)#await_
+                    }
+                }
+            }
+        }
+    });
+
+    Ok(quote! {
+        #input
+
+        #(#impls)*
+    })
+}
+
+/// Signature of a method handling a single [`cqrs::Command`].
+struct Handler {
+    /// Name of the method.
+    method: syn::Ident,
+
+    /// Type of the handled [`cqrs::Command`].
+    command: syn::Type,
+
+    /// Type of the context, which is `()` if the method doesn't accept any.
+    context: syn::Type,
+
+    /// Type of the produced [`cqrs::Event`]s.
+    event: syn::Type,
+
+    /// Type of the handling error.
+    err: syn::Type,
+
+    /// Type of the handling result.
+    ok: syn::Type,
+
+    /// Indicates whether the method accepts a context.
+    with_context: bool,
+
+    /// Indicates whether the method is `async`.
+    is_async: bool,
+}
+
+impl Handler {
+    /// Parses [`Handler`] out of the given method and its
+    /// `#[command_handler(...)]` attribute, if any.
+    fn parse(method: &syn::ImplItemFn, meta: Option<&util::Meta>) -> Result<Self> {
+        let sig = &method.sig;
+        let expected = "expected `fn(&self, cmd: Command[, ctx: &Context]) -> Result<Ok, Err>`";
+
+        let mut inputs = sig.inputs.iter();
+        match inputs.next() {
+            Some(syn::FnArg::Receiver(recv))
+                if recv.reference.is_some() && recv.mutability.is_none() => {}
+            _ => return Err(Error::new(sig.span(), expected)),
+        }
+        let command = match inputs.next() {
+            Some(syn::FnArg::Typed(arg)) => (*arg.ty).clone(),
+            _ => return Err(Error::new(sig.span(), expected)),
+        };
+        let context = match inputs.next() {
+            None => None,
+            Some(syn::FnArg::Typed(syn::PatType { ty, .. })) => match &**ty {
+                syn::Type::Reference(ty) if ty.mutability.is_none() => Some((*ty.elem).clone()),
+                _ => return Err(Error::new(ty.span(), expected)),
+            },
+            Some(arg) => return Err(Error::new(arg.span(), expected)),
+        };
+        if let Some(arg) = inputs.next() {
+            return Err(Error::new(arg.span(), expected));
+        }
+
+        let (ok, err) = match &sig.output {
+            syn::ReturnType::Type(_, ty) => util::parse_result_type(ty),
+            syn::ReturnType::Default => None,
+        }
+        .ok_or_else(|| Error::new(sig.output.span(), expected))?;
+
+        let event = match meta
+            .map(|meta| {
+                util::parse_lit_opt::<syn::LitStr>(
+                    meta,
+                    "event",
+                    VALID_METHOD_ARGS,
+                    ATTR_NAME,
+                    "= \"...\"",
+                )
+            })
+            .transpose()?
+            .flatten()
+        {
+            Some(lit) => lit.parse()?,
+            None => infer_event(&ok).ok_or_else(|| {
+                Error::new(
+                    ok.span(),
+                    format!(
+                        "Cannot infer type of events out of the result type; \
+                         consider specifying it with #[{}(event = \"...\")] attribute",
+                        ATTR_NAME,
+                    ),
+                )
+            })?,
+        };
+
+        Ok(Self {
+            method: sig.ident.clone(),
+            command,
+            with_context: context.is_some(),
+            context: context.unwrap_or_else(|| syn::parse_quote!(())),
+            event,
+            err,
+            ok,
+            is_async: sig.asyncness.is_some(),
+        })
+    }
+}
+
+/// Infers type of the produced [`cqrs::Event`]s out of the given type of
+/// the [`cqrs::Command`] handling result.
+///
+/// Returns the type parameter of the first [`cqrs::NumberedEvent`] found in
+/// the given type, or `()` if the result is `()`.
+fn infer_event(ok: &syn::Type) -> Option<syn::Type> {
+    match ok {
+        syn::Type::Tuple(ty) if ty.elems.is_empty() => Some(syn::parse_quote!(())),
+        _ => find_numbered_event(ok),
+    }
+}
+
+/// Finds type parameter of the first [`cqrs::NumberedEvent`] in the given
+/// type.
+fn find_numbered_event(ty: &syn::Type) -> Option<syn::Type> {
+    match ty {
+        syn::Type::Path(ty) => ty.path.segments.iter().rev().find_map(|segment| {
+            let args = match &segment.arguments {
+                syn::PathArguments::AngleBracketed(args) => &args.args,
+                _ => return None,
+            };
+            if segment.ident == "NumberedEvent" && args.len() == 1 {
+                if let syn::GenericArgument::Type(ev) = &args[0] {
+                    return Some(ev.clone());
+                }
+            }
+            args.iter().find_map(|arg| match arg {
+                syn::GenericArgument::Type(ty) => find_numbered_event(ty),
+                _ => None,
+            })
+        }),
+        syn::Type::Array(ty) => find_numbered_event(&ty.elem),
+        syn::Type::Group(ty) => find_numbered_event(&ty.elem),
+        syn::Type::Paren(ty) => find_numbered_event(&ty.elem),
+        syn::Type::Reference(ty) => find_numbered_event(&ty.elem),
+        syn::Type::Slice(ty) => find_numbered_event(&ty.elem),
+        syn::Type::Tuple(ty) => ty.elems.iter().find_map(find_numbered_event),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod spec {
+    use super::*;
+
+    #[test]
+    fn implements_for_each_method() {
+        let input = syn::parse_quote! {
+            impl Aggregate {
+                async fn create(
+                    &self,
+                    cmd: Create,
+                    ctx: &Context,
+                ) -> Result<[NumberedEvent<Created>; 1], Error> {
+                    unimplemented!()
+                }
+
+                #[command_handler(event = "Removed")]
+                fn remove(&self, cmd: Remove) -> Result<Option<Removed>, Infallible> {
+                    unimplemented!()
+                }
+            }
+        };
+
+        let output = quote! {
+            impl Aggregate {
+                async fn create(
+                    &self,
+                    cmd: Create,
+                    ctx: &Context,
+                ) -> Result<[NumberedEvent<Created>; 1], Error> {
+                    unimplemented!()
+                }
+
+                fn remove(&self, cmd: Remove) -> Result<Option<Removed>, Infallible> {
+                    unimplemented!()
+                }
+            }
+
+            ::cqrs::__async_trait_impl! {
+                #[automatically_derived]
+                impl ::cqrs::CommandHandler<Create> for Aggregate {
+                    type Context = Context;
+                    type Event = Created;
+                    type Err = Error;
+                    type Ok = [NumberedEvent<Created>; 1];
+
+                    #[inline]
+                    #[allow(unused_variables)]
+                    async fn handle(
+                        &self,
+                        cmd: Create,
+                        ctx: &Self::Context,
+                    ) -> ::core::result::Result<Self::Ok, Self::Err> {
+                        self.create(cmd, ctx).await
+                    }
+                }
+            }
+
+            ::cqrs::__async_trait_impl! {
+                #[automatically_derived]
+                impl ::cqrs::CommandHandler<Remove> for Aggregate {
+                    type Context = ();
+                    type Event = Removed;
+                    type Err = Infallible;
+                    type Ok = Option<Removed>;
+
+                    #[inline]
+                    #[allow(unused_variables)]
+                    async fn handle(
+                        &self,
+                        cmd: Remove,
+                        ctx: &Self::Context,
+                    ) -> ::core::result::Result<Self::Ok, Self::Err> {
+                        self.remove(cmd)
+                    }
+                }
+            }
+        };
+
+        assert_eq!(
+            attribute(TokenStream::new(), input).unwrap().to_string(),
+            output.to_string(),
+        );
+    }
+}
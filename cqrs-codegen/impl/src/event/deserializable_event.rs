@@ -0,0 +1,274 @@
+//! Codegen for [`cqrs::DeserializableEvent`].
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse_quote, Result};
+use synstructure::Structure;
+
+use crate::util;
+
+/// Name of the derived trait.
+const TRAIT_NAME: &str = "DeserializableEvent";
+
+/// Implements [`crate::deserializable_event_derive`] macro expansion.
+pub fn derive(input: syn::DeriveInput) -> Result<TokenStream> {
+    util::derive(input, TRAIT_NAME, derive_struct, derive_enum)
+}
+
+/// Implements [`crate::deserializable_event_derive`] macro expansion for
+/// structs.
+fn derive_struct(input: syn::DeriveInput) -> Result<TokenStream> {
+    let type_name = &input.ident;
+    let (impl_gens, ty_gens, where_clause) = input.generics.split_for_impl();
+
+    let mut where_clause = where_clause.cloned().unwrap_or_else(|| parse_quote!(where));
+    where_clause.predicates.push(parse_quote! {
+        Self: ::cqrs::StaticTypedEvent
+            + ::cqrs::StaticVersionedEvent
+            + for<'de> ::cqrs::private::serde::Deserialize<'de>
+    });
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl#impl_gens ::cqrs::DeserializableEvent for #type_name#ty_gens
+        #where_clause
+        {
+            #[inline]
+            fn is_deserializable(
+                event_type: &str,
+                event_version: ::cqrs::EventVersion,
+            ) -> bool {
+                event_type == <Self as ::cqrs::StaticTypedEvent>::EVENT_TYPE
+                    && event_version == <Self as ::cqrs::StaticVersionedEvent>::EVENT_VERSION
+            }
+
+            fn deserialize_event<'de, __D>(
+                event_type: &str,
+                event_version: ::cqrs::EventVersion,
+                deserializer: __D,
+            ) -> ::core::result::Result<::core::option::Option<Self>, __D::Error>
+            where
+                __D: ::cqrs::private::serde::Deserializer<'de>,
+            {
+                if !<Self as ::cqrs::DeserializableEvent>::is_deserializable(
+                    event_type,
+                    event_version,
+                ) {
+                    return ::core::result::Result::Ok(::core::option::Option::None);
+                }
+                <Self as ::cqrs::private::serde::Deserialize<'de>>::deserialize(deserializer)
+                    .map(::core::option::Option::Some)
+            }
+        }
+    })
+}
+
+/// Implements [`crate::deserializable_event_derive`] macro expansion for
+/// enums via [`synstructure`].
+///
+/// Variants are tried in their declaration order, so the first variant able to
+/// deserialize the tagged payload wins.
+fn derive_enum(input: syn::DeriveInput) -> Result<TokenStream> {
+    util::assert_valid_attr_args_used(&input.attrs, super::ATTR_NAME, super::VALID_ENUM_ARGS)?;
+
+    let structure = Structure::try_new(&input)?;
+    util::assert_all_enum_variants_have_single_field(&structure, TRAIT_NAME)?;
+
+    let syn::Data::Enum(data) = input.data else {
+        unreachable!("already checked")
+    };
+
+    let type_name = &input.ident;
+
+    let mut where_clause = input
+        .generics
+        .where_clause
+        .clone()
+        .unwrap_or_else(|| parse_quote!(where));
+    for v in &data.variants {
+        let ty = &v.fields.iter().next().expect("already checked").ty;
+        where_clause
+            .predicates
+            .push(parse_quote!(#ty: ::cqrs::DeserializableEvent));
+    }
+
+    let ty = data
+        .variants
+        .iter()
+        .map(|v| &v.fields.iter().next().expect("already checked").ty)
+        .collect::<Vec<_>>();
+
+    let variant = data.variants.iter().map(|v| {
+        let ident = &v.ident;
+        let field = &v.fields.iter().next().expect("already checked");
+        if let Some(field_ident) = &field.ident {
+            quote! { Self::#ident { #field_ident: ev } }
+        } else {
+            quote! { Self::#ident(ev) }
+        }
+    });
+
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::cqrs::DeserializableEvent for #type_name #ty_generics
+        #where_clause
+        {
+            fn is_deserializable(
+                event_type: &str,
+                event_version: ::cqrs::EventVersion,
+            ) -> bool {
+                false #( || <#ty as ::cqrs::DeserializableEvent>::is_deserializable(
+                    event_type,
+                    event_version,
+                ) )*
+            }
+
+            fn deserialize_event<'de, __D>(
+                event_type: &str,
+                event_version: ::cqrs::EventVersion,
+                deserializer: __D,
+            ) -> ::core::result::Result<::core::option::Option<Self>, __D::Error>
+            where
+                __D: ::cqrs::private::serde::Deserializer<'de>,
+            {
+                #( if <#ty as ::cqrs::DeserializableEvent>::is_deserializable(
+                    event_type,
+                    event_version,
+                ) {
+                    return <#ty as ::cqrs::DeserializableEvent>::deserialize_event(
+                        event_type,
+                        event_version,
+                        deserializer,
+                    )
+                    .map(|ev| ev.map(|ev| #variant));
+                } )*
+                ::core::result::Result::Ok(::core::option::Option::None)
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod spec {
+    use super::*;
+
+    #[test]
+    fn derives_struct_impl() {
+        let input = syn::parse_quote! {
+            #[event(name = "event", version = 1)]
+            struct Event;
+        };
+
+        let output = quote! {
+            #[automatically_derived]
+            impl ::cqrs::DeserializableEvent for Event
+            where
+                Self: ::cqrs::StaticTypedEvent
+                    + ::cqrs::StaticVersionedEvent
+                    + for<'de> ::cqrs::private::serde::Deserialize<'de>
+            {
+                #[inline]
+                fn is_deserializable(
+                    event_type: &str,
+                    event_version: ::cqrs::EventVersion,
+                ) -> bool {
+                    event_type == <Self as ::cqrs::StaticTypedEvent>::EVENT_TYPE
+                        && event_version == <Self as ::cqrs::StaticVersionedEvent>::EVENT_VERSION
+                }
+
+                fn deserialize_event<'de, __D>(
+                    event_type: &str,
+                    event_version: ::cqrs::EventVersion,
+                    deserializer: __D,
+                ) -> ::core::result::Result<::core::option::Option<Self>, __D::Error>
+                where
+                    __D: ::cqrs::private::serde::Deserializer<'de>,
+                {
+                    if !<Self as ::cqrs::DeserializableEvent>::is_deserializable(
+                        event_type,
+                        event_version,
+                    ) {
+                        return ::core::result::Result::Ok(::core::option::Option::None);
+                    }
+                    <Self as ::cqrs::private::serde::Deserialize<'de>>::deserialize(deserializer)
+                        .map(::core::option::Option::Some)
+                }
+            }
+        };
+
+        assert_eq!(derive(input).unwrap().to_string(), output.to_string())
+    }
+
+    #[test]
+    fn derives_enum_impl() {
+        let input = syn::parse_quote! {
+            enum Event {
+                Event1(Event1),
+                Event2 {
+                    other_event: Event2,
+                },
+            }
+        };
+
+        let output = quote! {
+            #[automatically_derived]
+            impl ::cqrs::DeserializableEvent for Event
+            where
+                Event1: ::cqrs::DeserializableEvent,
+                Event2: ::cqrs::DeserializableEvent
+            {
+                fn is_deserializable(
+                    event_type: &str,
+                    event_version: ::cqrs::EventVersion,
+                ) -> bool {
+                    false
+                        || <Event1 as ::cqrs::DeserializableEvent>::is_deserializable(
+                            event_type,
+                            event_version,
+                        )
+                        || <Event2 as ::cqrs::DeserializableEvent>::is_deserializable(
+                            event_type,
+                            event_version,
+                        )
+                }
+
+                fn deserialize_event<'de, __D>(
+                    event_type: &str,
+                    event_version: ::cqrs::EventVersion,
+                    deserializer: __D,
+                ) -> ::core::result::Result<::core::option::Option<Self>, __D::Error>
+                where
+                    __D: ::cqrs::private::serde::Deserializer<'de>,
+                {
+                    if <Event1 as ::cqrs::DeserializableEvent>::is_deserializable(
+                        event_type,
+                        event_version,
+                    ) {
+                        return <Event1 as ::cqrs::DeserializableEvent>::deserialize_event(
+                            event_type,
+                            event_version,
+                            deserializer,
+                        )
+                        .map(|ev| ev.map(|ev| Self::Event1(ev)));
+                    }
+                    if <Event2 as ::cqrs::DeserializableEvent>::is_deserializable(
+                        event_type,
+                        event_version,
+                    ) {
+                        return <Event2 as ::cqrs::DeserializableEvent>::deserialize_event(
+                            event_type,
+                            event_version,
+                            deserializer,
+                        )
+                        .map(|ev| ev.map(|ev| Self::Event2 { other_event: ev }));
+                    }
+                    ::core::result::Result::Ok(::core::option::Option::None)
+                }
+            }
+        };
+
+        assert_eq!(derive(input).unwrap().to_string(), output.to_string())
+    }
+}
@@ -2,11 +2,15 @@
 //! (e.g. [`cqrs::VersionedEvent`], etc).
 
 mod aggregate_event;
+mod deserializable_event;
 mod event;
 mod registered_event;
+mod serializable_event;
 mod typed_event;
 mod versioned_event;
 
+use std::num::NonZeroU8;
+
 use proc_macro2::TokenStream;
 use quote::quote;
 use syn::Result;
@@ -15,8 +19,10 @@ use synstructure::Structure;
 use crate::util;
 
 pub use aggregate_event::derive as aggregate_event_derive;
+pub use deserializable_event::derive as deserializable_event_derive;
 pub use event::derive as event_derive;
 pub use registered_event::derive as registered_event_derive;
+pub use serializable_event::derive as serializable_event_derive;
 pub use versioned_event::derive as versioned_event_derive;
 
 /// Name of the attribute, used for this family of derives.
@@ -71,3 +77,17 @@ fn render_enum_proxy_method_calls(
         }
     }))
 }
+
+/// Parses version of [`cqrs::Event`] from `#[event(...)]` attribute, if it's
+/// specified.
+fn parse_event_version_opt(meta: &util::Meta) -> Result<Option<u8>> {
+    util::parse_lit_opt::<syn::LitInt>(
+        meta,
+        "version",
+        VALID_STRUCT_ARGS,
+        ATTR_NAME,
+        "= <non-zero unsigned integer>",
+    )?
+    .map(|lit| Ok(lit.base10_parse::<NonZeroU8>()?.get()))
+    .transpose()
+}
@@ -0,0 +1,174 @@
+//! Codegen for [`cqrs::SerializableEvent`].
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse_quote, Result};
+use synstructure::Structure;
+
+use crate::util;
+
+/// Name of the derived trait.
+const TRAIT_NAME: &str = "SerializableEvent";
+
+/// Implements [`crate::serializable_event_derive`] macro expansion.
+pub fn derive(input: syn::DeriveInput) -> Result<TokenStream> {
+    util::derive(input, TRAIT_NAME, derive_struct, derive_enum)
+}
+
+/// Implements [`crate::serializable_event_derive`] macro expansion for
+/// structs.
+fn derive_struct(input: syn::DeriveInput) -> Result<TokenStream> {
+    let type_name = &input.ident;
+    let (impl_gens, ty_gens, where_clause) = input.generics.split_for_impl();
+
+    let mut where_clause = where_clause.cloned().unwrap_or_else(|| parse_quote!(where));
+    where_clause
+        .predicates
+        .push(parse_quote!(Self: ::cqrs::private::serde::Serialize));
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl#impl_gens ::cqrs::SerializableEvent for #type_name#ty_gens
+        #where_clause
+        {
+            #[inline]
+            fn serialize_event<__S>(
+                &self,
+                serializer: __S,
+            ) -> ::core::result::Result<__S::Ok, __S::Error>
+            where
+                __S: ::cqrs::private::serde::Serializer,
+            {
+                ::cqrs::private::serde::Serialize::serialize(self, serializer)
+            }
+        }
+    })
+}
+
+/// Implements [`crate::serializable_event_derive`] macro expansion for enums
+/// via [`synstructure`].
+fn derive_enum(input: syn::DeriveInput) -> Result<TokenStream> {
+    util::assert_valid_attr_args_used(&input.attrs, super::ATTR_NAME, super::VALID_ENUM_ARGS)?;
+
+    let structure = Structure::try_new(&input)?;
+    util::assert_all_enum_variants_have_single_field(&structure, TRAIT_NAME)?;
+
+    let syn::Data::Enum(data) = input.data else {
+        unreachable!("already checked")
+    };
+
+    let type_name = &input.ident;
+
+    let mut where_clause = input
+        .generics
+        .where_clause
+        .clone()
+        .unwrap_or_else(|| parse_quote!(where));
+    for v in &data.variants {
+        let ty = &v.fields.iter().next().expect("already checked").ty;
+        where_clause
+            .predicates
+            .push(parse_quote!(#ty: ::cqrs::SerializableEvent));
+    }
+
+    let variant = data.variants.iter().map(|v| {
+        let ident = &v.ident;
+        let field = &v.fields.iter().next().expect("already checked");
+        if let Some(field_ident) = &field.ident {
+            quote! { Self::#ident { #field_ident: ref ev } => ev.serialize_event(serializer) }
+        } else {
+            quote! { Self::#ident(ref ev) => ev.serialize_event(serializer) }
+        }
+    });
+
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::cqrs::SerializableEvent for #type_name #ty_generics
+        #where_clause
+        {
+            fn serialize_event<__S>(
+                &self,
+                serializer: __S,
+            ) -> ::core::result::Result<__S::Ok, __S::Error>
+            where
+                __S: ::cqrs::private::serde::Serializer,
+            {
+                match *self {
+                    #( #variant, )*
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod spec {
+    use super::*;
+
+    #[test]
+    fn derives_struct_impl() {
+        let input = syn::parse_quote! {
+            #[event(name = "event", version = 1)]
+            struct Event;
+        };
+
+        let output = quote! {
+            #[automatically_derived]
+            impl ::cqrs::SerializableEvent for Event
+            where
+                Self: ::cqrs::private::serde::Serialize
+            {
+                #[inline]
+                fn serialize_event<__S>(
+                    &self,
+                    serializer: __S,
+                ) -> ::core::result::Result<__S::Ok, __S::Error>
+                where
+                    __S: ::cqrs::private::serde::Serializer,
+                {
+                    ::cqrs::private::serde::Serialize::serialize(self, serializer)
+                }
+            }
+        };
+
+        assert_eq!(derive(input).unwrap().to_string(), output.to_string())
+    }
+
+    #[test]
+    fn derives_enum_impl() {
+        let input = syn::parse_quote! {
+            enum Event {
+                Event1(Event1),
+                Event2 {
+                    other_event: Event2,
+                },
+            }
+        };
+
+        let output = quote! {
+            #[automatically_derived]
+            impl ::cqrs::SerializableEvent for Event
+            where
+                Event1: ::cqrs::SerializableEvent,
+                Event2: ::cqrs::SerializableEvent
+            {
+                fn serialize_event<__S>(
+                    &self,
+                    serializer: __S,
+                ) -> ::core::result::Result<__S::Ok, __S::Error>
+                where
+                    __S: ::cqrs::private::serde::Serializer,
+                {
+                    match *self {
+                        Self::Event1(ref ev) => ev.serialize_event(serializer),
+                        Self::Event2 { other_event: ref ev } => ev.serialize_event(serializer),
+                    }
+                }
+            }
+        };
+
+        assert_eq!(derive(input).unwrap().to_string(), output.to_string())
+    }
+}
@@ -19,6 +19,14 @@ pub fn derive(input: syn::DeriveInput) -> Result<TokenStream> {
 /// Implements `cqrs::TypedEvent` part of [`crate::event_derive`] macro
 /// expansion for structs.
 fn derive_struct(input: syn::DeriveInput) -> Result<TokenStream> {
+    let meta = util::get_nested_meta(&input.attrs, super::ATTR_NAME)?;
+    let version = match super::parse_event_version_opt(&meta)? {
+        Some(ver) => quote! {
+            ::core::option::Option::Some(unsafe { ::cqrs::EventVersion::new_unchecked(#ver) })
+        },
+        None => quote!(::core::option::Option::None),
+    };
+
     let type_name = &input.ident;
     let (impl_gens, ty_gens, where_clause) = input.generics.split_for_impl();
 
@@ -28,6 +36,7 @@ fn derive_struct(input: syn::DeriveInput) -> Result<TokenStream> {
         .push(parse_quote!(Self: ::cqrs::StaticTypedEvent));
 
     let const_doc = format!("Type names of [`{type_name}`] events.");
+    let versions_doc = format!("Versions of [`{type_name}`] events.");
 
     Ok(quote! {
         #[automatically_derived]
@@ -36,6 +45,11 @@ fn derive_struct(input: syn::DeriveInput) -> Result<TokenStream> {
             const EVENT_TYPES: &'static [::cqrs::EventType] = &[
                 <Self as ::cqrs::StaticTypedEvent>::EVENT_TYPE
             ];
+
+            #[doc = #versions_doc]
+            const EVENT_VERSIONS: &'static [::core::option::Option<::cqrs::EventVersion>] = &[
+                #version
+            ];
         }
     })
 }
@@ -93,6 +107,7 @@ fn derive_enum(input: syn::DeriveInput) -> Result<TokenStream> {
 
     let type_name = &input.ident;
     let const_doc = format!("Type names of [`{type_name}`] events.");
+    let versions_doc = format!("Versions of [`{type_name}`] events.");
 
     let mut where_clause = input
         .generics
@@ -107,48 +122,108 @@ fn derive_enum(input: syn::DeriveInput) -> Result<TokenStream> {
 
     let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
 
-    let subtypes = types
-        .iter()
-        .map(|ty| quote! { <#ty as ::cqrs::TypedEvent>::EVENT_TYPES })
-        .collect::<Vec<_>>();
-
-    let len = quote! {
-        0 #(+ #subtypes.len())*
+    let event_types = render_concat_consts(
+        &types,
+        &quote!(EVENT_TYPES),
+        &quote!(::cqrs::EventType),
+        &quote!(""),
+    );
+    let event_versions = render_concat_consts(
+        &types,
+        &quote!(EVENT_VERSIONS),
+        &quote!(::core::option::Option<::cqrs::EventVersion>),
+        &quote!(::core::option::Option::None),
+    );
+
+    // Generic enums cannot be named here, so they're checked only once their
+    // `EVENT_TYPES` are used.
+    let check = if input.generics.params.is_empty() {
+        quote! {
+            const _: &[::cqrs::EventType] = <#type_name as ::cqrs::TypedEvent>::EVENT_TYPES;
+        }
+    } else {
+        quote!()
     };
 
     Ok(quote! {
         #[automatically_derived]
         impl#impl_generics ::cqrs::TypedEvent for #type_name#ty_generics #where_clause {
             #[doc = #const_doc]
-            const EVENT_TYPES: &'static [::cqrs::EventType] =
-                ::cqrs::private::slice_arr(
-                    &const {
-                        const __LEN: usize = 128;
-                        if #len > __LEN {
-                            panic!("`cqrs::TypedEvent::EVENT_TYPES` limit \
-                                    reached: 128");
-                        }
-
-                        let mut out = [""; __LEN];
-                        let mut len = 0;
-
-                        #({
-                            let mut i = 0;
-                            while i < #subtypes.len() {
-                                out[len] = #subtypes[i];
-                                i += 1;
-                                len += 1;
-                            }
-                        })*
-
-                        out
-                    },
-                    #len,
+            const EVENT_TYPES: &'static [::cqrs::EventType] = {
+                let types = #event_types;
+                ::cqrs::private::assert_unique_event_types(
+                    types,
+                    <Self as ::cqrs::TypedEvent>::EVENT_VERSIONS,
                 );
+                types
+            };
+
+            #[doc = #versions_doc]
+            const EVENT_VERSIONS: &'static [::core::option::Option<::cqrs::EventVersion>] =
+                #event_versions;
         }
+
+        #check
     })
 }
 
+/// Renders concatenation of the `konst` associated constants of
+/// `cqrs::TypedEvent` implemented by the given `types`, being slices of `ty`
+/// elements.
+///
+/// Every `konst` is padded with `default` up to the length of the according
+/// `EVENT_TYPES`, so the concatenated constants stay in the same order even if
+/// some of them are shorter (like the default `EVENT_VERSIONS`). `default` is
+/// also used to fill the unused elements of the intermediate array.
+fn render_concat_consts(
+    types: &[syn::Path],
+    konst: &TokenStream,
+    ty: &TokenStream,
+    default: &TokenStream,
+) -> TokenStream {
+    let subtypes = types
+        .iter()
+        .map(|path| quote! { <#path as ::cqrs::TypedEvent>::#konst })
+        .collect::<Vec<_>>();
+    let sublens = types
+        .iter()
+        .map(|path| quote! { <#path as ::cqrs::TypedEvent>::EVENT_TYPES.len() })
+        .collect::<Vec<_>>();
+
+    let len = quote! {
+        0 #(+ #sublens)*
+    };
+    let limit_msg = format!("`cqrs::TypedEvent::{konst}` limit reached: 128");
+
+    quote! {
+        ::cqrs::private::slice_arr(
+            &const {
+                const __LEN: usize = 128;
+                if #len > __LEN {
+                    panic!(#limit_msg);
+                }
+
+                let mut out: [#ty; __LEN] = [#default; __LEN];
+                let mut len = 0;
+
+                #({
+                    let mut i = 0;
+                    while i < #sublens {
+                        if i < #subtypes.len() {
+                            out[len] = #subtypes[i];
+                        }
+                        i += 1;
+                        len += 1;
+                    }
+                })*
+
+                out
+            },
+            #len,
+        )
+    }
+}
+
 #[cfg(test)]
 mod spec {
     use super::*;
@@ -156,7 +231,7 @@ mod spec {
     #[test]
     fn derives_struct_impl() {
         let input = syn::parse_quote! {
-            #[event(name = "event")]
+            #[event(name = "event", version = 1)]
             struct Event;
         };
 
@@ -170,6 +245,13 @@ mod spec {
                 const EVENT_TYPES: &'static [::cqrs::EventType] = &[
                     <Self as ::cqrs::StaticTypedEvent>::EVENT_TYPE
                 ];
+
+                #[doc = "Versions of [`Event`] events."]
+                const EVENT_VERSIONS: &'static [::core::option::Option<::cqrs::EventVersion>] = &[
+                    ::core::option::Option::Some(unsafe {
+                        ::cqrs::EventVersion::new_unchecked(1u8)
+                    })
+                ];
             }
         };
 
@@ -196,14 +278,124 @@ mod spec {
             {
                 #[doc = "Type names of [`Event`] events."]
                 const EVENT_TYPES: &'static [::cqrs::EventType] = {
-                    ::cqrs::const_concat_slices!(
-                        ::cqrs::EventType,
-                        <MyEvent as ::cqrs::TypedEvent>::EVENT_TYPES,
-                        <HisEvent as ::cqrs::TypedEvent>::EVENT_TYPES,
-                        <HerEvent as ::cqrs::TypedEvent>::EVENT_TYPES
-                    )
+                    let types = ::cqrs::private::slice_arr(
+                        &const {
+                            const __LEN: usize = 128;
+                            if 0
+                                 + <MyEvent as ::cqrs::TypedEvent>::EVENT_TYPES.len()
+                                 + <HisEvent as ::cqrs::TypedEvent>::EVENT_TYPES.len()
+                                 + <HerEvent as ::cqrs::TypedEvent>::EVENT_TYPES.len()
+                                 > __LEN
+                            {
+                                panic!("`cqrs::TypedEvent::EVENT_TYPES` limit reached: 128");
+                            }
+
+                            let mut out: [::cqrs::EventType; __LEN] = [""; __LEN];
+                            let mut len = 0;
+
+                            {
+                                let mut i = 0;
+                                while i < <MyEvent as ::cqrs::TypedEvent>::EVENT_TYPES.len() {
+                                    if i < <MyEvent as ::cqrs::TypedEvent>::EVENT_TYPES.len() {
+                                        out[len] = <MyEvent as ::cqrs::TypedEvent>::EVENT_TYPES[i];
+                                    }
+                                    i += 1;
+                                    len += 1;
+                                }
+                            }
+                            {
+                                let mut i = 0;
+                                while i < <HisEvent as ::cqrs::TypedEvent>::EVENT_TYPES.len() {
+                                    if i < <HisEvent as ::cqrs::TypedEvent>::EVENT_TYPES.len() {
+                                        out[len] = <HisEvent as ::cqrs::TypedEvent>::EVENT_TYPES[i];
+                                    }
+                                    i += 1;
+                                    len += 1;
+                                }
+                            }
+                            {
+                                let mut i = 0;
+                                while i < <HerEvent as ::cqrs::TypedEvent>::EVENT_TYPES.len() {
+                                    if i < <HerEvent as ::cqrs::TypedEvent>::EVENT_TYPES.len() {
+                                        out[len] = <HerEvent as ::cqrs::TypedEvent>::EVENT_TYPES[i];
+                                    }
+                                    i += 1;
+                                    len += 1;
+                                }
+                            }
+
+                            out
+                        },
+                        0
+                          + <MyEvent as ::cqrs::TypedEvent>::EVENT_TYPES.len()
+                          + <HisEvent as ::cqrs::TypedEvent>::EVENT_TYPES.len()
+                          + <HerEvent as ::cqrs::TypedEvent>::EVENT_TYPES.len(),
+                    );
+                    ::cqrs::private::assert_unique_event_types(
+                        types,
+                        <Self as ::cqrs::TypedEvent>::EVENT_VERSIONS,
+                    );
+                    types
                 };
+
+                #[doc = "Versions of [`Event`] events."]
+                const EVENT_VERSIONS: &'static [::core::option::Option<::cqrs::EventVersion>] =
+                    ::cqrs::private::slice_arr(
+                        &const {
+                            const __LEN: usize = 128;
+                            if 0
+                                 + <MyEvent as ::cqrs::TypedEvent>::EVENT_TYPES.len()
+                                 + <HisEvent as ::cqrs::TypedEvent>::EVENT_TYPES.len()
+                                 + <HerEvent as ::cqrs::TypedEvent>::EVENT_TYPES.len()
+                                 > __LEN
+                            {
+                                panic!("`cqrs::TypedEvent::EVENT_VERSIONS` limit reached: 128");
+                            }
+
+                            let mut out: [::core::option::Option<::cqrs::EventVersion>; __LEN] = [::core::option::Option::None; __LEN];
+                            let mut len = 0;
+
+                            {
+                                let mut i = 0;
+                                while i < <MyEvent as ::cqrs::TypedEvent>::EVENT_TYPES.len() {
+                                    if i < <MyEvent as ::cqrs::TypedEvent>::EVENT_VERSIONS.len() {
+                                        out[len] = <MyEvent as ::cqrs::TypedEvent>::EVENT_VERSIONS[i];
+                                    }
+                                    i += 1;
+                                    len += 1;
+                                }
+                            }
+                            {
+                                let mut i = 0;
+                                while i < <HisEvent as ::cqrs::TypedEvent>::EVENT_TYPES.len() {
+                                    if i < <HisEvent as ::cqrs::TypedEvent>::EVENT_VERSIONS.len() {
+                                        out[len] = <HisEvent as ::cqrs::TypedEvent>::EVENT_VERSIONS[i];
+                                    }
+                                    i += 1;
+                                    len += 1;
+                                }
+                            }
+                            {
+                                let mut i = 0;
+                                while i < <HerEvent as ::cqrs::TypedEvent>::EVENT_TYPES.len() {
+                                    if i < <HerEvent as ::cqrs::TypedEvent>::EVENT_VERSIONS.len() {
+                                        out[len] = <HerEvent as ::cqrs::TypedEvent>::EVENT_VERSIONS[i];
+                                    }
+                                    i += 1;
+                                    len += 1;
+                                }
+                            }
+
+                            out
+                        },
+                        0
+                          + <MyEvent as ::cqrs::TypedEvent>::EVENT_TYPES.len()
+                          + <HisEvent as ::cqrs::TypedEvent>::EVENT_TYPES.len()
+                          + <HerEvent as ::cqrs::TypedEvent>::EVENT_TYPES.len(),
+                    );
             }
+
+            const _: &[::cqrs::EventType] = <Event as ::cqrs::TypedEvent>::EVENT_TYPES;
         };
 
         assert_eq!(derive(input).unwrap().to_string(), output.to_string())
@@ -18,15 +18,32 @@ pub fn derive(input: syn::DeriveInput) -> Result<TokenStream> {
     util::derive(input, TRAIT_NAME, derive_struct, derive_enum)
 }
 
-/// Reports error if [`crate::event_sourced_derive`] macro applied to structs.
+/// Implements [`crate::event_sourced_derive`] macro expansion for structs,
+/// routing each event specified in `#[event_sourced(on(...))]` attribute to
+/// the named method of the struct.
 fn derive_struct(input: syn::DeriveInput) -> Result<TokenStream> {
-    match input.data {
-        syn::Data::Struct(data) => Err(Error::new(
-            data.struct_token.span(),
-            format!("Structs are not supported for deriving {}", TRAIT_NAME),
-        )),
-        _ => unreachable!(),
-    }
+    let meta = util::get_nested_meta(&input.attrs, ATTR_NAME)?;
+
+    let handlers = parse_event_sourced_handlers(&meta)?;
+
+    let type_name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let impls = handlers.iter().map(|(ev, method)| {
+        quote! {
+            #[automatically_derived]
+            impl#impl_generics ::cqrs::EventSourced<#ev> for #type_name#ty_generics #where_clause {
+                #[inline]
+                fn apply(&mut self, ev: &#ev) {
+                    self.#method(ev)
+                }
+            }
+        }
+    });
+
+    Ok(quote! {
+        #(#impls)*
+    })
 }
 
 /// Implements [`crate::event_sourced_derive`] macro expansion for enums
@@ -92,10 +109,76 @@ fn parse_event_sourced_aggregate(meta: &util::Meta) -> Result<String> {
     Ok(lit.value())
 }
 
+/// Parses events and the methods applying them from
+/// `#[event_sourced(on(...))]` attribute.
+fn parse_event_sourced_handlers(meta: &util::Meta) -> Result<Vec<(syn::Path, syn::Ident)>> {
+    let fmt = "(Event = \"method\", ...)";
+    let nested = util::parse_nested_opt(meta, "on", &["on"], ATTR_NAME, fmt)?
+        .filter(|nested| !nested.is_empty())
+        .ok_or_else(|| {
+            Error::new(
+                proc_macro2::Span::call_site(),
+                format!("Expected to have #[{}(on{})] attribute", ATTR_NAME, fmt),
+            )
+        })?;
+
+    nested
+        .iter()
+        .map(|m| match m {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                path,
+                value:
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(method),
+                        ..
+                    }),
+                ..
+            }) => Ok((path.clone(), method.parse()?)),
+            _ => Err(Error::new(
+                m.span(),
+                format!(
+                    "Wrong attribute format; expected #[{}(on{})]",
+                    ATTR_NAME, fmt
+                ),
+            )),
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod spec {
     use super::*;
 
+    #[test]
+    fn derives_struct_impl() {
+        let input = syn::parse_quote! {
+            #[event_sourced(on(Event1 = "apply_event1", events::Event2 = "apply_event2"))]
+            struct Aggregate {
+                id: i32,
+            }
+        };
+
+        let output = quote! {
+            #[automatically_derived]
+            impl ::cqrs::EventSourced<Event1> for Aggregate {
+                #[inline]
+                fn apply(&mut self, ev: &Event1) {
+                    self.apply_event1(ev)
+                }
+            }
+
+            #[automatically_derived]
+            impl ::cqrs::EventSourced<events::Event2> for Aggregate {
+                #[inline]
+                fn apply(&mut self, ev: &events::Event2) {
+                    self.apply_event2(ev)
+                }
+            }
+        };
+
+        assert_eq!(derive(input).unwrap().to_string(), output.to_string())
+    }
+
     #[test]
     fn derives_enum_impl() {
         let input = syn::parse_quote! {
@@ -1,7 +1,9 @@
 mod aggregate;
 mod command;
+mod command_handler;
 mod event;
 mod event_sourced;
+mod upcaster;
 mod util;
 
 use proc_macro2::TokenStream;
@@ -15,6 +17,9 @@ macro_rules! export {
     ($mod:ident::$fn:ident as $export:ident) => {
         pub use $mod::$fn as $export;
     };
+    (attribute $mod:ident::$fn:ident as $export:ident) => {
+        pub use $mod::$fn as $export;
+    };
 }
 
 #[cfg(feature = "watt")]
@@ -29,6 +34,12 @@ macro_rules! export {
             expand(syn::parse2(input), $mod::$fn)
         }
     };
+    (attribute $mod:ident::$fn:ident as $export:ident) => {
+        #[no_mangle]
+        pub extern "C" fn $export(args: TokenStream, input: TokenStream) -> TokenStream {
+            expand_attribute(args, syn::parse2(input), $mod::$fn)
+        }
+    };
 }
 
 /// Performs expansion of a given proc macro implementation.
@@ -42,10 +53,27 @@ pub fn expand<TS: From<TokenStream>>(
     }
 }
 
+/// Performs expansion of a given attribute proc macro implementation, applied
+/// to an `impl` block.
+pub fn expand_attribute<TS: From<TokenStream>>(
+    args: TokenStream,
+    input: syn::Result<syn::ItemImpl>,
+    macro_impl: fn(TokenStream, syn::ItemImpl) -> syn::Result<TokenStream>,
+) -> TS {
+    match input.and_then(|input| macro_impl(args, input)) {
+        Ok(res) => res.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
 export!(aggregate::derive as aggregate_derive);
 export!(command::derive as command_derive);
+export!(attribute command_handler::attribute as command_handler_attribute);
 export!(event::aggregate_event_derive);
+export!(event::deserializable_event_derive);
 export!(event::event_derive);
 export!(event::registered_event_derive);
+export!(event::serializable_event_derive);
 export!(event::versioned_event_derive);
 export!(event_sourced::derive as event_sourced_derive);
+export!(attribute upcaster::attribute as upcaster_attribute);
@@ -0,0 +1,244 @@
+//! Codegen for [`cqrs::UpcastableEvent`].
+
+use std::num::NonZeroU8;
+
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens as _};
+use syn::{spanned::Spanned as _, Error, Result};
+
+use crate::util;
+
+/// Name of the implemented trait.
+const TRAIT_NAME: &str = "UpcastableEvent";
+
+/// Name of the attribute, used by [`cqrs::UpcastableEvent`].
+const ATTR_NAME: &str = "upcaster";
+
+/// Names of the `#[upcaster(...)]` attribute's arguments, used on methods by
+/// [`cqrs::UpcastableEvent`].
+const VALID_METHOD_ARGS: &[&str] = &["from"];
+
+/// Implements [`crate::upcaster_attribute`] macro expansion.
+pub fn attribute(args: TokenStream, mut input: syn::ItemImpl) -> Result<TokenStream> {
+    if !args.is_empty() {
+        return Err(Error::new(
+            args.span(),
+            format!("#[{}] attribute accepts no arguments", ATTR_NAME),
+        ));
+    }
+    if let Some((_, path, _)) = &input.trait_ {
+        return Err(Error::new(
+            path.span(),
+            format!(
+                "Trait implementations are not supported for implementing {}",
+                TRAIT_NAME,
+            ),
+        ));
+    }
+
+    let mut upcasters = Vec::new();
+    for item in &mut input.items {
+        if let syn::ImplItem::Fn(method) = item {
+            let meta = util::find_nested_meta(&method.attrs, ATTR_NAME)?.ok_or_else(|| {
+                Error::new(
+                    method.sig.span(),
+                    format!(
+                        "Expected #[{}(from = ...)] attribute on the method",
+                        ATTR_NAME
+                    ),
+                )
+            })?;
+            method.attrs.retain(|attr| !attr.path().is_ident(ATTR_NAME));
+            upcasters.push(Upcaster::parse(method, &meta)?);
+        }
+    }
+
+    let (payload, err) = match upcasters.first() {
+        Some(upcaster) => (&upcaster.payload, &upcaster.err),
+        None => {
+            return Err(Error::new(
+                input.self_ty.span(),
+                format!("Expected at least one #[{}(from = ...)] method", ATTR_NAME),
+            ))
+        }
+    };
+    for upcaster in &upcasters[1..] {
+        let mismatched = if !is_same_type(&upcaster.payload, payload) {
+            Some(&upcaster.payload)
+        } else if !is_same_type(&upcaster.err, err) {
+            Some(&upcaster.err)
+        } else {
+            None
+        };
+        if let Some(ty) = mismatched {
+            return Err(Error::new(
+                ty.span(),
+                "All upcasters of an event should have the same payload and error types",
+            ));
+        }
+    }
+
+    let self_ty = &input.self_ty;
+    let (impl_generics, _, where_clause) = input.generics.split_for_impl();
+
+    let registrations = upcasters.iter().map(|Upcaster { method, from, .. }| {
+        quote! {
+            upcasters.register(
+                <Self as ::cqrs::StaticTypedEvent>::EVENT_TYPE,
+                unsafe { ::cqrs::EventVersion::new_unchecked(#from) },
+                Self::#method,
+            );
+        }
+    });
+
+    Ok(quote! {
+        #input
+
+        #[automatically_derived]
+        impl#impl_generics ::cqrs::UpcastableEvent<#payload, #err> for #self_ty #where_clause {
+            fn register_upcasters(upcasters: &mut ::cqrs::Upcasters<#payload, #err>) {
+                #( #registrations )*
+            }
+        }
+    })
+}
+
+/// Signature of a method upcasting a raw payload of a [`cqrs::Event`] to its
+/// next [`cqrs::EventVersion`].
+struct Upcaster {
+    /// Name of the method.
+    method: syn::Ident,
+
+    /// [`cqrs::EventVersion`] of the payloads accepted by the method.
+    from: u8,
+
+    /// Type of the raw payload.
+    payload: syn::Type,
+
+    /// Type of the upcasting error.
+    err: syn::Type,
+}
+
+impl Upcaster {
+    /// Parses [`Upcaster`] out of the given method and its
+    /// `#[upcaster(...)]` attribute.
+    fn parse(method: &syn::ImplItemFn, meta: &util::Meta) -> Result<Self> {
+        let sig = &method.sig;
+        let expected = "expected `fn(payload: Payload) -> Result<Payload, Err>`";
+
+        if sig.asyncness.is_some() || !sig.generics.params.is_empty() || sig.inputs.len() != 1 {
+            return Err(Error::new(sig.span(), expected));
+        }
+        let payload = match &sig.inputs[0] {
+            syn::FnArg::Typed(arg) => (*arg.ty).clone(),
+            syn::FnArg::Receiver(recv) => return Err(Error::new(recv.span(), expected)),
+        };
+        let (ok, err) = match &sig.output {
+            syn::ReturnType::Type(_, ty) => util::parse_result_type(ty),
+            syn::ReturnType::Default => None,
+        }
+        .ok_or_else(|| Error::new(sig.output.span(), expected))?;
+        if !is_same_type(&ok, &payload) {
+            return Err(Error::new(ok.span(), expected));
+        }
+
+        let from = util::parse_lit::<syn::LitInt>(
+            meta,
+            "from",
+            VALID_METHOD_ARGS,
+            ATTR_NAME,
+            "= <non-zero unsigned integer>",
+        )?
+        .base10_parse::<NonZeroU8>()?
+        .get();
+
+        Ok(Self {
+            method: sig.ident.clone(),
+            from,
+            payload,
+            err,
+        })
+    }
+}
+
+/// Indicates whether the given types are spelled the same way.
+fn is_same_type(a: &syn::Type, b: &syn::Type) -> bool {
+    a.to_token_stream().to_string() == b.to_token_stream().to_string()
+}
+
+#[cfg(test)]
+mod spec {
+    use super::*;
+
+    #[test]
+    fn implements_for_all_methods() {
+        let input = syn::parse_quote! {
+            impl Event {
+                #[upcaster(from = 1)]
+                fn from_v1(payload: Value) -> Result<Value, Error> {
+                    unimplemented!()
+                }
+
+                #[upcaster(from = 2)]
+                fn from_v2(payload: Value) -> Result<Value, Error> {
+                    unimplemented!()
+                }
+            }
+        };
+
+        let output = quote! {
+            impl Event {
+                fn from_v1(payload: Value) -> Result<Value, Error> {
+                    unimplemented!()
+                }
+
+                fn from_v2(payload: Value) -> Result<Value, Error> {
+                    unimplemented!()
+                }
+            }
+
+            #[automatically_derived]
+            impl ::cqrs::UpcastableEvent<Value, Error> for Event {
+                fn register_upcasters(upcasters: &mut ::cqrs::Upcasters<Value, Error>) {
+                    upcasters.register(
+                        <Self as ::cqrs::StaticTypedEvent>::EVENT_TYPE,
+                        unsafe { ::cqrs::EventVersion::new_unchecked(1u8) },
+                        Self::from_v1,
+                    );
+                    upcasters.register(
+                        <Self as ::cqrs::StaticTypedEvent>::EVENT_TYPE,
+                        unsafe { ::cqrs::EventVersion::new_unchecked(2u8) },
+                        Self::from_v2,
+                    );
+                }
+            }
+        };
+
+        assert_eq!(
+            attribute(TokenStream::new(), input).unwrap().to_string(),
+            output.to_string(),
+        );
+    }
+    #[test]
+    fn errors_on_method_without_attribute() {
+        let input = syn::parse_quote! {
+            impl Event {
+                #[upcaster(from = 1)]
+                fn from_v1(payload: Value) -> Result<Value, Error> {
+                    unimplemented!()
+                }
+
+                fn helper(payload: Value) -> Result<Value, Error> {
+                    unimplemented!()
+                }
+            }
+        };
+
+        let err = attribute(TokenStream::new(), input).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "Expected #[upcaster(from = ...)] attribute on the method",
+        );
+    }
+}
@@ -235,14 +235,32 @@ pub(crate) fn parse_lit<'meta, T>(
 where
     &'meta syn::Lit: TryInto<&'meta T>,
 {
-    let meta = find_arg(meta, arg, valid_args, attr, fmt)?;
-
-    let meta = meta.ok_or_else(|| {
+    parse_lit_opt(meta, arg, valid_args, attr, fmt)?.ok_or_else(|| {
         Error::new(
             proc_macro2::Span::call_site(),
             format!("Expected to have #[{}({}{})] attribute", attr, arg, fmt,),
         )
-    })?;
+    })
+}
+
+/// Parses specified inner argument `arg` from the given `#[<attr>(...)]` outer
+/// attribute, converting it to a type `T` (using [`util::TryInto`])
+/// if possible.
+/// Returns [`None`] if the argument is not specified.
+pub(crate) fn parse_lit_opt<'meta, T>(
+    meta: &'meta Meta,
+    arg: &str,
+    valid_args: &[&str],
+    attr: &str,
+    fmt: &str,
+) -> Result<Option<&'meta T>>
+where
+    &'meta syn::Lit: TryInto<&'meta T>,
+{
+    let meta = match find_arg(meta, arg, valid_args, attr, fmt)? {
+        Some(m) => m,
+        None => return Ok(None),
+    };
 
     let lit = match meta {
         syn::Meta::NameValue(syn::MetaNameValue {
@@ -253,9 +271,52 @@ where
     };
     let span = lit.span();
     lit.try_into()
+        .map(Some)
         .ok_or_else(move || wrong_format(span, attr, arg, fmt))
 }
 
+/// Parses specified inner argument `arg` from the given `#[<attr>(...)]` outer
+/// attribute, as a list of nested arguments (`arg(...)`).
+/// Returns [`None`] if the argument is not specified.
+pub(crate) fn parse_nested_opt(
+    meta: &Meta,
+    arg: &str,
+    valid_args: &[&str],
+    attr: &str,
+    fmt: &str,
+) -> Result<Option<Meta>> {
+    let meta = match find_arg(meta, arg, valid_args, attr, fmt)? {
+        Some(m) => m,
+        None => return Ok(None),
+    };
+
+    match meta {
+        syn::Meta::List(list) => list.parse_args_with(Meta::parse_terminated).map(Some),
+        _ => Err(wrong_format(meta, attr, arg, fmt)),
+    }
+}
+
+/// Parses `Ok` and `Err` types out of the given `Result<Ok, Err>` type.
+pub(crate) fn parse_result_type(ty: &syn::Type) -> Option<(syn::Type, syn::Type)> {
+    let segment = match ty {
+        syn::Type::Path(ty) if ty.qself.is_none() => ty.path.segments.last()?,
+        _ => return None,
+    };
+    if segment.ident != "Result" {
+        return None;
+    }
+    let args = match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) if args.args.len() == 2 => &args.args,
+        _ => return None,
+    };
+    match (&args[0], &args[1]) {
+        (syn::GenericArgument::Type(ok), syn::GenericArgument::Type(err)) => {
+            Some((ok.clone(), err.clone()))
+        }
+        _ => None,
+    }
+}
+
 /// Finds specified inner argument `arg` from `#[<attr>(...)]` outer attribute.
 fn find_arg<'meta>(
     meta: &'meta Meta,
@@ -18,6 +18,26 @@ macro_rules! import {
     };
 }
 
+#[cfg(all(not(feature = "watt"), feature = "no-watt"))]
+/// Imports attribute proc macro from implementation crate as is.
+macro_rules! import_attribute {
+    ($args:expr, $input:expr, $fn:ident) => {
+        cqrs_codegen_impl::expand_attribute(
+            $args.into(),
+            syn::parse($input),
+            cqrs_codegen_impl::$fn,
+        )
+    };
+}
+
+#[cfg(all(feature = "watt", not(feature = "no-watt")))]
+/// Imports attribute proc macro from implementation crate via WASM ABI.
+macro_rules! import_attribute {
+    ($args:expr, $input:expr, $fn:ident) => {
+        wasm::MACRO.proc_macro_attribute(stringify!($fn), $args, $input)
+    };
+}
+
 #[cfg(all(feature = "watt", not(feature = "no-watt")))]
 mod wasm {
     /// Generated WASM of implementation crate.
@@ -37,6 +57,10 @@ mod wasm {
 /// Any field can be explicitly specified as an id field
 /// with `#[aggregate(id)]` attribute.
 ///
+/// Version of the aggregate's snapshots format may be specified with
+/// `#[aggregate(snapshot_version = N)]` attribute (defaults to `1`).
+/// Snapshots of other versions are discarded when loading the aggregate.
+///
 /// # Examples
 /// ```
 /// # use cqrs_codegen::Aggregate;
@@ -59,6 +83,13 @@ mod wasm {
 /// #[derive(Aggregate, Default)]
 /// #[aggregate(name = "tuple.struct.aggregate")]
 /// struct TupleStructAggregate(#[aggregate(id)] i32, String);
+///
+/// #[derive(Aggregate, Default)]
+/// #[aggregate(name = "versioned.snapshot.aggregate", snapshot_version = 2)]
+/// struct VersionedSnapshotAggregate {
+///     id: i32,
+///     value: String,
+/// }
 /// ```
 #[proc_macro_derive(Aggregate, attributes(aggregate))]
 pub fn aggregate_derive(input: TokenStream) -> TokenStream {
@@ -149,6 +180,143 @@ pub fn command_derive(input: TokenStream) -> TokenStream {
     import!(input, command_derive)
 }
 
+/// Implements [`cqrs::CommandHandler`] for an aggregate out of the methods of
+/// its inherent `impl` block.
+///
+/// Every method of the `impl` block is treated as a handler of a single
+/// command, so it should have the following signature:
+/// ```text
+/// [async] fn name(&self, cmd: Command[, ctx: &Context]) -> Result<Ok, Err>
+/// ```
+///
+/// The associated types of [`cqrs::CommandHandler`] are inferred from the
+/// signature: `Context` is `()` if the method accepts no context, and
+/// `Event` is the type parameter of the [`cqrs::NumberedEvent`]s in the `Ok`
+/// type (or `()` if the `Ok` type is `()`). If the `Event` type cannot be
+/// inferred, it should be specified with `#[command_handler(event = "...")]`
+/// attribute on the method.
+///
+/// Helper methods, which are not command handlers, should be placed into a
+/// separate `impl` block.
+///
+/// # Examples
+/// ```
+/// # use std::convert::Infallible;
+/// #
+/// # use cqrs::{EventNumber, NumberedEvent};
+/// # use cqrs_codegen::{Aggregate, Command, Event};
+/// #
+/// # #[derive(Aggregate, Default)]
+/// # #[aggregate(name = "user")]
+/// # struct User {
+/// #     id: i32,
+/// # };
+/// #
+/// # #[derive(Command)]
+/// # #[command(aggregate = "User")]
+/// # struct CreateUser;
+/// #
+/// # #[derive(Command)]
+/// # #[command(aggregate = "User")]
+/// # struct RemoveUser;
+/// #
+/// # #[derive(Event)]
+/// # #[event(name = "user.created")]
+/// # struct UserCreated;
+/// #
+/// # struct Clock;
+/// #
+/// #[cqrs_codegen::command_handler]
+/// impl User {
+///     async fn create(
+///         &self,
+///         _: CreateUser,
+///         _: &Clock,
+///     ) -> Result<Vec<NumberedEvent<UserCreated>>, Infallible> {
+///         Ok(vec![NumberedEvent {
+///             num: EventNumber::MIN_VALUE,
+///             data: UserCreated,
+///         }])
+///     }
+///
+///     #[command_handler(event = "()")]
+///     fn remove(&self, _: RemoveUser) -> Result<Vec<()>, &'static str> {
+///         Err("users cannot be removed")
+///     }
+/// }
+///
+/// // Example macro-generated implementation:
+/// # #[cfg(exclude_from_doctest)]
+/// #[async_trait::async_trait(?Send)]
+/// impl cqrs::CommandHandler<CreateUser> for User {
+///     type Context = Clock;
+///     type Event = UserCreated;
+///     type Err = Infallible;
+///     type Ok = Vec<NumberedEvent<UserCreated>>;
+///
+///     async fn handle(&self, cmd: CreateUser, ctx: &Clock) -> Result<Self::Ok, Self::Err> {
+///         self.create(cmd, ctx).await
+///     }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn command_handler(args: TokenStream, input: TokenStream) -> TokenStream {
+    import_attribute!(args, input, command_handler_attribute)
+}
+
+/// Derives [`cqrs::DeserializableEvent`] implementation for structs and enums.
+///
+/// Requires `serde` feature of `cqrs` crate to be enabled.
+///
+/// # Structs
+///
+/// When deriving [`cqrs::DeserializableEvent`] for struct, the struct is
+/// treated as a single distinct event, which payload is deserialized via its
+/// [`serde::Deserialize`] implementation only if the given tag equals to its
+/// [`cqrs::StaticTypedEvent::EVENT_TYPE`] and
+/// [`cqrs::StaticVersionedEvent::EVENT_VERSION`] (so the struct should derive
+/// [`cqrs::Event`] and [`cqrs::VersionedEvent`] too).
+///
+/// # Enums
+///
+/// When deriving [`cqrs::DeserializableEvent`] for enum, the enum is treated as
+/// a sum-type representing a set of possible events.
+///
+/// In practice this means, that [`cqrs::DeserializableEvent`] can only be
+/// derived for an enum when all variants of such enum have exactly one field
+/// (variant can be either a tuple-variant or a struct-variant) and the field
+/// have to implement [`cqrs::DeserializableEvent`] itself.
+///
+/// Generated implementation of [`cqrs::DeserializableEvent::deserialize_event`]
+/// would pick the first variant, which field is able to deserialize the given
+/// tag, and proxy call to it.
+///
+/// # Examples
+/// ```
+/// # use cqrs_codegen::{DeserializableEvent, Event, VersionedEvent};
+/// # use serde::Deserialize;
+/// #
+/// #[derive(Deserialize, DeserializableEvent, Event, VersionedEvent)]
+/// #[event(name = "user.created", version = 1)]
+/// struct UserCreated {
+///     name: String,
+/// }
+///
+/// #[derive(Deserialize, DeserializableEvent, Event, VersionedEvent)]
+/// #[event(name = "user.removed", version = 1)]
+/// struct UserRemoved;
+///
+/// #[derive(DeserializableEvent, Event, VersionedEvent)]
+/// enum UserEvents {
+///     UserCreated(UserCreated),
+///     UserRemoved(UserRemoved),
+/// }
+/// ```
+#[proc_macro_derive(DeserializableEvent)]
+pub fn deserializable_event_derive(input: TokenStream) -> TokenStream {
+    import!(input, deserializable_event_derive)
+}
+
 /// Derives [`cqrs::Event`] implementation for structs and enums.
 ///
 /// # Structs
@@ -195,6 +363,28 @@ pub fn command_derive(input: TokenStream) -> TokenStream {
 /// }
 /// ```
 ///
+/// Variants containing the same [`cqrs::EventType`] of the same
+/// [`cqrs::EventVersion`] are rejected at compile time:
+/// ```compile_fail
+/// # use cqrs_codegen::Event;
+/// #
+/// #[derive(Event)]
+/// #[event(name = "user.created", version = 1)]
+/// struct UserCreated;
+///
+/// #[derive(Event)]
+/// #[event(name = "user.created", version = 1)]
+/// struct UserCreatedAgain;
+///
+/// #[derive(Event)]
+/// enum UserEvents {
+///     UserCreated(UserCreated),
+///     UserCreatedAgain(UserCreatedAgain),
+/// }
+/// #
+/// # let _ = <UserEvents as cqrs::TypedEvent>::EVENT_TYPES;
+/// ```
+///
 /// [`rust-lang/rust#76200`]: https://github.com/rust-lang/rust/issues/76200
 #[proc_macro_derive(Event, attributes(event))]
 pub fn event_derive(input: TokenStream) -> TokenStream {
@@ -245,6 +435,67 @@ pub fn registered_event_derive(input: TokenStream) -> TokenStream {
     import!(input, registered_event_derive)
 }
 
+/// Derives [`cqrs::SerializableEvent`] implementation for structs and enums.
+///
+/// Requires `serde` feature of `cqrs` crate to be enabled.
+///
+/// # Structs
+///
+/// When deriving [`cqrs::SerializableEvent`] for struct, the struct is treated
+/// as a single distinct event, which payload is serialized via its
+/// [`serde::Serialize`] implementation. The struct should implement
+/// [`cqrs::Event`] and [`cqrs::VersionedEvent`] too, as the payload is tagged
+/// with its [`cqrs::EventType`] and [`cqrs::EventVersion`].
+///
+/// # Enums
+///
+/// When deriving [`cqrs::SerializableEvent`] for enum, the enum is treated as
+/// a sum-type representing a set of possible events.
+///
+/// In practice this means, that [`cqrs::SerializableEvent`] can only be
+/// derived for an enum when all variants of such enum have exactly one field
+/// (variant can be either a tuple-variant or a struct-variant) and the field
+/// have to implement [`cqrs::SerializableEvent`] itself.
+///
+/// Generated implementation of [`cqrs::SerializableEvent::serialize_event`]
+/// would match on all variants and proxy calls to each variant's field.
+///
+/// # Examples
+/// ```
+/// # use cqrs::{Event as _, VersionedEvent as _};
+/// # use cqrs_codegen::{Event, SerializableEvent, VersionedEvent};
+/// # use serde::Serialize;
+/// #
+/// #[derive(Event, Serialize, SerializableEvent, VersionedEvent)]
+/// #[event(name = "user.created", version = 1)]
+/// struct UserCreated {
+///     name: String,
+/// }
+///
+/// #[derive(Event, Serialize, SerializableEvent, VersionedEvent)]
+/// #[event(name = "user.removed", version = 1)]
+/// struct UserRemoved;
+///
+/// #[derive(Event, SerializableEvent, VersionedEvent)]
+/// enum UserEvents {
+///     UserCreated(UserCreated),
+///     UserRemoved(UserRemoved),
+/// }
+///
+/// use cqrs::SerializableEvent as _;
+///
+/// let ev = UserEvents::UserCreated(UserCreated { name: "Alice".into() });
+/// let payload = ev.serialize_event(serde_json::value::Serializer).unwrap();
+///
+/// assert_eq!(ev.event_type(), "user.created");
+/// assert_eq!(ev.event_version().into_u8(), 1);
+/// assert_eq!(payload, serde_json::json!({"name": "Alice"}));
+/// ```
+#[proc_macro_derive(SerializableEvent)]
+pub fn serializable_event_derive(input: TokenStream) -> TokenStream {
+    import!(input, serializable_event_derive)
+}
+
 /// Derives [`cqrs::VersionedEvent`] implementation for structs and enums.
 ///
 /// # Structs
@@ -292,10 +543,11 @@ pub fn versioned_event_derive(input: TokenStream) -> TokenStream {
 }
 
 /// Derives [`cqrs::EventSourced`] implementation on [`cqrs::AggregateEvent`]
-/// for a specified [`cqrs::Aggregate`].
+/// for a specified [`cqrs::Aggregate`], or implementations of
+/// [`cqrs::EventSourced`] for an [`cqrs::Aggregate`] struct.
 ///
-/// This derive macro should be applied to enum representing a set of possible
-/// events, but it will generate implementation of [`cqrs::EventSourced`]
+/// When applied to an enum representing a set of possible events, this derive
+/// macro will generate implementation of [`cqrs::EventSourced`]
 /// for the [`cqrs::Aggregate`] type specified via
 /// `#[event_sourced(aggregate = "...")]` attribute.
 ///
@@ -306,8 +558,8 @@ pub fn versioned_event_derive(input: TokenStream) -> TokenStream {
 /// for each field of enum.
 ///
 /// Specifying `#[event_sourced(aggregate = "...")]` attribute is __mandatory__
-/// (and only single such attribute allowed per enum). The attribute is
-/// treated as a type of the aggregate that event is associated with.
+/// for enums (and only single such attribute allowed per enum). The attribute
+/// is treated as a type of the aggregate that event is associated with.
 ///
 /// # Examples
 /// ```
@@ -357,7 +609,114 @@ pub fn versioned_event_derive(input: TokenStream) -> TokenStream {
 ///     }
 /// }
 /// ```
+///
+/// When applied to a struct, the events and the struct's methods applying them
+/// should be specified via `#[event_sourced(on(Event = "method", ...))]`
+/// attribute, so [`cqrs::EventSourced`] is implemented for each of the events
+/// by calling the corresponding method. Combined with the derive on an enum
+/// of the events, this allows to avoid writing [`cqrs::EventSourced`]
+/// implementations by hand at all.
+///
+/// ```
+/// # use cqrs_codegen::{Aggregate, Event, EventSourced};
+/// #
+/// # #[derive(Event)]
+/// # #[event(name = "user.created")]
+/// # struct UserCreated;
+/// #
+/// # #[derive(Event)]
+/// # #[event(name = "user.removed")]
+/// # struct UserRemoved;
+/// #
+/// #[derive(Aggregate, Default, EventSourced)]
+/// #[aggregate(name = "user")]
+/// #[event_sourced(on(UserCreated = "created", UserRemoved = "removed"))]
+/// struct User {
+///     id: i32,
+///     is_removed: bool,
+/// }
+///
+/// impl User {
+///     fn created(&mut self, _: &UserCreated) {}
+///
+///     fn removed(&mut self, _: &UserRemoved) {
+///         self.is_removed = true;
+///     }
+/// }
+///
+/// #[derive(Event, EventSourced)]
+/// #[event_sourced(aggregate = "User")]
+/// enum UserEvents {
+///     UserCreated(UserCreated),
+///     UserRemoved(UserRemoved),
+/// }
+///
+/// // Example macro-generated implementation for the struct:
+/// # #[cfg(exclude_from_doctest)]
+/// impl cqrs::EventSourced<UserCreated> for User {
+///     fn apply(&mut self, ev: &UserCreated) {
+///         self.created(ev)
+///     }
+/// }
+/// ```
 #[proc_macro_derive(EventSourced, attributes(event_sourced))]
 pub fn event_sourced_derive(input: TokenStream) -> TokenStream {
     import!(input, event_sourced_derive)
 }
+
+/// Implements [`cqrs::UpcastableEvent`] for an event type out of the
+/// upcasting methods of its inherent `impl` block.
+///
+/// Every method of the `impl` block should be marked with
+/// `#[upcaster(from = <non-zero unsigned integer>)]` attribute, and should have
+/// a signature `fn(payload: Payload) -> Result<Payload, Err>`, transforming a
+/// raw payload of the event of the specified version into the raw payload of
+/// the next version. All the methods have to use the same `Payload` and `Err`
+/// types.
+///
+/// The event type should implement [`cqrs::StaticTypedEvent`], as the
+/// upcasters are registered for its [`cqrs::StaticTypedEvent::EVENT_TYPE`].
+///
+/// # Examples
+/// ```
+/// # use cqrs::Upcasters;
+/// # use cqrs_codegen::{upcaster, Event, VersionedEvent};
+/// # use serde_json::{json, Value};
+/// #
+/// #[derive(Event, VersionedEvent)]
+/// #[event(name = "user.created", version = 3)]
+/// struct UserCreated {
+///     first_name: String,
+///     last_name: String,
+/// }
+///
+/// #[upcaster]
+/// impl UserCreated {
+///     #[upcaster(from = 1)]
+///     fn add_name(mut payload: Value) -> Result<Value, String> {
+///         payload["name"] = json!("Unknown");
+///         Ok(payload)
+///     }
+///
+///     #[upcaster(from = 2)]
+///     fn split_name(payload: Value) -> Result<Value, String> {
+///         let name = payload["name"].as_str().ok_or("name is missing")?;
+///         let (first, last) = name.split_at(name.find(' ').unwrap_or(name.len()));
+///         Ok(json!({"first_name": first, "last_name": last.trim()}))
+///     }
+/// }
+///
+/// let mut upcasters = Upcasters::new();
+/// upcasters.register_event::<UserCreated>();
+///
+/// let (ver, payload) = upcasters
+///     .upcast("user.created", cqrs::EventVersion::new(1).unwrap(), json!({}))
+///     .unwrap();
+///
+/// assert_eq!(ver.into_u8(), 3);
+/// assert_eq!(payload, json!({"first_name": "Unknown", "last_name": ""}));
+/// ```
+#[proc_macro_attribute]
+pub fn upcaster(args: TokenStream, input: TokenStream) -> TokenStream {
+    import_attribute!(args, input, upcaster_attribute)
+}
@@ -62,7 +62,7 @@ fn derives_for_tuple_struct_with_explicit_id_field() {
 fn derives_for_generic_struct_with_inferred_id_field() {
     #[derive(Default, Aggregate)]
     #[aggregate(name = "test.aggregate")]
-    struct TestAggregate<T: Default> {
+    struct TestAggregate<T: Default + Send + Sync> {
         id: T,
         field: T,
     }
@@ -79,7 +79,7 @@ fn derives_for_generic_struct_with_inferred_id_field() {
 fn derives_for_generic_struct_with_explicit_id_field() {
     #[derive(Default, Aggregate)]
     #[aggregate(name = "test.aggregate")]
-    struct TestAggregate<T: Default> {
+    struct TestAggregate<T: Default + Send + Sync> {
         #[aggregate(id)]
         explicit_id: T,
         field: T,
@@ -97,7 +97,7 @@ fn derives_for_generic_struct_with_explicit_id_field() {
 fn derives_for_generic_struct_with_redundantly_explicit_id_field() {
     #[derive(Default, Aggregate)]
     #[aggregate(name = "test.aggregate")]
-    struct TestAggregate<T: Default> {
+    struct TestAggregate<T: Default + Send + Sync> {
         #[aggregate(id)]
         id: T,
         field: T,
@@ -115,7 +115,7 @@ fn derives_for_generic_struct_with_redundantly_explicit_id_field() {
 fn derives_for_generic_tuple_struct_with_explicit_id_field() {
     #[derive(Default, Aggregate)]
     #[aggregate(name = "test.aggregate")]
-    struct TestAggregate<T: Default = ()>(#[aggregate(id)] T, T);
+    struct TestAggregate<T: Default + Send + Sync = ()>(#[aggregate(id)] T, T);
 
     assert_eq!(TestAggregate::<i32>::AGGREGATE_TYPE, "test.aggregate");
     assert_eq!(
@@ -138,3 +138,21 @@ fn derives_for_struct_with_const_generic_parameters() {
     );
     assert_eq!(*TestAggregate::<1>::default().id(), 0);
 }
+
+#[test]
+fn derives_for_struct_with_snapshot_version() {
+    #[derive(Default, Aggregate)]
+    #[aggregate(name = "test.aggregate", snapshot_version = 3)]
+    struct TestAggregate {
+        id: i32,
+    }
+
+    #[derive(Default, Aggregate)]
+    #[aggregate(name = "test.aggregate")]
+    struct DefaultAggregate {
+        id: i32,
+    }
+
+    assert_eq!(TestAggregate::SNAPSHOT_VERSION, 3);
+    assert_eq!(DefaultAggregate::SNAPSHOT_VERSION, 1);
+}
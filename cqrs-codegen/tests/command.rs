@@ -137,7 +137,7 @@ fn derives_for_tuple_struct_with_id_and_version() {
 fn derives_for_struct_with_generic_parameters() {
     #[derive(Command)]
     #[command(aggregate = "Aggregate")]
-    struct TestCommand<T: core::fmt::Debug = ()> {
+    struct TestCommand<T: core::fmt::Debug + Send + Sync = ()> {
         id: i32,
         version: Version,
         parameter: T,
@@ -0,0 +1,88 @@
+#![allow(dead_code)]
+
+use std::convert::Infallible;
+
+use cqrs::{CommandHandler as _, EventNumber, NumberedEvent};
+use cqrs_codegen::{command_handler, Aggregate, Command, Event};
+use futures::executor::block_on;
+
+#[derive(Aggregate, Default)]
+#[aggregate(name = "aggregate")]
+struct Aggregate {
+    id: i32,
+}
+
+#[derive(Command)]
+#[command(aggregate = "Aggregate")]
+struct Create(i32);
+
+#[derive(Command)]
+#[command(aggregate = "Aggregate")]
+struct Rename;
+
+#[derive(Command)]
+#[command(aggregate = "Aggregate")]
+struct Remove;
+
+#[derive(Debug, Event, PartialEq)]
+#[event(name = "created")]
+struct Created(i32);
+
+#[derive(Debug, Event, PartialEq)]
+#[event(name = "renamed")]
+struct Renamed;
+
+/// Test context of command handlers.
+struct Multiplier(i32);
+
+#[command_handler]
+impl Aggregate {
+    async fn create(
+        &self,
+        cmd: Create,
+        ctx: &Multiplier,
+    ) -> Result<Vec<NumberedEvent<Created>>, Infallible> {
+        Ok(vec![NumberedEvent {
+            num: EventNumber::MIN_VALUE,
+            data: Created(cmd.0 * ctx.0),
+        }])
+    }
+
+    #[command_handler(event = "Renamed")]
+    fn rename(&self, _: Rename) -> Result<Option<Renamed>, Infallible> {
+        Ok(Some(Renamed))
+    }
+
+    async fn remove(&self, _: Remove) -> Result<(), &'static str> {
+        Err("cannot be removed")
+    }
+}
+
+#[test]
+fn implements_for_async_method_with_context() {
+    let events = block_on(Aggregate::default().handle(Create(2), &Multiplier(3))).unwrap();
+
+    assert_eq!(events[0].data, Created(6));
+}
+
+#[test]
+fn implements_for_sync_method_with_explicit_event() {
+    fn event<H: cqrs::CommandHandler<Rename, Event = Renamed, Context = ()>>(_: &H) {}
+    event(&Aggregate::default());
+
+    assert_eq!(
+        block_on(Aggregate::default().handle(Rename, &())).unwrap(),
+        Some(Renamed),
+    );
+}
+
+#[test]
+fn implements_for_method_producing_no_events() {
+    fn event<H: cqrs::CommandHandler<Remove, Event = ()>>(_: &H) {}
+    event(&Aggregate::default());
+
+    assert_eq!(
+        block_on(Aggregate::default().handle(Remove, &())),
+        Err("cannot be removed"),
+    );
+}
@@ -1,6 +1,6 @@
 #![allow(dead_code)]
 
-use cqrs::{Event as _, StaticTypedEvent as _};
+use cqrs::{Event as _, EventType, EventVersion, StaticTypedEvent as _, TypedEvent as _};
 use cqrs_codegen::Event;
 
 #[test]
@@ -96,3 +96,67 @@ fn derives_for_generic_enum() {
         "test.event.generic.2",
     );
 }
+
+#[test]
+fn derives_for_nested_enum_with_versions() {
+    #[derive(Event)]
+    #[event(name = "test.event", version = 1)]
+    struct TestEventV1;
+
+    #[derive(Event)]
+    #[event(name = "test.event", version = 2)]
+    struct TestEventV2;
+
+    #[derive(Event)]
+    #[event(name = "test.event.other")]
+    struct TestEventOther;
+
+    #[derive(Event)]
+    enum TestEventVersions {
+        V1(TestEventV1),
+        V2(TestEventV2),
+    }
+
+    #[derive(Event)]
+    enum TestEvent {
+        Versions(TestEventVersions),
+        Other { event: TestEventOther },
+    }
+
+    assert_eq!(
+        TestEvent::EVENT_TYPES,
+        &["test.event", "test.event", "test.event.other"],
+    );
+    assert_eq!(
+        TestEvent::EVENT_VERSIONS,
+        &[EventVersion::new(1), EventVersion::new(2), None],
+    );
+}
+
+#[test]
+fn derives_for_enum_with_unversioned_manual_impl() {
+    #[derive(Event)]
+    #[event(name = "test.event", version = 1)]
+    struct TestEventV1;
+
+    struct TestEventManual;
+
+    impl cqrs::Event for TestEventManual {
+        fn event_type(&self) -> EventType {
+            "test.event.manual"
+        }
+    }
+
+    impl cqrs::TypedEvent for TestEventManual {
+        const EVENT_TYPES: &'static [EventType] = &["test.event.manual"];
+    }
+
+    #[derive(Event)]
+    enum TestEvent {
+        Manual(TestEventManual),
+        V1(TestEventV1),
+    }
+
+    assert_eq!(TestEvent::EVENT_TYPES, &["test.event.manual", "test.event"],);
+    assert_eq!(TestEvent::EVENT_VERSIONS, &[None, EventVersion::new(1)]);
+}
@@ -96,3 +96,37 @@ fn derives_for_generic_enum() {
     assert!(aggregate.event3_applied);
     assert!(aggregate.event4_applied);
 }
+
+#[test]
+fn derives_for_struct() {
+    #[derive(Aggregate, Default, EventSourced)]
+    #[aggregate(name = "aggregate")]
+    #[event_sourced(on(Event1 = "apply_event1", Event2 = "apply_event2"))]
+    struct Aggregate {
+        id: i32,
+        applied: Vec<u8>,
+    }
+
+    impl Aggregate {
+        fn apply_event1(&mut self, _: &Event1) {
+            self.applied.push(1);
+        }
+
+        fn apply_event2(&mut self, _: &Event2) {
+            self.applied.push(2);
+        }
+    }
+
+    #[derive(Event, EventSourced)]
+    #[event_sourced(aggregate = "Aggregate")]
+    enum Event {
+        Event1(Event1),
+        Event2(Event2),
+    }
+
+    let mut aggregate = Aggregate::default();
+    aggregate.apply(&Event2);
+    aggregate.apply(&Event::Event1(Event1));
+
+    assert_eq!(aggregate.applied, vec![2, 1]);
+}
@@ -0,0 +1,153 @@
+#![allow(dead_code)]
+
+use cqrs::{
+    DeserializableEvent as _, Event as _, EventVersion, SerializableEvent as _, VersionedEvent as _,
+};
+use cqrs_codegen::{DeserializableEvent, Event, SerializableEvent, VersionedEvent};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+#[derive(
+    Debug,
+    Deserialize,
+    DeserializableEvent,
+    Event,
+    PartialEq,
+    Serialize,
+    SerializableEvent,
+    VersionedEvent,
+)]
+#[event(name = "test.event.created", version = 1)]
+struct TestEventCreatedV1 {
+    id: i32,
+}
+
+#[derive(
+    Debug,
+    Deserialize,
+    DeserializableEvent,
+    Event,
+    PartialEq,
+    Serialize,
+    SerializableEvent,
+    VersionedEvent,
+)]
+#[event(name = "test.event.created", version = 2)]
+struct TestEventCreatedV2 {
+    id: i32,
+    data: String,
+}
+
+#[derive(
+    Debug,
+    Deserialize,
+    DeserializableEvent,
+    Event,
+    PartialEq,
+    Serialize,
+    SerializableEvent,
+    VersionedEvent,
+)]
+#[event(name = "test.event.removed", version = 1)]
+struct TestEventRemoved;
+
+#[derive(Debug, DeserializableEvent, Event, PartialEq, SerializableEvent, VersionedEvent)]
+enum TestEventCreated {
+    V1(TestEventCreatedV1),
+    V2 { event: TestEventCreatedV2 },
+}
+
+#[derive(Debug, DeserializableEvent, Event, PartialEq, SerializableEvent, VersionedEvent)]
+enum TestEvent {
+    Created(TestEventCreated),
+    Removed(TestEventRemoved),
+}
+
+fn version(n: u8) -> EventVersion {
+    EventVersion::new(n).unwrap()
+}
+
+/// Serializes the given event into its tag and JSON payload.
+fn serialize(ev: &TestEvent) -> (&'static str, EventVersion, Value) {
+    let payload = ev.serialize_event(serde_json::value::Serializer).unwrap();
+    (ev.event_type(), *ev.event_version(), payload)
+}
+
+/// Deserializes an event out of the given tag and JSON payload.
+fn deserialize(event_type: &str, ver: EventVersion, payload: Value) -> Option<TestEvent> {
+    TestEvent::deserialize_event(event_type, ver, payload).unwrap()
+}
+
+#[test]
+fn serializes_tagged_payload() {
+    let ev = TestEvent::Created(TestEventCreated::V2 {
+        event: TestEventCreatedV2 {
+            id: 1,
+            data: "data".into(),
+        },
+    });
+
+    assert_eq!(
+        serialize(&ev),
+        (
+            "test.event.created",
+            version(2),
+            json!({"id": 1, "data": "data"}),
+        ),
+    );
+    assert_eq!(
+        serialize(&TestEvent::Removed(TestEventRemoved)),
+        ("test.event.removed", version(1), Value::Null),
+    );
+}
+
+#[test]
+fn roundtrips_through_tagged_payload() {
+    let events = vec![
+        TestEvent::Created(TestEventCreated::V1(TestEventCreatedV1 { id: 1 })),
+        TestEvent::Created(TestEventCreated::V2 {
+            event: TestEventCreatedV2 {
+                id: 2,
+                data: "data".into(),
+            },
+        }),
+        TestEvent::Removed(TestEventRemoved),
+    ];
+
+    for ev in events {
+        let (event_type, ver, payload) = serialize(&ev);
+        assert_eq!(deserialize(event_type, ver, payload), Some(ev));
+    }
+}
+
+#[test]
+fn skips_unknown_tags() {
+    assert!(TestEvent::is_deserializable(
+        "test.event.created",
+        version(1)
+    ));
+    assert!(!TestEvent::is_deserializable(
+        "test.event.created",
+        version(3)
+    ));
+    assert!(!TestEvent::is_deserializable(
+        "test.event.unknown",
+        version(1)
+    ));
+
+    assert_eq!(
+        deserialize("test.event.created", version(3), json!({})),
+        None
+    );
+    assert_eq!(
+        deserialize("test.event.unknown", version(1), json!({})),
+        None
+    );
+}
+
+#[test]
+fn fails_on_malformed_payload() {
+    let res = TestEvent::deserialize_event("test.event.created", version(2), json!({"id": 1}));
+
+    assert!(res.is_err());
+}
@@ -0,0 +1,252 @@
+#![allow(dead_code)]
+
+use std::{convert::Infallible, time::SystemTime};
+
+use cqrs::{
+    lifecycle::Basic, BoxTryStream, DeserializableEvent as _, EventNumber, EventSource,
+    EventSourced, EventVersion, NeverSnapshot, NumberedEvent, Since, SnapshotSource,
+    SnapshotVersion, UpcastError, Upcasters, Version,
+};
+use cqrs_codegen::{upcaster, Aggregate, DeserializableEvent, Event, VersionedEvent};
+use futures::{executor::block_on, stream};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+#[derive(Debug, Deserialize, DeserializableEvent, Event, PartialEq, VersionedEvent)]
+#[event(name = "test.event.created", version = 3)]
+struct TestEventCreated {
+    id: i32,
+    tags: Vec<String>,
+}
+
+#[upcaster]
+impl TestEventCreated {
+    #[upcaster(from = 1)]
+    fn rename_id(payload: Value) -> Result<Value, String> {
+        Ok(json!({ "id": payload["test_id"] }))
+    }
+
+    #[upcaster(from = 2)]
+    fn add_tags(mut payload: Value) -> Result<Value, String> {
+        if !payload["id"].is_i64() {
+            return Err("id is missing".into());
+        }
+        payload["tags"] = json!([]);
+        Ok(payload)
+    }
+}
+
+#[derive(Debug, Deserialize, DeserializableEvent, Event, PartialEq, VersionedEvent)]
+#[event(name = "test.event.removed", version = 1)]
+struct TestEventRemoved {
+    id: i32,
+}
+
+#[derive(Debug, DeserializableEvent, Event, PartialEq, VersionedEvent)]
+enum TestEvent {
+    Created(TestEventCreated),
+    Removed(TestEventRemoved),
+}
+
+fn version(n: u8) -> EventVersion {
+    EventVersion::new(n).unwrap()
+}
+
+fn upcasters() -> Upcasters<Value, String> {
+    let mut upcasters = Upcasters::new();
+    upcasters.register_event::<TestEventCreated>();
+    upcasters
+}
+
+#[test]
+fn registers_upcasters_of_event() {
+    let upcasters = upcasters();
+
+    assert!(upcasters.is_upcastable("test.event.created", version(1)));
+    assert!(upcasters.is_upcastable("test.event.created", version(2)));
+    assert!(!upcasters.is_upcastable("test.event.created", version(3)));
+    assert!(!upcasters.is_upcastable("test.event.removed", version(1)));
+}
+
+#[test]
+fn chains_upcasters() {
+    let upcasters = upcasters();
+
+    assert_eq!(
+        upcasters.upcast("test.event.created", version(1), json!({"test_id": 1})),
+        Ok((version(3), json!({"id": 1, "tags": []}))),
+    );
+    assert_eq!(
+        upcasters.upcast("test.event.created", version(2), json!({"id": 2})),
+        Ok((version(3), json!({"id": 2, "tags": []}))),
+    );
+    assert_eq!(
+        upcasters.upcast("test.event.created", version(1), json!({"id": 1})),
+        Err("id is missing".into()),
+    );
+}
+
+#[test]
+fn leaves_actual_versions_as_is() {
+    let upcasters = upcasters();
+
+    assert_eq!(
+        upcasters.upcast("test.event.created", version(3), json!({"id": 3})),
+        Ok((version(3), json!({"id": 3}))),
+    );
+    assert_eq!(
+        upcasters.upcast("test.event.removed", version(1), json!({"id": 4})),
+        Ok((version(1), json!({"id": 4}))),
+    );
+}
+
+#[test]
+fn upcasts_before_deserializing() {
+    let upcasters = upcasters();
+    let stored = vec![
+        ("test.event.created", version(1), json!({"test_id": 1})),
+        ("test.event.removed", version(1), json!({"id": 1})),
+    ];
+
+    let events = stored
+        .into_iter()
+        .map(|(event_type, ver, payload)| {
+            TestEvent::deserialize_upcast(&upcasters, event_type, ver, payload).unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        events,
+        vec![
+            Some(TestEvent::Created(TestEventCreated {
+                id: 1,
+                tags: vec![],
+            })),
+            Some(TestEvent::Removed(TestEventRemoved { id: 1 })),
+        ],
+    );
+}
+
+#[test]
+fn fails_to_deserialize_on_upcaster_failure() {
+    let upcasters = upcasters();
+
+    let res = TestEvent::deserialize_upcast(
+        &upcasters,
+        "test.event.created",
+        version(2),
+        json!({"test_id": 1}),
+    );
+
+    match res {
+        Err(UpcastError::Upcast(e)) => assert_eq!(e, "id is missing"),
+        res => panic!("unexpected result: {:?}", res),
+    }
+}
+
+#[derive(Aggregate, Debug, Default, PartialEq)]
+#[aggregate(name = "test")]
+struct TestAggregate {
+    id: i32,
+    tags: Vec<String>,
+    removed: bool,
+}
+
+impl EventSourced<TestEvent> for TestAggregate {
+    fn apply(&mut self, event: &TestEvent) {
+        match event {
+            TestEvent::Created(ev) => {
+                self.id = ev.id;
+                self.tags = ev.tags.clone();
+            }
+            TestEvent::Removed(_) => self.removed = true,
+        }
+    }
+}
+
+/// Store of raw payloads of [`TestEvent`]s, upcasting them on reading.
+struct RawStore {
+    events: Vec<(i32, &'static str, EventVersion, Value)>,
+    upcasters: Upcasters<Value, String>,
+}
+
+impl EventSource<TestAggregate, TestEvent> for RawStore {
+    type Err = UpcastError<String, serde_json::Error>;
+
+    fn read_events(
+        &self,
+        id: &i32,
+        since: Since,
+    ) -> BoxTryStream<'_, NumberedEvent<TestEvent>, Self::Err> {
+        let events = self
+            .events
+            .iter()
+            .filter(|(agg_id, ..)| agg_id == id)
+            .zip(1u128..)
+            .map(|(ev, n)| (ev, EventNumber::new(n).unwrap()))
+            .filter(|(_, num)| match since {
+                Since::BeginningOfStream => true,
+                Since::Event(last) => *num > last,
+            })
+            .filter_map(|((_, event_type, ver, payload), num)| {
+                TestEvent::deserialize_upcast(&self.upcasters, event_type, *ver, payload.clone())
+                    .map(|data| data.map(|data| NumberedEvent { num, data }))
+                    .transpose()
+            })
+            .collect::<Vec<_>>();
+        Box::pin(stream::iter(events))
+    }
+}
+
+cqrs::__async_trait_impl! {
+    impl SnapshotSource<TestAggregate> for RawStore {
+        type Err = Infallible;
+
+        async fn load_snapshots(
+            &self,
+            _: &[i32],
+        ) -> Result<Vec<(TestAggregate, Version, SnapshotVersion, SystemTime)>, Self::Err> {
+            Ok(vec![])
+        }
+    }
+}
+
+impl AsRef<RawStore> for RawStore {
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+#[test]
+fn loads_aggregate_from_upcast_events_of_store() {
+    let store = RawStore {
+        events: vec![
+            (1, "test.event.created", version(1), json!({"test_id": 1})),
+            (
+                2,
+                "test.event.created",
+                version(3),
+                json!({"id": 2, "tags": ["new"]}),
+            ),
+            (1, "test.event.removed", version(1), json!({"id": 1})),
+        ],
+        upcasters: upcasters(),
+    };
+    let lifecycle = Basic::new(NeverSnapshot);
+
+    let agg = block_on(
+        lifecycle.load_aggregate_and_rehydrate::<RawStore, RawStore, TestEvent, _, _>(&1, &store),
+    )
+    .unwrap()
+    .unwrap();
+
+    assert_eq!(agg.version(), Version::new(2u8));
+    assert_eq!(
+        agg.state(),
+        &TestAggregate {
+            id: 1,
+            tags: vec![],
+            removed: true,
+        },
+    );
+}
@@ -6,54 +6,6 @@ use std::{
 
 use super::{Aggregate, AggregateCommand, AggregateEvent};
 
-/// A precondition that must be upheld for a command to be executed or for events to be persisted.
-#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
-pub enum Precondition {
-    /// Requires that the target aggregate must not have had any events previously applied to it.
-    New,
-    /// Requires that the target event at least exist, whether as a snapshot, or as having had at least one event applied.
-    Exists,
-    /// Requires that the target aggregate have the exact version specified.
-    ExpectedVersion(Version),
-}
-
-impl Precondition {
-    /// Verifies that the precondition holds, given the `current_version`. If the precondition is violated, an error is
-    /// returned with the precondition.
-    pub fn verify(self, current_version: Option<Version>) -> Result<(), Self> {
-        match (self, current_version) {
-            (Precondition::ExpectedVersion(Version::Initial), None) => Ok(()),
-            (Precondition::ExpectedVersion(Version::Initial), Some(Version::Initial)) => Ok(()),
-            (Precondition::ExpectedVersion(e), Some(x)) if e == x => Ok(()),
-            (Precondition::New, None) => Ok(()),
-            (Precondition::Exists, Some(_)) => Ok(()),
-            (precondition, _) => Err(precondition),
-        }
-    }
-}
-
-impl From<Version> for Precondition {
-    #[inline]
-    fn from(v: Version) -> Self {
-        Precondition::ExpectedVersion(v)
-    }
-}
-
-impl fmt::Display for Precondition {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match *self {
-            Precondition::Exists => f.write_str("expect aggregate exists"),
-            Precondition::New => f.write_str("expect aggregate does not exist"),
-            Precondition::ExpectedVersion(Version::Initial) => {
-                f.write_str("expect aggregate to exist in initial state")
-            }
-            Precondition::ExpectedVersion(Version::Number(v)) => {
-                write!(f, "expect aggregate to exist with version {}", v)
-            }
-        }
-    }
-}
-
 /// Represents a common trait that all errors handled by CQRS should implement.
 pub trait CqrsError: fmt::Debug + fmt::Display + Send + Sync + 'static {}
 
@@ -6,15 +6,16 @@ use std::{
     borrow::Borrow,
     convert::{TryFrom, TryInto as _},
     fmt,
-    num::TryFromIntError,
+    num::{NonZeroUsize, TryFromIntError},
     ops, slice,
+    time::{Duration, SystemTime},
 };
 
 use async_trait::async_trait;
 
 #[cfg(doc)]
 use super::Event;
-use super::{EventNumber, EventSourced, NumberedEvent};
+use super::{EventNumber, EventSourced, MaybeSend, MaybeSync, NumberedEvent};
 
 /// [DDD aggregate] that represents an isolated tree of entities, is
 /// capable of handling [`Command`]s and is always kept in a consistent state.
@@ -24,11 +25,21 @@ use super::{EventNumber, EventSourced, NumberedEvent};
 /// and distinguishable only after at least one [`Event`] is applied to its
 /// initial state.
 ///
+/// If the `send` feature is enabled, [`Aggregate`] and its ID are required to
+/// be `Send + Sync`.
+///
 /// [DDD aggregate]: https://martinfowler.com/bliki/DDD_Aggregate.html
 /// [`Command`]: super::Command
-pub trait Aggregate: Default {
+pub trait Aggregate: Default + MaybeSend + MaybeSync {
     /// Type of [`Aggregate`]'s unique identifier (ID).
-    type Id;
+    type Id: MaybeSend + MaybeSync;
+
+    /// Version of the format of this [`Aggregate`]'s snapshots.
+    ///
+    /// Should be bumped every time the shape of the snapshotted state changes,
+    /// so the snapshots stored in the previous format are discarded and the
+    /// [`Aggregate`] is rebuilt from its [`Event`]s instead.
+    const SNAPSHOT_VERSION: SnapshotVersion = 1;
 
     /// Returns type of this [`Aggregate`].
     ///
@@ -41,17 +52,30 @@ pub trait Aggregate: Default {
 }
 
 /// Source for loading snapshots of some [`Aggregate`].
-#[async_trait(?Send)]
-pub trait SnapshotSource<Agg: Aggregate> {
+///
+/// Every loaded snapshot is accompanied by the [`SnapshotVersion`] it was
+/// stored with, so the snapshots of an outdated format can be recognized and
+/// discarded. Sources deserializing snapshots should check the stored
+/// [`SnapshotVersion`] before deserializing the [`Aggregate`] itself.
+///
+/// The moment the snapshot was persisted at is loaded along with it, so
+/// time-based [`SnapshotStrategy`]s keep working across loads of the
+/// [`Aggregate`].
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+pub trait SnapshotSource<Agg: Aggregate>: MaybeSync {
     /// Type of the shapshot loading error.
     /// If it never fails, consider to specify [`Infallible`].
     ///
     /// [`Infallible`]: std::convert::Infallible
-    type Err;
+    type Err: MaybeSend;
 
     /// Loads latest stored snapshot of a given [`Aggregate`].
     #[allow(unused_lifetimes)]
-    async fn load_snapshot(&self, id: &Agg::Id) -> Result<Option<(Agg, Version)>, Self::Err>
+    async fn load_snapshot(
+        &self,
+        id: &Agg::Id,
+    ) -> Result<Option<(Agg, Version, SnapshotVersion, SystemTime)>, Self::Err>
     where
         Agg: 'async_trait,
     {
@@ -59,19 +83,66 @@ pub trait SnapshotSource<Agg: Aggregate> {
     }
 
     /// Loads latest stored snapshots of given [`Aggregate`]s.
-    async fn load_snapshots(&self, ids: &[Agg::Id]) -> Result<Vec<(Agg, Version)>, Self::Err>;
+    async fn load_snapshots(
+        &self,
+        ids: &[Agg::Id],
+    ) -> Result<Vec<(Agg, Version, SnapshotVersion, SystemTime)>, Self::Err>;
+
+    /// Loads the newest stored snapshot of a given [`Aggregate`], which
+    /// [`Version`] doesn't exceed the given one.
+    ///
+    /// Default implementation considers the latest stored snapshot only, so
+    /// sources retaining older snapshots should override it.
+    #[allow(unused_lifetimes)]
+    async fn load_snapshot_at(
+        &self,
+        id: &Agg::Id,
+        ver: Version,
+    ) -> Result<Option<(Agg, Version, SnapshotVersion, SystemTime)>, Self::Err>
+    where
+        Agg: 'async_trait,
+    {
+        Ok(self
+            .load_snapshot(id)
+            .await?
+            .filter(|(_, snapshot_ver, ..)| *snapshot_ver <= ver))
+    }
+
+    /// Loads the newest stored snapshot of a given [`Aggregate`], which has
+    /// been taken no later than the given moment.
+    ///
+    /// Default implementation considers the latest stored snapshot only, so
+    /// sources retaining older snapshots should override it.
+    #[allow(unused_lifetimes)]
+    async fn load_snapshot_taken_until(
+        &self,
+        id: &Agg::Id,
+        at: SystemTime,
+    ) -> Result<Option<(Agg, Version, SnapshotVersion, SystemTime)>, Self::Err>
+    where
+        Agg: 'async_trait,
+    {
+        Ok(self
+            .load_snapshot(id)
+            .await?
+            .filter(|(.., taken_at)| *taken_at <= at))
+    }
 }
 
 /// Sink for persisting snapshots of some [`Aggregate`].
-#[async_trait(?Send)]
-pub trait SnapshotSink<Agg: Aggregate + ?Sized> {
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+pub trait SnapshotSink<Agg: Aggregate + ?Sized>: MaybeSync {
     /// Type of the shapshot persisting error.
     /// If it never fails, consider to specify [`Infallible`].
     ///
     /// [`Infallible`]: std::convert::Infallible
-    type Err;
+    type Err: MaybeSend;
 
     /// Persists [`Aggregate`]'s snapshot of a given [`Version`].
+    ///
+    /// The moment of persisting should be stored along with the snapshot, and
+    /// returned by the [`SnapshotSource`] on loading it.
     #[allow(unused_lifetimes)]
     async fn persist_snapshot(&self, agg: &Agg, ver: Version) -> Result<(), Self::Err> {
         self.persist_snapshots(slice::from_ref(&(agg, ver))).await
@@ -79,6 +150,23 @@ pub trait SnapshotSink<Agg: Aggregate + ?Sized> {
 
     /// Persists multiple [`Aggregate`]'s snapshots of given [`Version`]s.
     async fn persist_snapshots(&self, aggs: &[(&Agg, Version)]) -> Result<(), Self::Err>;
+
+    /// Removes stored snapshots of given [`Aggregate`]s, which are not
+    /// retained by the given [`SnapshotRetention`] policy.
+    ///
+    /// The latest snapshot of an [`Aggregate`] should be removed only if it's
+    /// older than required by [`SnapshotRetention::KeepSince`].
+    ///
+    /// Default implementation retains all the snapshots.
+    #[allow(unused_lifetimes)]
+    async fn prune_snapshots(
+        &self,
+        ids: &[&Agg::Id],
+        retention: SnapshotRetention,
+    ) -> Result<(), Self::Err> {
+        let _ = (ids, retention);
+        Ok(())
+    }
 }
 
 /// [`Aggregate`] that is [`EventSourced`] and keeps track of the version of its
@@ -91,6 +179,10 @@ pub struct HydratedAggregate<Agg> {
     /// [`Version`] of last snapshot of this [`Aggregate`].
     snapshot_ver: Option<Version>,
 
+    /// Moment when the last snapshot of this [`Aggregate`] was taken, if it's
+    /// known.
+    snapshot_at: Option<SystemTime>,
+
     /// The [`Aggregate`] itself.
     state: Agg,
 }
@@ -103,6 +195,7 @@ impl<Agg> HydratedAggregate<Agg> {
         Self {
             ver,
             snapshot_ver: None,
+            snapshot_at: None,
             state: agg,
         }
     }
@@ -114,10 +207,21 @@ impl<Agg> HydratedAggregate<Agg> {
         Self {
             ver,
             snapshot_ver: Some(ver),
+            snapshot_at: None,
             state: agg,
         }
     }
 
+    /// Creates new [`HydratedAggregate`] from a given [`Aggregate`], its
+    /// latest snapshot [`Version`] and the moment that snapshot was taken at.
+    #[inline]
+    pub fn from_snapshot_taken_at(agg: Agg, ver: Version, at: SystemTime) -> Self {
+        Self {
+            snapshot_at: Some(at),
+            ..Self::from_snapshot(agg, ver)
+        }
+    }
+
     /// Returns ID of this [`Aggregate`].
     #[inline(always)]
     pub fn id(&self) -> &Agg::Id
@@ -139,10 +243,31 @@ impl<Agg> HydratedAggregate<Agg> {
         self.snapshot_ver
     }
 
-    /// Sets [`Version`] of the latest snapshot for this [`Aggregate`].
+    /// Sets [`Version`] of the latest snapshot for this [`Aggregate`], which
+    /// is considered to be taken right now.
     #[inline(always)]
     pub fn set_snapshot_version(&mut self, new: Version) {
         self.snapshot_ver = Some(new);
+        self.snapshot_at = Some(SystemTime::now());
+    }
+
+    /// Returns the moment when the latest snapshot for this [`Aggregate`] was
+    /// taken, either loaded along with the snapshot (see
+    /// [`HydratedAggregate::from_snapshot_taken_at`]) or set via
+    /// [`HydratedAggregate::set_snapshot_version`].
+    ///
+    /// `None` if the moment is unknown.
+    #[inline(always)]
+    pub fn snapshot_taken_at(&self) -> Option<SystemTime> {
+        self.snapshot_at
+    }
+
+    /// Returns number of [`Event`]s applied to this [`Aggregate`] since its
+    /// latest snapshot, i.e. the number of [`Event`]s to be replayed on top of
+    /// that snapshot for loading this [`Aggregate`].
+    #[inline]
+    pub fn events_since_snapshot(&self) -> u64 {
+        events_since(self.ver, self.snapshot_ver)
     }
 
     /// Returns the inner [`Aggregate`] itself.
@@ -213,6 +338,7 @@ impl<Agg> HydratedAggregate<Agg> {
         HydratedAggregate {
             ver: self.ver,
             snapshot_ver: self.snapshot_ver,
+            snapshot_at: self.snapshot_at,
             state: f(self.state),
         }
     }
@@ -249,6 +375,9 @@ impl<Agg> Borrow<Agg> for HydratedAggregate<Agg> {
 /// Type of an [`Aggregate`].
 pub type AggregateType = &'static str;
 
+/// Version of the format of an [`Aggregate`]'s snapshots.
+pub type SnapshotVersion = u32;
+
 /// Version of an [`Aggregate`].
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum Version {
@@ -435,6 +564,32 @@ impl ops::Sub for Version {
     }
 }
 
+/// Policy of retaining older snapshots of an [`Aggregate`] once a newer one
+/// is persisted.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SnapshotRetention {
+    /// All the snapshots should be kept.
+    KeepAll,
+    /// Only the given number of the latest snapshots should be kept.
+    KeepLatest(NonZeroUsize),
+    /// No snapshots older than the given [`Version`] should be kept.
+    KeepSince(Version),
+}
+
+impl Default for SnapshotRetention {
+    #[inline]
+    fn default() -> Self {
+        Self::KeepAll
+    }
+}
+
+/// Returns number of [`Event`]s applied to an [`Aggregate`] of the given
+/// [`Version`] since its latest snapshot of the given [`Version`].
+#[inline]
+fn events_since(ver: Version, last_snapshot_ver: Option<Version>) -> u64 {
+    u64::try_from(ver - last_snapshot_ver.unwrap_or_default()).unwrap_or_default()
+}
+
 /// Recommendation on whether or not a snapshot of an [`Aggregate`] should be
 /// persisted.
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
@@ -446,7 +601,7 @@ pub enum SnapshotRecommendation {
 }
 
 /// Strategy determining when a snapshot of an [`Aggregate`] should be taken.
-pub trait SnapshotStrategy {
+pub trait SnapshotStrategy: MaybeSync {
     /// Gives the [`SnapshotRecommendation`] on whether or not to perform
     /// a snapshot for an [`Aggregate`].
     fn recommendation(
@@ -454,6 +609,23 @@ pub trait SnapshotStrategy {
         ver: Version,
         last_snapshot_ver: Option<Version>,
     ) -> SnapshotRecommendation;
+
+    /// Gives the [`SnapshotRecommendation`] on whether or not to perform
+    /// a snapshot for an [`Aggregate`], knowing the moment its latest snapshot
+    /// was taken at (see [`HydratedAggregate::snapshot_taken_at`]).
+    ///
+    /// Ignores the moment and delegates to
+    /// [`SnapshotStrategy::recommendation`] by default, so should be
+    /// overridden by time-based [`SnapshotStrategy`]s only.
+    #[inline]
+    fn recommendation_at(
+        &self,
+        ver: Version,
+        last_snapshot_ver: Option<Version>,
+        _last_snapshot_at: Option<SystemTime>,
+    ) -> SnapshotRecommendation {
+        self.recommendation(ver, last_snapshot_ver)
+    }
 }
 
 /// [`SnapshotStrategy`] that will never recommend taking a snapshot.
@@ -479,3 +651,162 @@ impl SnapshotStrategy for AlwaysSnapshot {
         SnapshotRecommendation::ShouldSnapshot
     }
 }
+
+/// [`SnapshotStrategy`] that recommends taking a snapshot once at least `N`
+/// [`Event`]s are applied to an [`Aggregate`] since its latest snapshot.
+///
+/// `N` must be positive, so `SnapshotEvery<0>` is rejected at compile time:
+/// ```compile_fail
+/// use cqrs_core::{SnapshotEvery, SnapshotStrategy as _, Version};
+///
+/// let _ = SnapshotEvery::<0>.recommendation(Version::Initial, None);
+/// ```
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub struct SnapshotEvery<const N: u64>;
+
+impl<const N: u64> SnapshotEvery<N> {
+    /// Asserts `N` to be positive, once evaluated.
+    const POSITIVE_N: () = assert!(N > 0, "`SnapshotEvery<N>` requires `N` to be positive");
+}
+
+impl<const N: u64> SnapshotStrategy for SnapshotEvery<N> {
+    #[inline]
+    fn recommendation(
+        &self,
+        ver: Version,
+        last_snapshot_ver: Option<Version>,
+    ) -> SnapshotRecommendation {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::POSITIVE_N;
+        if events_since(ver, last_snapshot_ver) >= N {
+            SnapshotRecommendation::ShouldSnapshot
+        } else {
+            SnapshotRecommendation::DoNotSnapshot
+        }
+    }
+}
+
+/// [`SnapshotStrategy`] that recommends taking a snapshot of an [`Aggregate`]
+/// having new [`Event`]s applied, once the given period of time passes since
+/// its latest snapshot.
+///
+/// If the moment of the latest snapshot is unknown (see
+/// [`HydratedAggregate::snapshot_taken_at`]), the snapshot is recommended
+/// right away, so consider combining it with [`SnapshotEvery`] via
+/// [`SnapshotAll`] for short-living [`Aggregate`]s.
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub struct SnapshotAfter(pub Duration);
+
+impl SnapshotStrategy for SnapshotAfter {
+    #[inline]
+    fn recommendation(
+        &self,
+        ver: Version,
+        last_snapshot_ver: Option<Version>,
+    ) -> SnapshotRecommendation {
+        self.recommendation_at(ver, last_snapshot_ver, None)
+    }
+
+    #[inline]
+    fn recommendation_at(
+        &self,
+        ver: Version,
+        last_snapshot_ver: Option<Version>,
+        last_snapshot_at: Option<SystemTime>,
+    ) -> SnapshotRecommendation {
+        let is_due = match last_snapshot_at {
+            // Snapshots taken "in the future" due to clock skew are not due.
+            Some(at) => at.elapsed().is_ok_and(|passed| passed >= self.0),
+            None => true,
+        };
+        if events_since(ver, last_snapshot_ver) > 0 && is_due {
+            SnapshotRecommendation::ShouldSnapshot
+        } else {
+            SnapshotRecommendation::DoNotSnapshot
+        }
+    }
+}
+
+/// [`SnapshotStrategy`] combinator that recommends taking a snapshot if any of
+/// the given [`SnapshotStrategy`]s recommends it.
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub struct SnapshotAny<A, B>(pub A, pub B);
+
+impl<A, B> SnapshotStrategy for SnapshotAny<A, B>
+where
+    A: SnapshotStrategy,
+    B: SnapshotStrategy,
+{
+    #[inline]
+    fn recommendation(
+        &self,
+        ver: Version,
+        last_snapshot_ver: Option<Version>,
+    ) -> SnapshotRecommendation {
+        match self.0.recommendation(ver, last_snapshot_ver) {
+            SnapshotRecommendation::ShouldSnapshot => SnapshotRecommendation::ShouldSnapshot,
+            SnapshotRecommendation::DoNotSnapshot => self.1.recommendation(ver, last_snapshot_ver),
+        }
+    }
+
+    #[inline]
+    fn recommendation_at(
+        &self,
+        ver: Version,
+        last_snapshot_ver: Option<Version>,
+        last_snapshot_at: Option<SystemTime>,
+    ) -> SnapshotRecommendation {
+        match self
+            .0
+            .recommendation_at(ver, last_snapshot_ver, last_snapshot_at)
+        {
+            SnapshotRecommendation::ShouldSnapshot => SnapshotRecommendation::ShouldSnapshot,
+            SnapshotRecommendation::DoNotSnapshot => {
+                self.1
+                    .recommendation_at(ver, last_snapshot_ver, last_snapshot_at)
+            }
+        }
+    }
+}
+
+/// [`SnapshotStrategy`] combinator that recommends taking a snapshot only if
+/// all the given [`SnapshotStrategy`]s recommend it.
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub struct SnapshotAll<A, B>(pub A, pub B);
+
+impl<A, B> SnapshotStrategy for SnapshotAll<A, B>
+where
+    A: SnapshotStrategy,
+    B: SnapshotStrategy,
+{
+    #[inline]
+    fn recommendation(
+        &self,
+        ver: Version,
+        last_snapshot_ver: Option<Version>,
+    ) -> SnapshotRecommendation {
+        match self.0.recommendation(ver, last_snapshot_ver) {
+            SnapshotRecommendation::ShouldSnapshot => self.1.recommendation(ver, last_snapshot_ver),
+            SnapshotRecommendation::DoNotSnapshot => SnapshotRecommendation::DoNotSnapshot,
+        }
+    }
+
+    #[inline]
+    fn recommendation_at(
+        &self,
+        ver: Version,
+        last_snapshot_ver: Option<Version>,
+        last_snapshot_at: Option<SystemTime>,
+    ) -> SnapshotRecommendation {
+        match self
+            .0
+            .recommendation_at(ver, last_snapshot_ver, last_snapshot_at)
+        {
+            SnapshotRecommendation::ShouldSnapshot => {
+                self.1
+                    .recommendation_at(ver, last_snapshot_ver, last_snapshot_at)
+            }
+            SnapshotRecommendation::DoNotSnapshot => SnapshotRecommendation::DoNotSnapshot,
+        }
+    }
+}
@@ -4,7 +4,7 @@
 
 use async_trait::async_trait;
 
-use super::{Aggregate, Version};
+use super::{Aggregate, HydratedAggregate, MaybeSend, MaybeSync, Version};
 
 /// [CQRS] command that describes an intent to change the [`Aggregate`]'s state.
 ///
@@ -16,7 +16,7 @@ use super::{Aggregate, Version};
 /// (an [`Event`], usually).
 ///
 /// [CQRS]: https://martinfowler.com/bliki/CQRS.html
-pub trait Command {
+pub trait Command: MaybeSend {
     /// Type of [`Aggregate`] that this [`Command`] should be handled for.
     type Aggregate: Aggregate;
 
@@ -43,25 +43,81 @@ pub trait Command {
 }
 
 /// Handler of a specific [`Command`] that processes it for its [`Aggregate`].
-#[async_trait(?Send)]
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 pub trait CommandHandler<Cmd: Command> {
     /// Type of context required by this [`CommandHandler`] for performing
     /// an operation.
     ///
     /// This should be used to provide any additional services/resources
     /// required to handle the [`Command`].
-    type Context: ?Sized;
+    type Context: MaybeSync + ?Sized;
     /// Type of event produced as a result of the [`Command`] handling.
     /// If it doesn't produce any events, consider to specify `()`.
-    type Event;
+    type Event: MaybeSend + MaybeSync;
     /// Type of the error if [`Command`] handling fails. If it never fails,
     /// consider to specify [`Infallible`].
     ///
     /// [`Infallible`]: std::convert::Infallible.
-    type Err;
+    type Err: MaybeSend;
     /// Type of the result of successful [`Command`] handling.
-    type Ok;
+    type Ok: MaybeSend;
 
     /// Handles and processes given [`Command`] for its [`Aggregate`].
     async fn handle(&self, cmd: Cmd, ctx: &Self::Context) -> Result<Self::Ok, Self::Err>;
 }
+
+/// [`Command`] addressed to multiple existing [`Aggregate`]s of the same type
+/// at once, which should be changed together.
+pub trait MultiAggregateCommand: MaybeSend {
+    /// Type of [`Aggregate`]s that this [`MultiAggregateCommand`] should be
+    /// handled for.
+    type Aggregate: Aggregate;
+
+    /// Returns IDs of the [`Aggregate`]s that this [`MultiAggregateCommand`]
+    /// is addressed to.
+    fn aggregate_ids(&self) -> &[<Self::Aggregate as Aggregate>::Id];
+
+    /// Returns expected [`Version`] of the [`Aggregate`] with the given ID.
+    ///
+    /// `None` means that [`MultiAggregateCommand`] may be handled for any
+    /// [`Version`] of this [`Aggregate`]. [`Version::Initial`] means that this
+    /// [`Aggregate`] is expected not to exist yet.
+    #[inline(always)]
+    fn expected_version(&self, _id: &<Self::Aggregate as Aggregate>::Id) -> Option<Version> {
+        None
+    }
+}
+
+/// Handler of a specific [`MultiAggregateCommand`] that processes it for all
+/// its [`Aggregate`]s at once.
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+pub trait MultiAggregateCommandHandler<Cmd>: Aggregate
+where
+    Cmd: MultiAggregateCommand<Aggregate = Self>,
+{
+    /// Type of context required by this [`MultiAggregateCommandHandler`] for
+    /// performing an operation.
+    type Context: MaybeSync + ?Sized;
+    /// Type of event produced as a result of the [`MultiAggregateCommand`]
+    /// handling.
+    type Event: MaybeSend + MaybeSync;
+    /// Type of the error if [`MultiAggregateCommand`] handling fails.
+    type Err: MaybeSend;
+    /// Type of the result of successful [`MultiAggregateCommand`] handling.
+    ///
+    /// Usually, it's a collection of produced events grouped by IDs of the
+    /// [`Aggregate`]s they should be applied to.
+    type Ok: MaybeSend;
+
+    /// Handles and processes given [`MultiAggregateCommand`] for the given
+    /// [`Aggregate`]s.
+    ///
+    /// [`Aggregate`]s which don't exist yet are omitted.
+    async fn handle(
+        cmd: Cmd,
+        aggs: &[HydratedAggregate<Self>],
+        ctx: &Self::Context,
+    ) -> Result<Self::Ok, Self::Err>;
+}
@@ -3,14 +3,21 @@
 #![allow(clippy::module_name_repetitions)]
 
 use std::{
+    collections::VecDeque,
     convert::{Infallible, TryFrom, TryInto as _},
+    error::Error,
     fmt,
-    num::{NonZeroU128, NonZeroU8, TryFromIntError},
+    num::{NonZeroU128, NonZeroU8, NonZeroUsize, TryFromIntError},
 };
 
 use async_trait::async_trait;
+use futures::{future, stream, StreamExt as _, TryFutureExt as _, TryStreamExt as _};
 
-use super::{Aggregate, LocalBoxTryStream, Version};
+use super::{Aggregate, BoxTryStream, MaybeSend, MaybeSync, Version};
+#[cfg(doc)]
+use super::PerEventMeta;
+#[cfg(feature = "serde")]
+use super::{UpcastError, Upcasters};
 
 /// [Event Sourcing] event that describes something that has occurred (happened
 /// fact).
@@ -40,6 +47,14 @@ pub trait EventSourced<Ev: ?Sized> {
 pub trait TypedEvent {
     /// All available types of this [`Event`].
     const EVENT_TYPES: &'static [EventType];
+
+    /// [`EventVersion`]s of all available types of this [`Event`], in the same
+    /// order as [`TypedEvent::EVENT_TYPES`], or [`None`] for the unversioned
+    /// ones.
+    ///
+    /// May be shorter than [`TypedEvent::EVENT_TYPES`], in which case the
+    /// types without a version are considered unversioned. Empty by default.
+    const EVENT_VERSIONS: &'static [Option<EventVersion>] = &[];
 }
 
 /// [`TypedEvent`] with a statically known [`EventType`].
@@ -70,6 +85,64 @@ pub trait StaticVersionedEvent {
     const EVENT_VERSION: EventVersion;
 }
 
+/// [`Event`] (or a set of them) which payload can be serialized with [`serde`].
+///
+/// The serialized payload doesn't contain the [`EventType`] and the
+/// [`EventVersion`] of the [`Event`], so they should be stored along with it,
+/// as they're required to deserialize the payload back via
+/// [`DeserializableEvent`].
+#[cfg(feature = "serde")]
+pub trait SerializableEvent: Event + VersionedEvent {
+    /// Serializes the payload of this [`Event`] with the given [`Serializer`].
+    ///
+    /// [`Serializer`]: serde::Serializer
+    fn serialize_event<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>;
+}
+
+/// [`Event`] (or a set of them) which can be deserialized with [`serde`] out of
+/// a payload tagged with its [`EventType`] and [`EventVersion`].
+#[cfg(feature = "serde")]
+pub trait DeserializableEvent: Sized {
+    /// Indicates whether the payload of an [`Event`] of the given
+    /// [`EventType`] and [`EventVersion`] can be deserialized into this type.
+    fn is_deserializable(event_type: &str, event_version: EventVersion) -> bool;
+
+    /// Deserializes the payload of an [`Event`] of the given [`EventType`] and
+    /// [`EventVersion`] with the given [`Deserializer`].
+    ///
+    /// Returns [`None`] without touching the [`Deserializer`] if the tag is
+    /// not known to this type (see [`DeserializableEvent::is_deserializable`]).
+    ///
+    /// [`Deserializer`]: serde::Deserializer
+    fn deserialize_event<'de, D: serde::Deserializer<'de>>(
+        event_type: &str,
+        event_version: EventVersion,
+        deserializer: D,
+    ) -> Result<Option<Self>, D::Error>;
+
+    /// Upcasts the raw `payload` of an [`Event`] of the given [`EventType`]
+    /// and [`EventVersion`] with the given [`Upcasters`], and deserializes the
+    /// result (see [`DeserializableEvent::deserialize_event`]).
+    ///
+    /// [`EventSource`]s storing raw payloads should use it, so the stored
+    /// [`Event`]s of previous [`EventVersion`]s are deserialized as the actual
+    /// ones.
+    fn deserialize_upcast<'de, P, Err>(
+        upcasters: &Upcasters<P, Err>,
+        event_type: &str,
+        event_version: EventVersion,
+        payload: P,
+    ) -> Result<Option<Self>, UpcastError<Err, P::Error>>
+    where
+        P: serde::Deserializer<'de>,
+    {
+        let (ver, payload) = upcasters
+            .upcast(event_type, event_version, payload)
+            .map_err(UpcastError::Upcast)?;
+        Self::deserialize_event(event_type, ver, payload).map_err(UpcastError::Deserialize)
+    }
+}
+
 /// Structured pair combining an [`Event`] and its [`EventNumber`].
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
 pub struct NumberedEvent<Ev> {
@@ -115,13 +188,13 @@ impl<'a, Ev, Mt> From<&'a NumberedEventWithMeta<Ev, Mt>> for NumberedEvent<&'a E
 }
 
 /// Source of reading all [`Event`]s belonging to some [`Aggregate`].
-pub trait EventSource<Agg, Ev>
+pub trait EventSource<Agg, Ev>: MaybeSync
 where
     Agg: Aggregate + EventSourced<Ev>,
 {
     /// Type of the error if reading [`NumberedEvent`]s fails.
     /// If it never fails, consider to specify [`Infallible`].
-    type Err;
+    type Err: MaybeSend;
 
     /// Reads all stored [`Event`]s of a given [`Aggregate`].
     ///
@@ -139,42 +212,190 @@ where
         &self,
         id: &Agg::Id,
         since: Since,
-    ) -> LocalBoxTryStream<'_, NumberedEvent<Ev>, Self::Err>;
+    ) -> BoxTryStream<'_, NumberedEvent<Ev>, Self::Err>;
+
+    /// Reads all stored [`Event`]s of multiple given [`Aggregate`]s in a single
+    /// batch.
+    ///
+    /// Each yielded [`NumberedEvent`] is paired with the index of its
+    /// [`Aggregate`] in `reqs`. [`Event`]s of the same [`Aggregate`] must be
+    /// yielded in order, while [`Event`]s of different [`Aggregate`]s may be
+    /// interleaved arbitrarily.
+    ///
+    /// By default, reads [`Aggregate`]s' [`Stream`]s via
+    /// [`EventSource::read_events`] concurrently, with at most
+    /// `concurrency_limit` of them being read at the same time. Implementations
+    /// capable of reading multiple [`Aggregate`]s in one round-trip should
+    /// override this method, and may ignore `concurrency_limit`.
+    ///
+    /// [`Stream`]: futures::Stream
+    fn read_events_batch<'a>(
+        &'a self,
+        reqs: &'a [(&'a Agg::Id, Since)],
+        concurrency_limit: NonZeroUsize,
+    ) -> BoxTryStream<'a, (usize, NumberedEvent<Ev>), Self::Err> {
+        Box::pin(
+            stream::iter(reqs.iter().enumerate())
+                .map(move |(i, (id, since))| self.read_events(id, *since).map_ok(move |ev| (i, ev)))
+                .flatten_unordered(concurrency_limit.get()),
+        )
+    }
+
+    /// Reads stored [`Event`]s of a given [`Aggregate`] within the given
+    /// [`ReadRange`].
+    ///
+    /// By default, reads the [`Aggregate`]'s [`Stream`] via
+    /// [`EventSource::read_events`], stopping at the upper bound of the
+    /// [`ReadRange`]. Reading [`ReadDirection::Backward`] buffers the range
+    /// before yielding anything, keeping at most [`ReadRange::limit`] latest
+    /// events buffered (or the whole range, if it's not limited), so
+    /// implementations capable of reading in reverse order natively should
+    /// override this method.
+    ///
+    /// [`Stream`]: futures::Stream
+    fn read_events_range<'a>(
+        &'a self,
+        id: &Agg::Id,
+        range: ReadRange,
+    ) -> BoxTryStream<'a, NumberedEvent<Ev>, Self::Err>
+    where
+        Ev: MaybeSend + 'a,
+        Self::Err: 'a,
+    {
+        let events = self
+            .read_events(id, range.since)
+            .try_take_while(move |ev| future::ok(range.is_within_upper_bound(ev.num)));
+        let limit = range.limit.unwrap_or(usize::MAX);
+        match range.direction {
+            ReadDirection::Forward => Box::pin(events.take(limit)),
+            ReadDirection::Backward if limit == 0 => Box::pin(stream::empty()),
+            ReadDirection::Backward => Box::pin(
+                events
+                    .try_fold(VecDeque::new(), move |mut evs, ev| {
+                        if evs.len() == limit {
+                            let _ = evs.pop_front();
+                        }
+                        evs.push_back(ev);
+                        future::ok(evs)
+                    })
+                    .map_ok(|evs| stream::iter(evs.into_iter().rev().map(Ok)))
+                    .try_flatten_stream(),
+            ),
+        }
+    }
+}
+
+/// [`EventSource`] capable of reading [`Event`]s along with the metadata they
+/// were persisted with by an [`EventSink`].
+pub trait EventSourceWithMeta<Agg, Ev, Mt>: EventSource<Agg, Ev>
+where
+    Agg: Aggregate + EventSourced<Ev>,
+{
+    /// Reads all stored [`Event`]s of a given [`Aggregate`] along with their
+    /// metadata.
+    ///
+    /// Apart from the metadata, the returned [`Stream`] should be the same as
+    /// the one returned by [`EventSource::read_events`].
+    ///
+    /// [`Stream`]: futures::Stream
+    fn read_events_with_meta(
+        &self,
+        id: &Agg::Id,
+        since: Since,
+    ) -> BoxTryStream<'_, NumberedEventWithMeta<Ev, Mt>, Self::Err>;
 }
 
 /// Sink for persisting [`Event`]s belonging to some [`Aggregate`].
-#[async_trait(?Send)]
-pub trait EventSink<Agg, Ev, Mt>
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+pub trait EventSink<Agg, Ev, Mt>: MaybeSync
 where
     Agg: Aggregate,
     Mt: ?Sized,
 {
     /// Type of the error if persisting [`Event`]s fails.
     /// If it never fails, consider to specify [`Infallible`].
-    type Err;
+    type Err: MaybeSend;
     /// Type of returned [`NumberedEvent`]s which were persisted.
     // TODO: Try return NumberedEvent<&E>, to avoid unnecessary cloning in
     //       implementations, when Rust will support GATs, as at the moment
     //       lifetime parameter is required, and providing one complicates
     //       the whole framework code as HRTB conflicts with extracting
     //       associated types.
-    type Ok: IntoIterator<Item = NumberedEvent<Ev>>;
+    type Ok: IntoIterator<Item = NumberedEvent<Ev>> + MaybeSend;
 
     /// Persists given [`Event`]s with associated metadata and returns them
     /// as [`NumberedEvent`]s in the order they were persisted.
     ///
-    /// The associated metadata is applied to all [`Event`]s in the given group.
+    /// The associated metadata belongs to the whole given group, so
+    /// implementations should persist every [`Event`] with the metadata
+    /// individualized for it via [`PerEventMeta::for_event`], if the metadata
+    /// supports it.
     ///
     /// It's responsibility of the implementation to assign a correct
     /// [`EventNumber`] for each [`Event`].
+    ///
+    /// The implementation should atomically check that the stored [`Version`]
+    /// of the [`Aggregate`] equals to the `expected_ver`, and persist nothing
+    /// if it doesn't, returning an error representing the [`VersionConflict`]
+    /// (see [`AsVersionConflict`] for details).
     async fn append_events(
         &self,
         id: &Agg::Id,
+        expected_ver: Version,
         events: &[NumberedEvent<Ev>],
         meta: &Mt,
     ) -> Result<Self::Ok, Self::Err>;
 }
 
+/// Error of the [`Aggregate`]'s stored [`Version`] not matching the expected
+/// one, which indicates a concurrent modification of the [`Aggregate`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct VersionConflict {
+    /// [`Version`] the [`Aggregate`] was expected to have.
+    pub expected: Version,
+
+    /// [`Version`] the [`Aggregate`] actually has.
+    pub actual: Version,
+}
+
+impl fmt::Display for VersionConflict {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "expected aggregate version {}, but actual is {}",
+            self.expected, self.actual,
+        )
+    }
+}
+
+impl Error for VersionConflict {}
+
+/// Error that may represent a [`VersionConflict`].
+///
+/// Should be implemented by [`EventSink::Err`] types, so the
+/// [`VersionConflict`] may be recognized and reported separately from other
+/// failures.
+pub trait AsVersionConflict {
+    /// Returns the [`VersionConflict`] represented by this error, if any.
+    fn as_version_conflict(&self) -> Option<&VersionConflict>;
+}
+
+impl AsVersionConflict for VersionConflict {
+    #[inline]
+    fn as_version_conflict(&self) -> Option<&VersionConflict> {
+        Some(self)
+    }
+}
+
+impl AsVersionConflict for Infallible {
+    #[inline]
+    fn as_version_conflict(&self) -> Option<&VersionConflict> {
+        match *self {}
+    }
+}
+
 /// Type of an [`Event`].
 pub type EventType = &'static str;
 
@@ -424,3 +645,104 @@ impl From<Version> for Since {
         }
     }
 }
+
+/// Direction of reading a stream of values from an [`EventSource`].
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
+pub enum ReadDirection {
+    /// Reads events from the oldest to the newest.
+    Forward,
+    /// Reads events from the newest to the oldest.
+    Backward,
+}
+
+impl Default for ReadDirection {
+    #[inline]
+    fn default() -> Self {
+        Self::Forward
+    }
+}
+
+/// Range of values to be read from a stream of an [`EventSource`].
+///
+/// Reads all the events by default.
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
+pub struct ReadRange {
+    /// Starting point of the range (exclusive).
+    pub since: Since,
+
+    /// [`EventNumber`] of the last event in the range (inclusive), if the
+    /// range is bounded.
+    pub until: Option<EventNumber>,
+
+    /// Maximum number of events to be read, if any.
+    ///
+    /// The limit is applied in the reading [`ReadDirection`], so reading
+    /// [`ReadDirection::Backward`] yields the latest events of the range.
+    pub limit: Option<usize>,
+
+    /// [`ReadDirection`] of reading the range.
+    pub direction: ReadDirection,
+}
+
+impl ReadRange {
+    /// Creates a new [`ReadRange`] of all the events after the given [`Since`]
+    /// point.
+    #[inline]
+    pub fn since<S: Into<Since>>(since: S) -> Self {
+        Self {
+            since: since.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Bounds this [`ReadRange`] with the given [`EventNumber`] (inclusive).
+    #[inline]
+    pub fn until(mut self, num: EventNumber) -> Self {
+        self.until = Some(num);
+        self
+    }
+
+    /// Limits this [`ReadRange`] with the given number of events.
+    #[inline]
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Makes this [`ReadRange`] to be read from the newest events to the
+    /// oldest ones.
+    #[inline]
+    pub fn backward(mut self) -> Self {
+        self.direction = ReadDirection::Backward;
+        self
+    }
+
+    /// Indicates whether the given [`EventNumber`] doesn't exceed the upper
+    /// bound of this [`ReadRange`].
+    #[inline]
+    pub fn is_within_upper_bound(&self, num: EventNumber) -> bool {
+        match self.until {
+            Some(until) => num <= until,
+            None => true,
+        }
+    }
+}
+
+impl Default for ReadRange {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            since: Since::BeginningOfStream,
+            until: None,
+            limit: None,
+            direction: ReadDirection::default(),
+        }
+    }
+}
+
+impl From<Since> for ReadRange {
+    #[inline]
+    fn from(since: Since) -> Self {
+        Self::since(since)
+    }
+}
@@ -28,24 +28,67 @@ mod command;
 
 mod event;
 //mod into;
+mod meta;
+mod subscription;
+mod upcast;
 
 use std::pin::Pin;
 
 use futures::Stream;
 
 #[doc(inline)]
-pub use self::{aggregate::*, command::*, event::*};
+pub use self::{aggregate::*, command::*, event::*, meta::*, subscription::*, upcast::*};
 
 /// Helper alias for pin-boxed `?Send` [`Stream`] which yields [`Result`]s.
 pub type LocalBoxTryStream<'a, I, E> = Pin<Box<dyn Stream<Item = Result<I, E>> + 'a>>;
 
+/// Helper alias for pin-boxed [`Stream`] which yields [`Result`]s, and is
+/// `Send` only if the `send` feature is enabled.
+#[cfg(feature = "send")]
+pub type BoxTryStream<'a, I, E> = Pin<Box<dyn Stream<Item = Result<I, E>> + Send + 'a>>;
+
+/// Helper alias for pin-boxed [`Stream`] which yields [`Result`]s, and is
+/// `Send` only if the `send` feature is enabled.
+#[cfg(not(feature = "send"))]
+pub type BoxTryStream<'a, I, E> = LocalBoxTryStream<'a, I, E>;
+
+/// Marker trait implying [`Send`] only if the `send` feature is enabled.
+#[cfg(feature = "send")]
+pub trait MaybeSend: Send {}
+
+#[cfg(feature = "send")]
+impl<T: Send + ?Sized> MaybeSend for T {}
+
+/// Marker trait implying [`Send`] only if the `send` feature is enabled.
+#[cfg(not(feature = "send"))]
+pub trait MaybeSend {}
+
+#[cfg(not(feature = "send"))]
+impl<T: ?Sized> MaybeSend for T {}
+
+/// Marker trait implying [`Sync`] only if the `send` feature is enabled.
+#[cfg(feature = "send")]
+pub trait MaybeSync: Sync {}
+
+#[cfg(feature = "send")]
+impl<T: Sync + ?Sized> MaybeSync for T {}
+
+/// Marker trait implying [`Sync`] only if the `send` feature is enabled.
+#[cfg(not(feature = "send"))]
+pub trait MaybeSync {}
+
+#[cfg(not(feature = "send"))]
+impl<T: ?Sized> MaybeSync for T {}
+
 #[doc(hidden)]
 pub mod private {
-    /// Slices an array of strings at compile time.
-    pub const fn slice_arr<const N: usize>(
-        arr: &'static [&'static str; N],
-        at: usize,
-    ) -> &'static [&'static str] {
+    #[cfg(feature = "serde")]
+    pub use serde;
+
+    use super::{EventType, EventVersion};
+
+    /// Slices an array at compile time.
+    pub const fn slice_arr<T, const N: usize>(arr: &'static [T; N], at: usize) -> &'static [T] {
         if at > N {
             panic!("index out of bounds");
         }
@@ -54,4 +97,101 @@ pub mod private {
             std::slice::from_raw_parts(arr.as_ptr(), at)
         }
     }
+
+    /// Asserts at compile time that the given [`EventType`]s don't contain
+    /// duplicates of the same [`EventVersion`].
+    ///
+    /// `versions` are expected to be in the same order as `types`, and the
+    /// `types` without a version are considered unversioned.
+    pub const fn assert_unique_event_types(types: &[EventType], versions: &[Option<EventVersion>]) {
+        let mut i = 0;
+        while i < types.len() {
+            let mut j = i + 1;
+            while j < types.len() {
+                let ver = version_at(versions, i);
+                if str_eq(types[i], types[j]) && version_eq(ver, version_at(versions, j)) {
+                    panic_duplicate_event_type(types[i], ver);
+                }
+                j += 1;
+            }
+            i += 1;
+        }
+    }
+
+    /// Compares the given strings at compile time.
+    const fn str_eq(a: &str, b: &str) -> bool {
+        let (a, b) = (a.as_bytes(), b.as_bytes());
+        if a.len() != b.len() {
+            return false;
+        }
+        let mut i = 0;
+        while i < a.len() {
+            if a[i] != b[i] {
+                return false;
+            }
+            i += 1;
+        }
+        true
+    }
+
+    /// Returns [`EventVersion`] at the given index, if any.
+    const fn version_at(versions: &[Option<EventVersion>], i: usize) -> Option<EventVersion> {
+        if i < versions.len() {
+            versions[i]
+        } else {
+            None
+        }
+    }
+
+    /// Compares the given [`EventVersion`]s at compile time.
+    const fn version_eq(a: Option<EventVersion>, b: Option<EventVersion>) -> bool {
+        match (a, b) {
+            (Some(a), Some(b)) => a.into_u8() == b.into_u8(),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    /// Panics at compile time with a message describing the duplicated
+    /// [`EventType`] of the given [`EventVersion`].
+    const fn panic_duplicate_event_type(ty: EventType, ver: Option<EventVersion>) -> ! {
+        const MAX_LEN: usize = 256;
+
+        /// Appends the given bytes to the message, truncating them if the
+        /// message is full.
+        const fn append(
+            (mut msg, mut len): ([u8; MAX_LEN], usize),
+            bytes: &[u8],
+        ) -> ([u8; MAX_LEN], usize) {
+            let mut i = 0;
+            while i < bytes.len() && len < MAX_LEN {
+                msg[len] = bytes[i];
+                len += 1;
+                i += 1;
+            }
+            (msg, len)
+        }
+
+        let mut msg = append(([0; MAX_LEN], 0), b"duplicate event type `");
+        msg = append(msg, ty.as_bytes());
+        msg = append(msg, b"`");
+        if let Some(ver) = ver {
+            let ver = ver.into_u8();
+            let digits = [b'0' + ver / 100, b'0' + ver / 10 % 10, b'0' + ver % 10];
+            let skip = if ver >= 100 {
+                0
+            } else if ver >= 10 {
+                1
+            } else {
+                2
+            };
+            msg = append(msg, b" of version ");
+            msg = append(msg, digits.split_at(skip).1);
+        }
+
+        match std::str::from_utf8(msg.0.split_at(msg.1).0) {
+            Ok(msg) => panic!("{}", msg),
+            Err(_) => panic!("duplicate event type"),
+        }
+    }
 }
@@ -0,0 +1,183 @@
+//! Standard metadata of events.
+
+use std::time::SystemTime;
+
+use super::EventNumber;
+#[cfg(doc)]
+use super::{Aggregate, Command, Event, EventSink};
+
+/// Metadata of [`Event`]s appended at once, which is individualized for every
+/// single [`Event`] of them.
+///
+/// [`EventSink`]s persist every [`Event`] with the metadata returned by
+/// [`for_event`], given the ID of its [`Aggregate`] and its [`EventNumber`].
+/// Individualizing must be deterministic, so the [`Event`]s caused by an
+/// [`Event`] refer to the very metadata it has been persisted with.
+///
+/// Metadata, which doesn't identify [`Event`]s, may simply return its copy.
+///
+/// [`for_event`]: PerEventMeta::for_event
+pub trait PerEventMeta<AggId: ?Sized> {
+    /// Creates metadata of the [`Event`] of the given number of the
+    /// [`Aggregate`] with the given ID, which is appended along with the other
+    /// [`Event`]s having this metadata.
+    fn for_event(&self, id: &AggId, num: EventNumber) -> Self;
+}
+
+impl<AggId: ?Sized> PerEventMeta<AggId> for () {
+    #[inline(always)]
+    fn for_event(&self, _: &AggId, _: EventNumber) -> Self {}
+}
+
+/// Metadata of [`Event`]s, which is propagated to the [`Event`]s caused by
+/// them.
+///
+/// When an [`Event`] handler dispatches a [`Command`], the [`Event`]s produced
+/// by the [`Command`] are persisted with the metadata [`caused`] by the
+/// metadata of the handled [`Event`].
+///
+/// Metadata, which doesn't track causation, may simply return its copy.
+///
+/// [`caused`]: CausalMeta::caused
+pub trait CausalMeta {
+    /// Creates metadata of the [`Event`]s caused by the [`Event`] having this
+    /// metadata.
+    fn caused(&self) -> Self;
+}
+
+impl CausalMeta for () {
+    #[inline(always)]
+    fn caused(&self) -> Self {}
+}
+
+/// Metadata of [`Event`]s, which knows the moment the [`Event`]s have
+/// occurred.
+pub trait TimestampedMeta {
+    /// Returns the moment when the [`Event`]s have occurred.
+    fn timestamp(&self) -> SystemTime;
+}
+
+/// Type of unique identifiers, which can be generated on demand.
+pub trait GenerateId {
+    /// Generates a new unique identifier.
+    fn generate_id() -> Self;
+}
+
+/// Type of unique identifiers, from which the identifiers of the [`Event`]s
+/// of different [`Aggregate`]s can be derived.
+pub trait DeriveId<AggId: ?Sized> {
+    /// Derives a new unique identifier of the [`Event`] of the given number of
+    /// the [`Aggregate`] with the given ID.
+    ///
+    /// Deriving must be deterministic, while the identifiers derived for
+    /// different [`Event`]s must not collide.
+    fn derive_id(&self, id: &AggId, num: EventNumber) -> Self;
+}
+
+/// Standard metadata envelope of [`Event`]s.
+///
+/// [`Event`]s appended at once by an [`EventSink`] are persisted with their
+/// own [`EventMeta`]s, each having the ID [derived] from the one of the
+/// [`EventMeta`] they're appended with.
+///
+/// [derived]: DeriveId::derive_id
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct EventMeta<Id, Actor> {
+    /// Unique ID of the [`Event`].
+    pub event_id: Id,
+
+    /// Moment when the [`Event`] has occurred.
+    pub timestamp: SystemTime,
+
+    /// ID of the chain of causation the [`Event`] belongs to.
+    ///
+    /// Equals to the [`EventMeta::event_id`] of the [`EventMeta`] which has
+    /// started the chain.
+    pub correlation_id: Id,
+
+    /// ID of the [`Event`] which has directly caused this [`Event`], if any.
+    pub causation_id: Option<Id>,
+
+    /// Actor which has initiated the [`Event`], if known.
+    pub actor: Option<Actor>,
+}
+
+impl<Id, Actor> EventMeta<Id, Actor>
+where
+    Id: Clone + GenerateId,
+{
+    /// Creates a new [`EventMeta`] of [`Event`]s, which are not caused by any
+    /// other ones, starting a new chain of causation.
+    pub fn new() -> Self {
+        let event_id = Id::generate_id();
+        Self {
+            correlation_id: event_id.clone(),
+            event_id,
+            timestamp: SystemTime::now(),
+            causation_id: None,
+            actor: None,
+        }
+    }
+
+    /// Sets the actor which has initiated the [`Event`]s.
+    #[inline]
+    pub fn with_actor(mut self, actor: Actor) -> Self {
+        self.actor = Some(actor);
+        self
+    }
+}
+
+impl<Id, Actor> Default for EventMeta<Id, Actor>
+where
+    Id: Clone + GenerateId,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Id, Actor, AggId> PerEventMeta<AggId> for EventMeta<Id, Actor>
+where
+    Id: Clone + DeriveId<AggId>,
+    Actor: Clone,
+    AggId: ?Sized,
+{
+    /// Creates [`EventMeta`] of the ID derived from the one of this
+    /// [`EventMeta`], sharing everything else with it.
+    fn for_event(&self, id: &AggId, num: EventNumber) -> Self {
+        Self {
+            event_id: self.event_id.derive_id(id, num),
+            timestamp: self.timestamp,
+            correlation_id: self.correlation_id.clone(),
+            causation_id: self.causation_id.clone(),
+            actor: self.actor.clone(),
+        }
+    }
+}
+
+impl<Id, Actor> CausalMeta for EventMeta<Id, Actor>
+where
+    Id: Clone + GenerateId,
+    Actor: Clone,
+{
+    /// Creates [`EventMeta`] of a new ID and timestamp, caused by this
+    /// [`EventMeta`] and initiated by the same actor.
+    fn caused(&self) -> Self {
+        Self {
+            event_id: Id::generate_id(),
+            timestamp: SystemTime::now(),
+            correlation_id: self.correlation_id.clone(),
+            causation_id: Some(self.event_id.clone()),
+            actor: self.actor.clone(),
+        }
+    }
+}
+
+impl<Id, Actor> TimestampedMeta for EventMeta<Id, Actor> {
+    #[inline]
+    fn timestamp(&self) -> SystemTime {
+        self.timestamp
+    }
+}
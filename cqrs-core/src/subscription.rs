@@ -0,0 +1,175 @@
+//! Reading [`Event`]s across the streams of all the [`Aggregate`]s.
+
+use super::{AggregateType, BoxTryStream, EventType, MaybeSend, MaybeSync, NumberedEvent};
+
+#[cfg(doc)]
+use super::{Aggregate, Event, EventSource};
+
+/// Position of an [`Event`] in the global order of all the [`Event`]s stored
+/// in some storage.
+///
+/// Unlike an [`EventNumber`], which orders [`Event`]s within a single
+/// [`Aggregate`]'s stream, [`GlobalPosition`]s order the [`Event`]s of all the
+/// streams in the order they were committed. Storing the [`GlobalPosition`] of
+/// the last processed [`Event`] allows to resume reading right after it.
+///
+/// [`EventNumber`]: super::EventNumber
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct GlobalPosition(u64);
+
+impl GlobalPosition {
+    /// [`GlobalPosition`] preceding all the stored [`Event`]s.
+    pub const BEGINNING: Self = Self(0);
+
+    /// Creates a new [`GlobalPosition`] out of the given number, where `0`
+    /// stands for the [`GlobalPosition::BEGINNING`].
+    #[inline]
+    pub const fn new(pos: u64) -> Self {
+        Self(pos)
+    }
+
+    /// Returns the number of this [`GlobalPosition`].
+    #[inline]
+    pub const fn get(self) -> u64 {
+        self.0
+    }
+}
+
+impl From<u64> for GlobalPosition {
+    #[inline]
+    fn from(pos: u64) -> Self {
+        Self::new(pos)
+    }
+}
+
+impl From<GlobalPosition> for u64 {
+    #[inline]
+    fn from(pos: GlobalPosition) -> Self {
+        pos.get()
+    }
+}
+
+/// [`Event`] read from the streams of all the [`Aggregate`]s along with its
+/// [`GlobalPosition`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GlobalEvent<Id, Ev, Mt> {
+    /// [`GlobalPosition`] of the [`Event`].
+    pub position: GlobalPosition,
+
+    /// Type of the [`Aggregate`] the [`Event`] belongs to.
+    pub aggregate_type: AggregateType,
+
+    /// ID of the [`Aggregate`] the [`Event`] belongs to.
+    pub id: Id,
+
+    /// The [`Event`] itself, numbered within its [`Aggregate`]'s stream.
+    pub event: NumberedEvent<Ev>,
+
+    /// Metadata the [`Event`] was persisted with.
+    pub meta: Mt,
+}
+
+/// Filter of the [`Event`]s read by a [`TypedGlobalEventSource`].
+///
+/// Matches all the [`Event`]s by default.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct EventFilter {
+    /// Types of the [`Aggregate`]s to read the [`Event`]s of, or [`None`] to
+    /// read the [`Event`]s of any [`Aggregate`].
+    pub aggregate_types: Option<Vec<AggregateType>>,
+
+    /// Types of the [`Event`]s to read, or [`None`] to read the [`Event`]s of
+    /// any type.
+    pub event_types: Option<Vec<EventType>>,
+}
+
+impl EventFilter {
+    /// Creates a new [`EventFilter`] matching all the [`Event`]s.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Restricts this [`EventFilter`] to the [`Event`]s of the [`Aggregate`]s
+    /// of the given types only.
+    #[inline]
+    pub fn with_aggregate_types<T>(mut self, types: T) -> Self
+    where
+        T: IntoIterator<Item = AggregateType>,
+    {
+        self.aggregate_types = Some(types.into_iter().collect());
+        self
+    }
+
+    /// Restricts this [`EventFilter`] to the [`Event`]s of the given types
+    /// only.
+    #[inline]
+    pub fn with_event_types<T>(mut self, types: T) -> Self
+    where
+        T: IntoIterator<Item = EventType>,
+    {
+        self.event_types = Some(types.into_iter().collect());
+        self
+    }
+
+    /// Indicates whether this [`EventFilter`] matches an [`Event`] of the
+    /// given type, belonging to an [`Aggregate`] of the given type.
+    pub fn matches(&self, aggregate_type: AggregateType, event_type: EventType) -> bool {
+        fn allows<T: PartialEq>(types: &Option<Vec<T>>, ty: &T) -> bool {
+            match types {
+                Some(types) => types.contains(ty),
+                None => true,
+            }
+        }
+        allows(&self.aggregate_types, &aggregate_type) && allows(&self.event_types, &event_type)
+    }
+}
+
+/// Source for reading [`Event`]s of the given types across the streams of all
+/// the [`Aggregate`]s in the order they were committed.
+///
+/// Unlike an [`EventSource`], which reads a single [`Aggregate`]'s stream, it
+/// allows building projections and reactors without depending on a concrete
+/// storage.
+///
+/// Note, that all the read [`Event`]s are of the same `Ev` type, and belong to
+/// the [`Aggregate`]s of the same `Id` type. So, a storage holding several
+/// [`Aggregate`] types can implement it only if their IDs and [`Event`]s share
+/// the same types (an `enum` of all the [`Event`]s, for example). Otherwise,
+/// it should implement it for every [`Aggregate`] type separately, and each
+/// implementation reads the streams of a single [`Aggregate`] type only.
+pub trait TypedGlobalEventSource<Id, Ev, Mt>: MaybeSync {
+    /// Type of the error of reading [`Event`]s.
+    /// If it never fails, consider to specify [`Infallible`].
+    ///
+    /// [`Infallible`]: std::convert::Infallible
+    type Err: MaybeSend;
+
+    /// Reads the stored [`Event`]s matching the given [`EventFilter`] after
+    /// the given [`GlobalPosition`], in their global order.
+    ///
+    /// The returned [`Stream`] completes once all the currently stored
+    /// [`Event`]s are read.
+    ///
+    /// [`Stream`]: futures::Stream
+    fn read_all_events(
+        &self,
+        after: GlobalPosition,
+        filter: EventFilter,
+    ) -> BoxTryStream<'_, GlobalEvent<Id, Ev, Mt>, Self::Err>;
+
+    /// Subscribes to the [`Event`]s matching the given [`EventFilter`] after
+    /// the given [`GlobalPosition`], in their global order.
+    ///
+    /// The returned [`Stream`] catches up with the stored [`Event`]s first, and
+    /// then yields the newly committed [`Event`]s as they appear, so it never
+    /// completes on its own.
+    ///
+    /// [`Stream`]: futures::Stream
+    fn subscribe_all_events(
+        &self,
+        after: GlobalPosition,
+        filter: EventFilter,
+    ) -> BoxTryStream<'_, GlobalEvent<Id, Ev, Mt>, Self::Err>;
+}
@@ -0,0 +1,172 @@
+//! Upcasting raw payloads of [`Event`]s of previous versions.
+
+use std::{collections::HashMap, error::Error, fmt};
+
+use super::{EventType, EventVersion, StaticTypedEvent};
+
+#[cfg(all(doc, feature = "serde"))]
+use super::DeserializableEvent;
+#[cfg(doc)]
+use super::{Event, EventSource};
+
+/// Function transforming a raw payload of an [`Event`] of some
+/// [`EventVersion`] into the raw payload of the next [`EventVersion`].
+pub type Upcaster<P, Err> = fn(P) -> Result<P, Err>;
+
+/// Registry of [`Upcaster`]s, which transforms raw payloads of stored
+/// [`Event`]s of previous [`EventVersion`]s into the actual ones.
+///
+/// [`Upcaster`]s are registered per [`EventType`] and are chained, so an
+/// [`Event`] of version `1` is upcast to version `2`, then to version `3`, and
+/// so on, until there is no [`Upcaster`] for its current [`EventVersion`].
+///
+/// [`EventSource`]s storing raw payloads should apply [`Upcasters::upcast`]
+/// before deserializing them (see [`DeserializableEvent::deserialize_upcast`]),
+/// so the [`Event`]s can evolve without rewriting the already stored ones.
+pub struct Upcasters<P, Err> {
+    upcasters: HashMap<EventType, HashMap<EventVersion, Upcaster<P, Err>>>,
+}
+
+impl<P, Err> Upcasters<P, Err> {
+    /// Creates a new empty [`Upcasters`] registry.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            upcasters: HashMap::new(),
+        }
+    }
+
+    /// Registers the given [`Upcaster`] of the raw payloads of the [`Event`]s
+    /// of the given [`EventType`] from the given [`EventVersion`] to the next
+    /// one.
+    ///
+    /// Replaces the [`Upcaster`] registered previously for the same
+    /// [`EventType`] and [`EventVersion`], if any.
+    #[inline]
+    pub fn register(
+        &mut self,
+        event_type: EventType,
+        from: EventVersion,
+        upcaster: Upcaster<P, Err>,
+    ) {
+        let _ = self
+            .upcasters
+            .entry(event_type)
+            .or_default()
+            .insert(from, upcaster);
+    }
+
+    /// Registers all the [`Upcaster`]s of the given [`UpcastableEvent`].
+    #[inline]
+    pub fn register_event<Ev>(&mut self)
+    where
+        Ev: UpcastableEvent<P, Err>,
+    {
+        Ev::register_upcasters(self)
+    }
+
+    /// Indicates whether any [`Upcaster`] is registered for the [`Event`]s of
+    /// the given [`EventType`] and [`EventVersion`].
+    #[inline]
+    pub fn is_upcastable(&self, event_type: &str, ver: EventVersion) -> bool {
+        match self.upcasters.get(event_type) {
+            Some(upcasters) => upcasters.contains_key(&ver),
+            None => false,
+        }
+    }
+
+    /// Upcasts the given raw payload of an [`Event`] of the given [`EventType`]
+    /// and [`EventVersion`] through the chain of the registered [`Upcaster`]s.
+    ///
+    /// Returns the upcast payload along with its resulting [`EventVersion`],
+    /// or the given ones as is, if there is no [`Upcaster`] for them.
+    pub fn upcast(
+        &self,
+        event_type: &str,
+        mut ver: EventVersion,
+        mut payload: P,
+    ) -> Result<(EventVersion, P), Err> {
+        let upcasters = match self.upcasters.get(event_type) {
+            Some(upcasters) => upcasters,
+            None => return Ok((ver, payload)),
+        };
+        while let Some(upcast) = upcasters.get(&ver) {
+            let next = match ver.into_u8().checked_add(1).and_then(EventVersion::new) {
+                Some(next) => next,
+                None => break,
+            };
+            payload = upcast(payload)?;
+            ver = next;
+        }
+        Ok((ver, payload))
+    }
+}
+
+impl<P, Err> Default for Upcasters<P, Err> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P, Err> Clone for Upcasters<P, Err> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            upcasters: self.upcasters.clone(),
+        }
+    }
+}
+
+impl<P, Err> fmt::Debug for Upcasters<P, Err> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.upcasters.iter().map(|(ty, upcasters)| {
+                let mut vers = upcasters.keys().copied().collect::<Vec<_>>();
+                vers.sort();
+                (ty, vers)
+            }))
+            .finish()
+    }
+}
+
+/// [`Event`] providing [`Upcaster`]s of its raw payloads of previous
+/// [`EventVersion`]s.
+pub trait UpcastableEvent<P, Err>: StaticTypedEvent {
+    /// Registers all the [`Upcaster`]s of this [`Event`] in the given
+    /// [`Upcasters`] registry.
+    fn register_upcasters(upcasters: &mut Upcasters<P, Err>);
+}
+
+/// Error of upcasting and deserializing a raw payload of an [`Event`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum UpcastError<U, D> {
+    /// [`Upcaster`] has failed.
+    Upcast(U),
+    /// Deserializing of the upcast payload has failed.
+    Deserialize(D),
+}
+
+impl<U: fmt::Display, D: fmt::Display> fmt::Display for UpcastError<U, D> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Upcast(e) => write!(f, "upcasting event payload failed: {}", e),
+            Self::Deserialize(e) => write!(f, "deserializing event payload failed: {}", e),
+        }
+    }
+}
+
+impl<U, D> Error for UpcastError<U, D>
+where
+    U: Error + 'static,
+    D: Error + 'static,
+{
+    #[inline]
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Upcast(e) => Some(e),
+            Self::Deserialize(e) => Some(e),
+        }
+    }
+}
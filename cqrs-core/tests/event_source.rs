@@ -0,0 +1,128 @@
+use std::{collections::HashMap, convert::Infallible, num::NonZeroUsize};
+
+use cqrs_core as cqrs;
+use futures::{executor::block_on, stream, TryStreamExt as _};
+
+/// Test aggregate with no state.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct TestAggregate;
+
+impl cqrs::Aggregate for TestAggregate {
+    type Id = u8;
+
+    fn aggregate_type(&self) -> cqrs::AggregateType {
+        "test"
+    }
+
+    fn id(&self) -> &Self::Id {
+        &0
+    }
+}
+
+/// Test event carrying the ID of its aggregate.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct TestEvent(u8);
+
+impl cqrs::EventSourced<TestEvent> for TestAggregate {
+    fn apply(&mut self, _: &TestEvent) {}
+}
+
+/// Test source storing events of each aggregate separately.
+#[derive(Debug, Default)]
+pub struct TestSource(HashMap<u8, Vec<cqrs::NumberedEvent<TestEvent>>>);
+
+impl TestSource {
+    fn with_events(events: &[(u8, u8)]) -> Self {
+        let mut src = Self::default();
+        for &(id, count) in events {
+            let _ = src.0.insert(
+                id,
+                (1..=count)
+                    .map(|n| cqrs::NumberedEvent {
+                        num: cqrs::EventNumber::new(n).unwrap(),
+                        data: TestEvent(id),
+                    })
+                    .collect(),
+            );
+        }
+        src
+    }
+}
+
+impl cqrs::EventSource<TestAggregate, TestEvent> for TestSource {
+    type Err = Infallible;
+
+    fn read_events(
+        &self,
+        id: &u8,
+        since: cqrs::Since,
+    ) -> cqrs::BoxTryStream<'_, cqrs::NumberedEvent<TestEvent>, Infallible> {
+        let events = self
+            .0
+            .get(id)
+            .into_iter()
+            .flatten()
+            .filter(move |ev| match since {
+                cqrs::Since::BeginningOfStream => true,
+                cqrs::Since::Event(num) => ev.num > num,
+            })
+            .map(|ev| Ok(*ev));
+        Box::pin(stream::iter(events))
+    }
+}
+
+#[test]
+fn reads_events_batch_by_default_via_read_events() {
+    use cqrs::EventSource as _;
+
+    let src = TestSource::with_events(&[(1, 3), (2, 2), (3, 1)]);
+    let reqs = [
+        (&1, cqrs::Since::BeginningOfStream),
+        (&2, cqrs::Since::Event(cqrs::EventNumber::MIN_VALUE)),
+        (&4, cqrs::Since::BeginningOfStream),
+    ];
+
+    let mut events = block_on(
+        src.read_events_batch(&reqs, NonZeroUsize::new(2).unwrap())
+            .try_collect::<Vec<_>>(),
+    )
+    .unwrap();
+    events.sort_by_key(|(i, _)| *i);
+
+    let nums = events
+        .iter()
+        .map(|(i, ev)| (*i, ev.data.0, u128::from(ev.num)))
+        .collect::<Vec<_>>();
+    assert_eq!(nums, vec![(0, 1, 1), (0, 1, 2), (0, 1, 3), (1, 2, 2)]);
+}
+
+#[test]
+fn reads_events_range_by_default_via_read_events() {
+    use cqrs::EventSource as _;
+
+    let src = TestSource::with_events(&[(1, 5)]);
+    let read = |range| {
+        block_on(src.read_events_range(&1, range).try_collect::<Vec<_>>())
+            .unwrap()
+            .into_iter()
+            .map(|ev| u128::from(ev.num))
+            .collect::<Vec<_>>()
+    };
+    let num = |n: u8| cqrs::EventNumber::new(n).unwrap();
+
+    assert_eq!(read(cqrs::ReadRange::default()), vec![1, 2, 3, 4, 5]);
+    assert_eq!(
+        read(cqrs::ReadRange::since(num(1)).until(num(4))),
+        vec![2, 3, 4],
+    );
+    assert_eq!(read(cqrs::ReadRange::default().limit(2)), vec![1, 2]);
+    assert_eq!(
+        read(cqrs::ReadRange::default().until(num(4)).limit(3).backward()),
+        vec![4, 3, 2],
+    );
+    assert_eq!(
+        read(cqrs::ReadRange::since(num(2)).backward()),
+        vec![5, 4, 3],
+    );
+    assert_eq!(read(cqrs::ReadRange::default().limit(0).backward()), vec![]);
+}
@@ -0,0 +1,175 @@
+use std::{
+    convert::TryFrom,
+    time::{Duration, SystemTime},
+};
+
+use cqrs::{
+    AlwaysSnapshot, EventNumber, HydratedAggregate, NeverSnapshot, NumberedEvent, SnapshotAfter,
+    SnapshotAll, SnapshotAny, SnapshotEvery, SnapshotRecommendation,
+    SnapshotRecommendation::{DoNotSnapshot, ShouldSnapshot},
+    SnapshotStrategy, Version,
+};
+use cqrs_core as cqrs;
+
+/// Test aggregate with no state.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct TestAggregate;
+
+impl cqrs::Aggregate for TestAggregate {
+    type Id = u8;
+
+    fn aggregate_type(&self) -> cqrs::AggregateType {
+        "test"
+    }
+
+    fn id(&self) -> &Self::Id {
+        &0
+    }
+}
+
+impl cqrs::EventSourced<()> for TestAggregate {
+    fn apply(&mut self, _: &()) {}
+}
+
+/// Creates a [`HydratedAggregate`] of the given [`Version`], having the given
+/// number of events applied since its latest snapshot.
+fn aggregate(ver: u8, since_snapshot: u8) -> HydratedAggregate<TestAggregate> {
+    let snapshot_ver = Version::new(ver - since_snapshot);
+    let mut agg = HydratedAggregate::from_version(TestAggregate, Version::new(ver));
+    if snapshot_ver != Version::Initial {
+        agg.set_snapshot_version(snapshot_ver);
+    }
+    agg
+}
+
+/// Gives the [`SnapshotRecommendation`] of the given [`SnapshotStrategy`] for
+/// the given [`HydratedAggregate`], as the lifecycle does.
+fn recommend(
+    strategy: &dyn SnapshotStrategy,
+    agg: &HydratedAggregate<TestAggregate>,
+) -> SnapshotRecommendation {
+    strategy.recommendation_at(
+        agg.version(),
+        agg.snapshot_version(),
+        agg.snapshot_taken_at(),
+    )
+}
+
+#[test]
+fn counts_events_since_snapshot() {
+    assert_eq!(aggregate(5, 5).events_since_snapshot(), 5);
+    assert_eq!(aggregate(5, 2).events_since_snapshot(), 2);
+    assert_eq!(aggregate(5, 0).events_since_snapshot(), 0);
+}
+
+#[test]
+fn snapshots_every_n_events() {
+    let strategy = SnapshotEvery::<3>;
+
+    assert_eq!(recommend(&strategy, &aggregate(2, 2)), DoNotSnapshot);
+    assert_eq!(recommend(&strategy, &aggregate(3, 3)), ShouldSnapshot);
+    assert_eq!(recommend(&strategy, &aggregate(7, 2)), DoNotSnapshot);
+    assert_eq!(recommend(&strategy, &aggregate(9, 4)), ShouldSnapshot);
+}
+
+#[test]
+fn snapshots_after_period() {
+    let long = SnapshotAfter(Duration::from_secs(3600));
+    let instant = SnapshotAfter(Duration::from_secs(0));
+
+    assert_eq!(recommend(&long, &aggregate(2, 2)), ShouldSnapshot);
+    assert_eq!(recommend(&long, &aggregate(4, 2)), DoNotSnapshot);
+    assert_eq!(recommend(&instant, &aggregate(4, 2)), ShouldSnapshot);
+    assert_eq!(recommend(&instant, &aggregate(4, 0)), DoNotSnapshot);
+}
+
+/// Creates a [`HydratedAggregate`] loaded from a snapshot taken the given time
+/// ago, with a single event applied on top of it.
+fn loaded(taken_ago: Duration) -> HydratedAggregate<TestAggregate> {
+    let mut agg = HydratedAggregate::from_snapshot_taken_at(
+        TestAggregate,
+        Version::new(2u8),
+        SystemTime::now() - taken_ago,
+    );
+    agg.apply(NumberedEvent {
+        num: EventNumber::new(3u8).unwrap(),
+        data: &(),
+    });
+    agg
+}
+
+#[test]
+fn snapshots_after_period_since_loaded_snapshot() {
+    let strategy = SnapshotAfter(Duration::from_secs(3600));
+
+    assert_eq!(
+        recommend(&strategy, &loaded(Duration::from_secs(60))),
+        DoNotSnapshot,
+    );
+    assert_eq!(
+        recommend(&strategy, &loaded(Duration::from_secs(7200))),
+        ShouldSnapshot,
+    );
+}
+
+#[test]
+fn combines_strategies() {
+    let agg = aggregate(4, 2);
+
+    assert_eq!(
+        recommend(&SnapshotAny(NeverSnapshot, AlwaysSnapshot), &agg),
+        ShouldSnapshot
+    );
+    assert_eq!(
+        recommend(&SnapshotAny(NeverSnapshot, SnapshotEvery::<3>), &agg),
+        DoNotSnapshot
+    );
+    assert_eq!(
+        recommend(&SnapshotAll(AlwaysSnapshot, SnapshotEvery::<2>), &agg),
+        ShouldSnapshot
+    );
+    assert_eq!(
+        recommend(
+            &SnapshotAll(SnapshotAfter(Duration::from_secs(0)), SnapshotEvery::<3>),
+            &agg,
+        ),
+        DoNotSnapshot,
+    );
+}
+
+#[test]
+fn combines_strategies_knowing_snapshot_moment() {
+    let agg = loaded(Duration::from_secs(60));
+    let after_hour = SnapshotAfter(Duration::from_secs(3600));
+
+    assert_eq!(
+        recommend(&SnapshotAny(NeverSnapshot, after_hour), &agg),
+        DoNotSnapshot
+    );
+    assert_eq!(
+        recommend(&SnapshotAll(AlwaysSnapshot, after_hour), &agg),
+        DoNotSnapshot
+    );
+}
+
+/// [`SnapshotStrategy`] implementing only the required method.
+struct OnEvenVersion;
+
+impl SnapshotStrategy for OnEvenVersion {
+    fn recommendation(&self, ver: Version, _: Option<Version>) -> SnapshotRecommendation {
+        if u64::try_from(ver - Version::Initial).unwrap() % 2 == 0 {
+            ShouldSnapshot
+        } else {
+            DoNotSnapshot
+        }
+    }
+}
+
+#[test]
+fn uses_required_recommendation_by_default() {
+    assert_eq!(
+        recommend(&OnEvenVersion, &loaded(Duration::from_secs(60))),
+        DoNotSnapshot
+    );
+    assert_eq!(recommend(&OnEvenVersion, &aggregate(4, 2)), ShouldSnapshot);
+}
@@ -50,7 +50,8 @@ impl cqrs::Command for TestCommand {
     }
 }
 
-#[async_trait(?Send)]
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 impl cqrs::CommandHandler<TestCommand> for TestAggregate {
     type Context = ();
     type Err = Infallible;
@@ -14,29 +14,12 @@ mod graphql;
 
 use r2d2_postgres::PostgresConnectionManager;
 
-#[derive(Clone, Copy, Default, Debug, Hash, PartialEq, Eq)]
-struct SnapshotEvery10;
-
-impl cqrs::SnapshotStrategy for SnapshotEvery10 {
-    fn snapshot_recommendation(
-        &self,
-        version: cqrs::Version,
-        last_snapshot_version: Option<cqrs::Version>,
-    ) -> cqrs::SnapshotRecommendation {
-        if version - last_snapshot_version.unwrap_or_default() >= 10 {
-            cqrs::SnapshotRecommendation::ShouldSnapshot
-        } else {
-            cqrs::SnapshotRecommendation::DoNotSnapshot
-        }
-    }
-}
-
 type TodoStore<'conn> = cqrs_postgres::PostgresStore<
     'conn,
     cqrs_todo_core::TodoAggregate,
     cqrs_todo_core::TodoEvent,
     cqrs_todo_core::TodoMetadata,
-    SnapshotEvery10,
+    cqrs::SnapshotEvery<10>,
 >;
 
 pub fn start_todo_server(conn_str: &str, prefill_qty: usize) -> iron::Listening {
@@ -2,15 +2,20 @@ use std::{
     any::{type_name, Any, TypeId},
     collections::HashMap,
     convert::TryFrom,
+    error::Error,
     fmt,
     marker::PhantomData,
+    num::NonZeroUsize,
     sync::{atomic::AtomicPtr, Arc},
 };
 
 use async_trait::async_trait;
-use cqrs_core::Event;
+use cqrs_core::{Event, MaybeSend, MaybeSync};
 
-#[async_trait(?Send)]
+use crate::Backoff;
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 pub trait EventHandler<Ev: ?Sized> {
     type Context: ?Sized;
     type Err;
@@ -102,9 +107,77 @@ pub trait RegisteredEvent: 'static {
     fn type_id(&self) -> TypeId;
 }
 
+/// Mode of executing [`EventHandler`]s registered for a single event.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ExecutionMode {
+    /// [`EventHandler`]s are executed one after another.
+    Sequential,
+
+    /// [`EventHandler`]s are executed concurrently, but no more than the given
+    /// number at once. Their results are collected in the order of execution
+    /// start.
+    Concurrent(NonZeroUsize),
+
+    /// All [`EventHandler`]s are executed concurrently at once. Their results
+    /// are collected in the order of completion.
+    ConcurrentUnordered,
+}
+
+impl Default for ExecutionMode {
+    #[inline]
+    fn default() -> Self {
+        Self::Sequential
+    }
+}
+
+/// Error of executing [`EventHandler`]s, aggregating failures of all of them.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EventHandlersError<Err>(Vec<Err>);
+
+impl<Err> EventHandlersError<Err> {
+    /// Returns errors of all the failed [`EventHandler`]s.
+    #[inline]
+    pub fn errors(&self) -> &[Err] {
+        &self.0
+    }
+
+    /// Converts this [`EventHandlersError`] into errors of all the failed
+    /// [`EventHandler`]s.
+    #[inline]
+    pub fn into_errors(self) -> Vec<Err> {
+        self.0
+    }
+}
+
+impl<Err> From<Vec<Err>> for EventHandlersError<Err> {
+    #[inline]
+    fn from(errors: Vec<Err>) -> Self {
+        Self(errors)
+    }
+}
+
+impl<Err: fmt::Display> fmt::Display for EventHandlersError<Err> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} event handler(s) failed", self.0.len())?;
+        for (i, e) in self.0.iter().enumerate() {
+            write!(f, "{} {}", if i == 0 { ":" } else { ";" }, e)?;
+        }
+        Ok(())
+    }
+}
+
+impl<Err: Error + 'static> Error for EventHandlersError<Err> {
+    #[inline]
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        let err: &(dyn Error + 'static) = self.0.first()?;
+        Some(err)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct EventProcessingConfiguration {
     handlers: Arc<EventHandlersRegistry>,
+    execution_mode: ExecutionMode,
 }
 
 sa::assert_impl_all!(EventProcessingConfiguration: Send, Sync);
@@ -114,9 +187,16 @@ impl EventProcessingConfiguration {
     pub fn new() -> EventProcessingConfigurationBuilder {
         EventProcessingConfigurationBuilder {
             handlers: EventHandlersRegistry::default(),
+            execution_mode: ExecutionMode::default(),
         }
     }
 
+    /// Returns [`ExecutionMode`] of registered [`EventHandler`]s.
+    #[inline]
+    pub fn execution_mode(&self) -> ExecutionMode {
+        self.execution_mode
+    }
+
     #[inline]
     pub fn iter_event_handlers_of<Ev, Ctx, Err>(
         &self,
@@ -134,6 +214,7 @@ impl EventProcessingConfiguration {
 #[derive(Debug)]
 pub struct EventProcessingConfigurationBuilder {
     handlers: EventHandlersRegistry,
+    execution_mode: ExecutionMode,
 }
 
 impl EventProcessingConfigurationBuilder {
@@ -141,23 +222,207 @@ impl EventProcessingConfigurationBuilder {
     pub fn build(self) -> EventProcessingConfiguration {
         EventProcessingConfiguration {
             handlers: Arc::new(self.handlers),
+            execution_mode: self.execution_mode,
         }
     }
 
+    /// Sets [`ExecutionMode`] of registered [`EventHandler`]s.
+    ///
+    /// [`ExecutionMode::Sequential`] is used by default.
+    #[inline]
+    pub fn set_execution_mode(&mut self, mode: ExecutionMode) {
+        self.execution_mode = mode;
+    }
+
+    /// Registers the given [`EventHandler`] with [`FailurePolicy::report`].
     #[inline]
     pub fn register_event_handler<Ev, AsEv, Ctx, Err, H>(&mut self, handler: H)
     where
-        Ev: ?Sized + 'static,
+        Ev: MaybeSync + ?Sized + 'static,
+        for<'e> &'e Ev: TryFrom<&'e AsEv>,
+        AsEv: MaybeSync + ?Sized + 'static,
+        Ctx: AsRef<H::Context> + MaybeSync + ?Sized + 'static,
+        Err: From<H::Err> + MaybeSend + 'static,
+        H: EventHandler<Ev> + Send + Sync + 'static,
+    {
+        self.register_event_handler_with_policy::<Ev, AsEv, Ctx, Err, H>(
+            handler,
+            FailurePolicy::report(),
+        )
+    }
+
+    /// Registers the given [`EventHandler`] with the given [`FailurePolicy`]
+    /// applied on its failures.
+    #[inline]
+    pub fn register_event_handler_with_policy<Ev, AsEv, Ctx, Err, H>(
+        &mut self,
+        handler: H,
+        policy: FailurePolicy<AsEv, Err>,
+    ) where
+        Ev: MaybeSync + ?Sized + 'static,
         for<'e> &'e Ev: TryFrom<&'e AsEv>,
-        AsEv: ?Sized + 'static,
-        Ctx: AsRef<H::Context> + ?Sized + 'static,
-        Err: From<H::Err> + 'static,
+        AsEv: MaybeSync + ?Sized + 'static,
+        Ctx: AsRef<H::Context> + MaybeSync + ?Sized + 'static,
+        Err: From<H::Err> + MaybeSend + 'static,
         H: EventHandler<Ev> + Send + Sync + 'static,
     {
-        self.handlers.register::<Ev, AsEv, Ctx, Err, H>(handler)
+        self.handlers
+            .register::<Ev, AsEv, Ctx, Err, H>(handler, policy)
     }
 }
 
+/// Policy of handling failures of a single registered [`EventHandler`].
+///
+/// Failed [`EventHandler`] is retried first (if configured via
+/// [`FailurePolicy::with_retries`]), and only once all the attempts fail, the
+/// final failure is handled.
+pub struct FailurePolicy<Ev: ?Sized, Err> {
+    retries: Option<Retries>,
+    fallback: Fallback<Ev, Err>,
+}
+
+impl<Ev: ?Sized, Err> FailurePolicy<Ev, Err> {
+    /// Creates new [`FailurePolicy`] returning the failure from
+    /// [`Static::exec_event_handlers`] along with the failures of other
+    /// [`EventHandler`]s, without stopping their execution.
+    ///
+    /// [`Static::exec_event_handlers`]: crate::lifecycle::Static::exec_event_handlers
+    #[inline]
+    pub fn report() -> Self {
+        Self {
+            retries: None,
+            fallback: Fallback::Report,
+        }
+    }
+
+    /// Creates new [`FailurePolicy`] stopping [`Static::exec_event_handlers`]
+    /// on the failure and returning it along with the failures reported
+    /// before.
+    ///
+    /// [`EventHandler`]s of the failed event being executed concurrently are
+    /// not polled anymore, and the events not handled yet are buffered back,
+    /// so the next [`Static::exec_event_handlers`] call starts from them.
+    ///
+    /// [`Static::exec_event_handlers`]: crate::lifecycle::Static::exec_event_handlers
+    #[inline]
+    pub fn fail_fast() -> Self {
+        Self {
+            retries: None,
+            fallback: Fallback::FailFast,
+        }
+    }
+
+    /// Creates new [`FailurePolicy`] logging the failure and skipping the
+    /// event, as if it was handled successfully.
+    #[inline]
+    pub fn skip_and_log() -> Self
+    where
+        Err: fmt::Debug,
+    {
+        Self {
+            retries: None,
+            fallback: Fallback::SkipAndLog(|handler, err| {
+                log::warn!(
+                    "EventHandler({}) failed, skipping event: {:?}",
+                    handler,
+                    err
+                )
+            }),
+        }
+    }
+
+    /// Creates new [`FailurePolicy`] pushing the failed event into the given
+    /// [`DeadLetterSink`].
+    #[inline]
+    pub fn dead_letter<S>(sink: S) -> Self
+    where
+        S: DeadLetterSink<Ev, Err> + Send + Sync + 'static,
+    {
+        Self {
+            retries: None,
+            fallback: Fallback::DeadLetter(Arc::new(sink)),
+        }
+    }
+
+    /// Creates new [`FailurePolicy`] retrying the failed [`EventHandler`]
+    /// with the given [`Backoff`], and reporting the failure once all
+    /// `max_attempts` (including the first one) fail.
+    #[inline]
+    pub fn retry<B>(max_attempts: usize, backoff: B) -> Self
+    where
+        B: Backoff + Send + Sync + 'static,
+    {
+        Self::report().with_retries(max_attempts, backoff)
+    }
+
+    /// Makes this [`FailurePolicy`] to retry the failed [`EventHandler`] with
+    /// the given [`Backoff`] before handling its failure, performing at most
+    /// `max_attempts` attempts (including the first one).
+    #[inline]
+    pub fn with_retries<B>(mut self, max_attempts: usize, backoff: B) -> Self
+    where
+        B: Backoff + Send + Sync + 'static,
+    {
+        self.retries = Some(Retries {
+            max_attempts,
+            backoff: Arc::new(backoff),
+        });
+        self
+    }
+}
+
+impl<Ev: ?Sized, Err> fmt::Debug for FailurePolicy<Ev, Err> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FailurePolicy")
+            .field(
+                "max_attempts",
+                &self.retries.as_ref().map(|r| r.max_attempts),
+            )
+            .field("fallback", &self.fallback)
+            .finish()
+    }
+}
+
+// `std::env::Args` type is `!Send + !Sync`
+sa::assert_impl_all!(FailurePolicy<std::env::Args, std::env::Args>: Send, Sync);
+
+/// Retrying of a failed [`EventHandler`].
+struct Retries {
+    max_attempts: usize,
+    backoff: Arc<dyn Backoff + Send + Sync>,
+}
+
+/// Handling of an [`EventHandler`] failure, once it's not retried anymore.
+enum Fallback<Ev: ?Sized, Err> {
+    Report,
+    FailFast,
+    SkipAndLog(fn(&'static str, &Err)),
+    DeadLetter(Arc<dyn DeadLetterSink<Ev, Err> + Send + Sync>),
+}
+
+impl<Ev: ?Sized, Err> fmt::Debug for Fallback<Ev, Err> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Report => "Report",
+            Self::FailFast => "FailFast",
+            Self::SkipAndLog(_) => "SkipAndLog",
+            Self::DeadLetter(_) => "DeadLetter",
+        })
+    }
+}
+
+/// Sink of events failed to be handled by [`EventHandler`]s, used by
+/// [`FailurePolicy::dead_letter`].
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+pub trait DeadLetterSink<Ev: ?Sized, Err> {
+    /// Stores the `event` failed to be handled by the given `handler` with the
+    /// given `err`.
+    ///
+    /// Returned error is treated as a failure of the [`EventHandler`].
+    async fn push(&self, event: &Ev, handler: &'static str, err: Err) -> Result<(), Err>;
+}
+
 #[derive(Debug, Default)]
 struct EventHandlersRegistry(
     HashMap<TypeId, HashMap<(TypeId, TypeId, TypeId), HashMap<TypeId, OpaqueEventHandler>>>,
@@ -166,18 +431,24 @@ struct EventHandlersRegistry(
 sa::assert_impl_all!(EventHandlersRegistry: Send, Sync);
 
 impl EventHandlersRegistry {
-    fn register<Ev, AsEv, Ctx, Err, H>(&mut self, handler: H)
+    fn register<Ev, AsEv, Ctx, Err, H>(&mut self, handler: H, policy: FailurePolicy<AsEv, Err>)
     where
-        Ev: ?Sized + 'static,
+        Ev: MaybeSync + ?Sized + 'static,
         for<'e> &'e Ev: TryFrom<&'e AsEv>,
-        AsEv: ?Sized + 'static,
-        Ctx: AsRef<H::Context> + ?Sized + 'static,
-        Err: From<H::Err> + 'static,
+        AsEv: MaybeSync + ?Sized + 'static,
+        Ctx: AsRef<H::Context> + MaybeSync + ?Sized + 'static,
+        Err: From<H::Err> + MaybeSend + 'static,
         H: EventHandler<Ev> + Send + Sync + 'static,
     {
         let raw =
             RawEventHandler::<H, Ev, Ctx, Err>(handler, PhantomData, PhantomData, PhantomData);
-        let r#dyn = DynEventHandler::<AsEv, Ctx, Err>(Box::new(raw));
+        let fails_fast = matches!(policy.fallback, Fallback::FailFast);
+        let policed = PolicedEventHandler {
+            handler: raw,
+            name: type_name::<H>(),
+            policy,
+        };
+        let r#dyn = DynEventHandler::<AsEv, Ctx, Err>(Box::new(policed), fails_fast);
         let opaque = OpaqueEventHandler(Box::new(r#dyn));
         let _ = self
             .0
@@ -228,19 +499,35 @@ impl fmt::Debug for OpaqueEventHandler {
 
 pub struct DynEventHandler<Ev, Ctx, Err>(
     Box<dyn EventHandler<Ev, Context = Ctx, Err = Err> + Send + Sync>,
+    bool,
 )
 where
     Ev: ?Sized,
     Ctx: ?Sized;
 
+impl<Ev, Ctx, Err> DynEventHandler<Ev, Ctx, Err>
+where
+    Ev: ?Sized,
+    Ctx: ?Sized,
+{
+    /// Indicates whether this [`EventHandler`] is registered with
+    /// [`FailurePolicy::fail_fast`], so its failure should stop execution of
+    /// other [`EventHandler`]s.
+    #[inline]
+    pub fn fails_fast(&self) -> bool {
+        self.1
+    }
+}
+
 // `std::env::Args` type is `!Send + !Sync`
 sa::assert_impl_all!(DynEventHandler<u8, std::env::Args, std::env::Args>: Send, Sync);
 
-#[async_trait(?Send)]
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 impl<Ev, Ctx, Err> EventHandler<Ev> for DynEventHandler<Ev, Ctx, Err>
 where
-    Ev: ?Sized,
-    Ctx: ?Sized,
+    Ev: MaybeSync + ?Sized,
+    Ctx: MaybeSync + ?Sized,
 {
     type Context = Ctx;
     type Err = Err;
@@ -266,14 +553,15 @@ sa::assert_impl_all!(
     RawEventHandler<u8, std::env::Args, std::env::Args, std::env::Args>: Send, Sync
 );
 
-#[async_trait(?Send)]
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 impl<AsEv, H, Ev, Ctx, Err> EventHandler<AsEv> for RawEventHandler<H, Ev, Ctx, Err>
 where
-    AsEv: ?Sized,
-    H: EventHandler<Ev>,
-    Ev: ?Sized,
+    AsEv: MaybeSync + ?Sized,
+    H: EventHandler<Ev> + MaybeSync,
+    Ev: MaybeSync + ?Sized,
     for<'e> &'e Ev: TryFrom<&'e AsEv>,
-    Ctx: AsRef<H::Context> + ?Sized,
+    Ctx: AsRef<H::Context> + MaybeSync + ?Sized,
     Err: From<H::Err>,
 {
     type Context = Ctx;
@@ -281,16 +569,63 @@ where
 
     #[inline]
     async fn on(&self, event: &AsEv, ctx: &Self::Context) -> Result<(), Self::Err> {
-        if let Ok(ev) = <&Ev>::try_from(event) {
-            self.0.on(ev, ctx.as_ref()).await.map_err(Err::from)
-        } else {
-            panic!(
+        // Conversion result is not held across `.await`, so the returned
+        // `Future` doesn't require it to be `Send`.
+        let ev = match <&Ev>::try_from(event) {
+            Ok(ev) => ev,
+            Err(_) => panic!(
                 "Event({}) fails to convert into Event({}) \
                  on calling EventHandler({})",
                 type_name::<AsEv>(),
                 type_name::<Ev>(),
                 type_name::<H>()
-            )
+            ),
+        };
+        self.0.on(ev, ctx.as_ref()).await.map_err(Err::from)
+    }
+}
+
+/// [`EventHandler`] applying [`FailurePolicy`] on failures of the wrapped
+/// one.
+struct PolicedEventHandler<H, Ev: ?Sized, Err> {
+    handler: H,
+    name: &'static str,
+    policy: FailurePolicy<Ev, Err>,
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl<H, Ev, Err> EventHandler<Ev> for PolicedEventHandler<H, Ev, Err>
+where
+    H: EventHandler<Ev, Err = Err> + MaybeSync,
+    H::Context: MaybeSync,
+    Ev: MaybeSync + ?Sized,
+    Err: MaybeSend,
+{
+    type Context = H::Context;
+    type Err = Err;
+
+    async fn on(&self, event: &Ev, ctx: &Self::Context) -> Result<(), Self::Err> {
+        let mut attempt = 1;
+        let err = loop {
+            match self.handler.on(event, ctx).await {
+                Ok(()) => return Ok(()),
+                Err(e) => match &self.policy.retries {
+                    Some(r) if attempt < r.max_attempts => {
+                        r.backoff.wait(attempt).await;
+                        attempt += 1;
+                    }
+                    _ => break e,
+                },
+            }
+        };
+        match &self.policy.fallback {
+            Fallback::Report | Fallback::FailFast => Err(err),
+            Fallback::SkipAndLog(log) => {
+                log(self.name, &err);
+                Ok(())
+            }
+            Fallback::DeadLetter(sink) => sink.push(event, self.name, err).await,
         }
     }
 }
@@ -314,7 +649,7 @@ mod event_processing_configuration_spec {
     };
 
     use async_trait::async_trait;
-    use derive_more::{From, TryIntoRef};
+    use derive_more::{From, TryInto};
 
     use super::EventProcessingConfiguration;
 
@@ -326,9 +661,14 @@ mod event_processing_configuration_spec {
         }
     }
 
-    impl crate::RegisteredEvent for TestEvent {}
+    impl crate::RegisteredEvent for TestEvent {
+        fn type_id(&self) -> TypeId {
+            TypeId::of::<Self>()
+        }
+    }
 
-    #[derive(From, TryIntoRef)]
+    #[derive(From, TryInto)]
+    #[try_into(ref)]
     enum TestAggregateEvent {
         TestEvent(TestEvent),
     }
@@ -349,12 +689,13 @@ mod event_processing_configuration_spec {
 
     struct TestHandler;
 
-    #[async_trait(?Send)]
+    #[cfg_attr(feature = "send", async_trait)]
+    #[cfg_attr(not(feature = "send"), async_trait(?Send))]
     impl crate::EventHandler<TestEvent> for TestHandler {
         type Context = ();
         type Err = Infallible;
 
-        async fn on(&self, ev: &TestEvent, ctx: &Self::Context) -> Result<(), Self::Err> {
+        async fn on(&self, _: &TestEvent, _: &Self::Context) -> Result<(), Self::Err> {
             unreachable!()
         }
     }
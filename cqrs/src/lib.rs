@@ -48,6 +48,10 @@
 
 mod event_processing;
 pub mod lifecycle;
+#[cfg(feature = "memory")]
+pub mod memory;
+mod repository;
+mod retry;
 
 use async_trait::async_trait;
 
@@ -59,13 +63,53 @@ pub use cqrs_core::*;
 #[doc(inline)]
 pub use self::{
     event_processing::{
-        EventHandler, EventHandlersRegistrar, EventProcessingConfiguration,
-        EventProcessingConfigurationBuilder, RegisteredEvent,
+        DeadLetterSink, EventHandler, EventHandlersError, EventHandlersRegistrar,
+        EventProcessingConfiguration, EventProcessingConfigurationBuilder, ExecutionMode,
+        FailurePolicy, RegisteredEvent,
     },
     lifecycle::BorrowableAsContext,
+    repository::Repository,
+    retry::{Backoff, OnVersionConflict, RetryCondition, RetryPolicy, RetryingCommandBus},
 };
 
-#[async_trait(?Send)]
+#[doc(hidden)]
+pub mod private {
+    pub use async_trait::async_trait;
+    pub use cqrs_core::private::*;
+}
+
+/// Applies `#[async_trait]` to the given implementation, producing `Send`
+/// futures only if the `send` feature is enabled.
+///
+/// Used by code generated in user crates, which cannot know the enabled
+/// features of this crate.
+#[cfg(feature = "send")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __async_trait_impl {
+    ($($item:tt)*) => {
+        #[$crate::private::async_trait]
+        $($item)*
+    };
+}
+
+/// Applies `#[async_trait]` to the given implementation, producing `Send`
+/// futures only if the `send` feature is enabled.
+///
+/// Used by code generated in user crates, which cannot know the enabled
+/// features of this crate.
+#[cfg(not(feature = "send"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __async_trait_impl {
+    ($($item:tt)*) => {
+        #[$crate::private::async_trait(?Send)]
+        $($item)*
+    };
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 pub trait CommandGateway<Cmd, Mt> {
     type Err;
     type Ok;
@@ -73,7 +117,8 @@ pub trait CommandGateway<Cmd, Mt> {
     async fn send(&self, cmd: Cmd, meta: Mt) -> Result<Self::Ok, Self::Err>;
 }
 
-#[async_trait(?Send)]
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 pub trait CommandBus<Cmd: Command> {
     type Err;
     type Ok;
@@ -91,7 +136,8 @@ pub trait AggregateEvent {
 
 pub trait Query {}
 
-#[async_trait(?Send)]
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 pub trait QueryGateway<Qr: Query> {
     type Err;
     type Ok;
@@ -101,7 +147,8 @@ pub trait QueryGateway<Qr: Query> {
         Qr: 'async_trait;
 }
 
-#[async_trait(?Send)]
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 pub trait QueryHandler<Qr: Query> {
     type Context: ?Sized;
     type Err;
@@ -1,26 +1,64 @@
+use std::{collections::HashMap, fmt, hash::Hash, num::NonZeroUsize, slice, time::SystemTime};
+
 use cqrs_core::{
-    Aggregate, Command, CommandHandler, Event, EventNumber, EventSink, EventSource, EventSourced,
-    HydratedAggregate, NumberedEvent, SnapshotRecommendation, SnapshotSink, SnapshotSource,
-    SnapshotStrategy,
+    Aggregate, AsVersionConflict, BoxTryStream, Command, CommandHandler, Event, EventNumber,
+    EventSink, EventSource, EventSourceWithMeta, EventSourced, HydratedAggregate, MaybeSend,
+    MaybeSync, MultiAggregateCommand, MultiAggregateCommandHandler, NumberedEvent, PerEventMeta,
+    ReadRange, Since, SnapshotRecommendation, SnapshotRetention, SnapshotSink, SnapshotSource,
+    SnapshotStrategy, TimestampedMeta, Version, VersionConflict,
 };
 use derive_more::{Display, Error, From};
 use futures::{future, TryStreamExt as _};
 use smallvec::SmallVec;
 
 use super::{
-    BufferedContext, CommandHandlerContext, CommandHandlerErr, CommandHandlerEvent,
-    CommandHandlerOk,
+    Atomicity, BestEffort, BufferedContext, CommandHandlerContext, CommandHandlerErr,
+    CommandHandlerEvent, CommandHandlerOk, MultiCommandHandlerContext, MultiCommandHandlerErr,
+    MultiCommandHandlerEvent, MultiCommandHandlerOk, Transaction, UnitOfWork, UnitOfWorkError,
 };
 
-#[derive(Debug)]
+/// Default maximum number of [`Aggregate`]s' events streams being read
+/// concurrently by [`Basic`] lifecycle.
+const DEFAULT_READ_CONCURRENCY: usize = 16;
+
+#[derive(Clone, Debug)]
 pub struct Basic<Snp> {
     snapshot_strategy: Snp,
+    snapshot_retention: SnapshotRetention,
+    read_concurrency: NonZeroUsize,
 }
 
 impl<Snp> Basic<Snp> {
     #[inline]
     pub fn new(snapshot_strategy: Snp) -> Self {
-        Self { snapshot_strategy }
+        Self {
+            snapshot_strategy,
+            snapshot_retention: SnapshotRetention::default(),
+            read_concurrency: NonZeroUsize::new(DEFAULT_READ_CONCURRENCY).unwrap(),
+        }
+    }
+
+    /// Sets [`SnapshotRetention`] policy, which older snapshots of an
+    /// [`Aggregate`] are pruned with once its new snapshot is persisted.
+    ///
+    /// Pruning is best-effort: its failure is logged and doesn't fail the
+    /// persisting, as the new snapshot is persisted already by that moment.
+    ///
+    /// See [`SnapshotSink::prune_snapshots`] for details.
+    #[inline]
+    pub fn with_snapshot_retention(mut self, retention: SnapshotRetention) -> Self {
+        self.snapshot_retention = retention;
+        self
+    }
+
+    /// Sets maximum number of [`Aggregate`]s' events streams being read
+    /// concurrently, if the [`EventSource`] doesn't support batch reading.
+    ///
+    /// See [`EventSource::read_events_batch`] for details.
+    #[inline]
+    pub fn with_read_concurrency(mut self, limit: NonZeroUsize) -> Self {
+        self.read_concurrency = limit;
+        self
     }
 }
 
@@ -32,6 +70,11 @@ impl<Snp> AsRef<Basic<Snp>> for Basic<Snp> {
 }
 
 impl<Snp> Basic<Snp> {
+    /// Loads the [`Aggregate`] from its latest snapshot.
+    ///
+    /// A snapshot stored in a format other than the current
+    /// [`Aggregate::SNAPSHOT_VERSION`] is discarded, so the [`Aggregate`]
+    /// should be rebuilt from its events instead.
     pub async fn load_aggregate_from_snapshot<SsSrc, Agg>(
         &self,
         id: &Agg::Id,
@@ -44,9 +87,15 @@ impl<Snp> Basic<Snp> {
         Ok(snapshot_source
             .load_snapshot(id)
             .await?
-            .map(|(agg, ver)| HydratedAggregate::from_snapshot(agg, ver)))
+            .filter(|(_, _, snapshot_ver, _)| *snapshot_ver == Agg::SNAPSHOT_VERSION)
+            .map(|(agg, ver, _, at)| HydratedAggregate::from_snapshot_taken_at(agg, ver, at)))
     }
 
+    /// Loads the [`Aggregate`]s from their latest snapshots.
+    ///
+    /// Snapshots stored in a format other than the current
+    /// [`Aggregate::SNAPSHOT_VERSION`] are discarded, so the [`Aggregate`]s
+    /// should be rebuilt from their events instead.
     pub async fn load_aggregates_from_snapshot<SsSrc, Agg>(
         &self,
         ids: &[Agg::Id],
@@ -63,10 +112,31 @@ impl<Snp> Basic<Snp> {
             .load_snapshots(ids)
             .await?
             .into_iter()
-            .map(|(agg, ver)| HydratedAggregate::from_snapshot(agg, ver))
+            .filter(|(_, _, snapshot_ver, _)| *snapshot_ver == Agg::SNAPSHOT_VERSION)
+            .map(|(agg, ver, _, at)| HydratedAggregate::from_snapshot_taken_at(agg, ver, at))
             .collect())
     }
 
+    /// Reads events of the [`Aggregate`] within the given [`ReadRange`],
+    /// without loading the [`Aggregate`] itself.
+    ///
+    /// See [`EventSource::read_events_range`] for details.
+    #[inline]
+    pub fn read_events<'a, EvSrc, Ev, Agg>(
+        &self,
+        id: &Agg::Id,
+        range: ReadRange,
+        event_source: &'a EvSrc,
+    ) -> BoxTryStream<'a, NumberedEvent<Ev>, EvSrc::Err>
+    where
+        Agg: Aggregate + EventSourced<Ev>,
+        Ev: MaybeSend + 'a,
+        EvSrc: EventSource<Agg, Ev> + ?Sized,
+        EvSrc::Err: 'a,
+    {
+        event_source.read_events_range(id, range)
+    }
+
     pub async fn rehydrate_aggregate<EvSrc, Ev, Agg>(
         &self,
         agg: &mut HydratedAggregate<Agg>,
@@ -78,7 +148,10 @@ impl<Snp> Basic<Snp> {
     {
         event_source
             .read_events(agg.id(), agg.version().into())
-            .try_for_each(|ev| future::ok(agg.apply(&ev)))
+            .try_for_each(|ev| {
+                agg.apply(&ev);
+                future::ok(())
+            })
             .await
     }
 
@@ -93,18 +166,179 @@ impl<Snp> Basic<Snp> {
         EvSrc: EventSource<Agg, Ev> + ?Sized,
         Repo: AsRef<SsSrc> + AsRef<EvSrc> + ?Sized,
     {
-        let agg = self
+        let snapshot = self
             .load_aggregate_from_snapshot::<SsSrc, _>(id, repo.as_ref())
             .await
             .map_err(LoadError::Snapshot)?;
-        if agg.is_none() {
+        let is_snapshotted = snapshot.is_some();
+
+        let mut agg = snapshot.unwrap_or_default();
+        let event_source: &EvSrc = repo.as_ref();
+        event_source
+            .read_events(id, agg.version().into())
+            .try_for_each(|ev| {
+                agg.apply(&ev);
+                future::ok(())
+            })
+            .await
+            .map_err(LoadError::Events)?;
+
+        if !is_snapshotted && agg.version() == Version::Initial {
             return Ok(None);
         }
+        Ok(Some(agg))
+    }
+
+    /// Rebuilds the [`Aggregate`] as it was at the given [`Version`].
+    ///
+    /// Starts from the newest snapshot not exceeding the given [`Version`], if
+    /// any, and applies the events up to the given [`Version`] only. The
+    /// rebuilt [`Aggregate`] is never persisted as a snapshot.
+    ///
+    /// Returns [`None`] if the [`Aggregate`] didn't exist at the given
+    /// [`Version`].
+    pub async fn load_aggregate_at_version<SsSrc, EvSrc, Ev, Agg, Repo>(
+        &self,
+        id: &Agg::Id,
+        ver: Version,
+        repo: &Repo,
+    ) -> Result<Option<HydratedAggregate<Agg>>, LoadError<SsSrc::Err, EvSrc::Err>>
+    where
+        Agg: Aggregate + EventSourced<Ev>,
+        SsSrc: SnapshotSource<Agg> + ?Sized,
+        EvSrc: EventSource<Agg, Ev> + ?Sized,
+        Repo: AsRef<SsSrc> + AsRef<EvSrc> + ?Sized,
+    {
+        let until = match ver.event_number() {
+            Some(num) => num,
+            None => return Ok(None),
+        };
+
+        let snapshot_source: &SsSrc = repo.as_ref();
+        let snapshot = snapshot_source
+            .load_snapshot_at(id, ver)
+            .await
+            .map_err(LoadError::Snapshot)?
+            .filter(|(_, _, snapshot_ver, _)| *snapshot_ver == Agg::SNAPSHOT_VERSION)
+            .map(|(agg, ver, _, at)| HydratedAggregate::from_snapshot_taken_at(agg, ver, at));
+        let is_snapshotted = snapshot.is_some();
+
+        let mut agg = snapshot.unwrap_or_default();
+        let event_source: &EvSrc = repo.as_ref();
+        event_source
+            .read_events(id, agg.version().into())
+            .try_take_while(|ev| future::ok(ev.num <= until))
+            .try_for_each(|ev| {
+                agg.apply(&ev);
+                future::ok(())
+            })
+            .await
+            .map_err(LoadError::Events)?;
+
+        if !is_snapshotted && agg.version() == Version::Initial {
+            return Ok(None);
+        }
+        Ok(Some(agg))
+    }
+
+    /// Rebuilds the [`Aggregate`] as it was at the given moment, considering
+    /// the events occurred no later than that moment according to their
+    /// [`TimestampedMeta`].
+    ///
+    /// Timestamps of the [`Aggregate`]'s events are expected to not decrease,
+    /// as the events are read only until the first one occurred after the
+    /// given moment.
+    ///
+    /// Starts from the newest snapshot taken no later than the given moment,
+    /// as the events it covers are expected to have occurred before it was
+    /// taken. If there is no such snapshot, starts from the latest one, if
+    /// the event it was taken at occurred no later than the given moment,
+    /// reading the events since that one in a single pass. Otherwise, the
+    /// [`Aggregate`] is rebuilt from the beginning of its events stream. The
+    /// rebuilt [`Aggregate`] is never persisted as a snapshot.
+    ///
+    /// Returns [`None`] if the [`Aggregate`] didn't exist at the given moment.
+    pub async fn load_aggregate_at_time<SsSrc, EvSrc, Ev, Mt, Agg, Repo>(
+        &self,
+        id: &Agg::Id,
+        at: SystemTime,
+        repo: &Repo,
+    ) -> Result<Option<HydratedAggregate<Agg>>, LoadError<SsSrc::Err, EvSrc::Err>>
+    where
+        Agg: Aggregate + EventSourced<Ev>,
+        Mt: TimestampedMeta,
+        SsSrc: SnapshotSource<Agg> + ?Sized,
+        EvSrc: EventSourceWithMeta<Agg, Ev, Mt> + ?Sized,
+        Repo: AsRef<SsSrc> + AsRef<EvSrc> + ?Sized,
+    {
+        let snapshot_source: &SsSrc = repo.as_ref();
+        let event_source: &EvSrc = repo.as_ref();
+
+        let taken_until = snapshot_source
+            .load_snapshot_taken_until(id, at)
+            .await
+            .map_err(LoadError::Snapshot)?
+            .filter(|(_, _, snapshot_ver, _)| *snapshot_ver == Agg::SNAPSHOT_VERSION)
+            .map(|(agg, ver, _, taken_at)| {
+                HydratedAggregate::from_snapshot_taken_at(agg, ver, taken_at)
+            });
+        if let Some(mut agg) = taken_until {
+            event_source
+                .read_events_with_meta(id, agg.version().into())
+                .try_take_while(|ev| future::ok(ev.meta.timestamp() <= at))
+                .try_for_each(|ev| {
+                    agg.apply(&ev);
+                    future::ok(())
+                })
+                .await
+                .map_err(LoadError::Events)?;
+            return Ok(Some(agg));
+        }
 
-        let mut agg = agg.unwrap();
-        self.rehydrate_aggregate::<EvSrc, Ev, _>(&mut agg, repo.as_ref())
+        let snapshot = self
+            .load_aggregate_from_snapshot::<SsSrc, _>(id, snapshot_source)
+            .await
+            .map_err(LoadError::Snapshot)?;
+        if let Some(mut agg) = snapshot {
+            if let Some(snapshot_num) = agg.version().event_number() {
+                // The snapshotted event itself is read too, to check whether it
+                // occurred no later than the given moment.
+                let since = EventNumber::new(u128::from(snapshot_num) - 1)
+                    .map_or(Since::BeginningOfStream, Since::Event);
+                let mut is_reached = false;
+                event_source
+                    .read_events_with_meta(id, since)
+                    .try_take_while(|ev| future::ok(ev.meta.timestamp() <= at))
+                    .try_for_each(|ev| {
+                        if ev.num > snapshot_num {
+                            agg.apply(&ev);
+                        } else {
+                            is_reached = true;
+                        }
+                        future::ok(())
+                    })
+                    .await
+                    .map_err(LoadError::Events)?;
+                if is_reached {
+                    return Ok(Some(agg));
+                }
+            }
+        }
+
+        let mut agg = HydratedAggregate::default();
+        event_source
+            .read_events_with_meta(id, Since::BeginningOfStream)
+            .try_take_while(|ev| future::ok(ev.meta.timestamp() <= at))
+            .try_for_each(|ev| {
+                agg.apply(&ev);
+                future::ok(())
+            })
             .await
             .map_err(LoadError::Events)?;
+
+        if agg.version() == Version::Initial {
+            return Ok(None);
+        }
         Ok(Some(agg))
     }
 
@@ -115,32 +349,62 @@ impl<Snp> Basic<Snp> {
     ) -> Result<Vec<HydratedAggregate<Agg>>, LoadError<SsSrc::Err, EvSrc::Err>>
     where
         Agg: Aggregate + EventSourced<Ev>,
+        Agg::Id: Eq + Hash,
         SsSrc: SnapshotSource<Agg> + ?Sized,
         EvSrc: EventSource<Agg, Ev> + ?Sized,
         Repo: AsRef<SsSrc> + AsRef<EvSrc> + ?Sized,
     {
-        let mut aggs = self
+        let snapshots = self
             .load_aggregates_from_snapshot::<SsSrc, _>(ids, repo.as_ref())
             .await
             .map_err(LoadError::Snapshot)?;
-        if aggs.is_empty() {
-            return Ok(vec![]);
-        }
 
-        // TODO: sequential events loading is inefficient
-        for agg in aggs.iter_mut() {
-            self.rehydrate_aggregate::<EvSrc, Ev, _>(agg, repo.as_ref())
-                .await
-                .map_err(LoadError::Events)?;
+        let positions = ids
+            .iter()
+            .enumerate()
+            .map(|(i, id)| (id, i))
+            .collect::<HashMap<_, _>>();
+        let mut aggs = ids
+            .iter()
+            .map(|_| HydratedAggregate::default())
+            .collect::<Vec<_>>();
+        let mut is_snapshotted = vec![false; ids.len()];
+        for agg in snapshots {
+            if let Some(&i) = positions.get(agg.id()) {
+                aggs[i] = agg;
+                is_snapshotted[i] = true;
+            }
         }
+
+        let reqs = ids
+            .iter()
+            .zip(&aggs)
+            .map(|(id, agg)| (id, agg.version().into()))
+            .collect::<Vec<_>>();
+        let event_source: &EvSrc = repo.as_ref();
+        event_source
+            .read_events_batch(&reqs, self.read_concurrency)
+            .try_for_each(|(i, ev)| {
+                aggs[i].apply(&ev);
+                future::ok(())
+            })
+            .await
+            .map_err(LoadError::Events)?;
+
+        let mut is_snapshotted = is_snapshotted.into_iter();
+        aggs.retain(|agg| is_snapshotted.next().unwrap() || agg.version() != Version::Initial);
         Ok(aggs)
     }
 }
 
+/// Error of loading an [`Aggregate`].
 #[derive(Clone, Copy, Debug, Display, Eq, Error, PartialEq)]
 pub enum LoadError<SsSrcErr, EvSrcErr> {
+    /// Loading the [`Aggregate`]'s snapshot failed.
     #[display(fmt = "Loading aggregate snapshot failed: {}", _0)]
     Snapshot(SsSrcErr),
+
+    /// Reading the [`Aggregate`]'s events failed.
     #[display(fmt = "Loading events failed: {}", _0)]
     Events(EvSrcErr),
 }
@@ -157,18 +421,23 @@ where
     where
         Agg: Aggregate,
         SsSnk: SnapshotSink<Agg> + ?Sized,
+        SsSnk::Err: fmt::Debug,
         Repo: AsRef<SsSnk> + ?Sized,
     {
-        let rcmnd = self
-            .snapshot_strategy
-            .recommendation(agg.version(), agg.snapshot_version());
+        let rcmnd = self.snapshot_strategy.recommendation_at(
+            agg.version(),
+            agg.snapshot_version(),
+            agg.snapshot_taken_at(),
+        );
         if let SnapshotRecommendation::ShouldSnapshot = rcmnd {
             let shapshot_sink: &SsSnk = repo.as_ref();
             shapshot_sink
                 .persist_snapshot(agg.state(), agg.version())
                 .await?;
 
-            agg.set_snapshot_version(agg.version())
+            agg.set_snapshot_version(agg.version());
+
+            self.prune_snapshots(&[agg.id()], shapshot_sink).await;
         }
         Ok(())
     }
@@ -181,18 +450,21 @@ where
     where
         Agg: Aggregate,
         SsSnk: SnapshotSink<Agg> + ?Sized,
+        SsSnk::Err: fmt::Debug,
         Repo: AsRef<SsSnk> + ?Sized,
     {
         if aggs.is_empty() {
             return Ok(());
         }
 
-        let should_snapshot_aggs = aggs
+        let mut should_snapshot_aggs = aggs
             .iter_mut()
             .filter_map(|agg| {
-                let rcmnd = self
-                    .snapshot_strategy
-                    .recommendation(agg.version(), agg.snapshot_version());
+                let rcmnd = self.snapshot_strategy.recommendation_at(
+                    agg.version(),
+                    agg.snapshot_version(),
+                    agg.snapshot_taken_at(),
+                );
                 if let SnapshotRecommendation::ShouldSnapshot = rcmnd {
                     Some(agg)
                 } else {
@@ -204,25 +476,50 @@ where
             return Ok(());
         }
 
+        let shapshot_sink: &SsSnk = repo.as_ref();
         {
             let for_persisting = should_snapshot_aggs
                 .iter()
                 .map(|agg| (agg.state(), agg.version()))
                 .collect::<SmallVec<[_; 10]>>();
 
-            let shapshot_sink: &SsSnk = repo.as_ref();
             shapshot_sink
                 .persist_snapshots(for_persisting.as_slice())
                 .await?;
         }
 
-        for agg in should_snapshot_aggs.into_iter() {
+        for agg in should_snapshot_aggs.iter_mut() {
             agg.set_snapshot_version(agg.version())
         }
 
+        let ids = should_snapshot_aggs
+            .iter()
+            .map(|agg| agg.id())
+            .collect::<SmallVec<[_; 10]>>();
+        self.prune_snapshots(ids.as_slice(), shapshot_sink).await;
+
         Ok(())
     }
 
+    /// Prunes older snapshots of the given [`Aggregate`]s according to the
+    /// [`SnapshotRetention`] policy, only logging the failure, if any.
+    async fn prune_snapshots<SsSnk, Agg>(&self, ids: &[&Agg::Id], snapshot_sink: &SsSnk)
+    where
+        Agg: Aggregate,
+        SsSnk: SnapshotSink<Agg> + ?Sized,
+        SsSnk::Err: fmt::Debug,
+    {
+        if self.snapshot_retention == SnapshotRetention::KeepAll {
+            return;
+        }
+        if let Err(e) = snapshot_sink
+            .prune_snapshots(ids, self.snapshot_retention)
+            .await
+        {
+            log::warn!("Pruning snapshots failed, keeping them: {:?}", e);
+        }
+    }
+
     pub async fn load_aggregate_rehydrate_and_persist<SsSrc, EvSrc, SsSnk, Ev, Agg, Repo>(
         &self,
         id: &Agg::Id,
@@ -233,6 +530,7 @@ where
         SsSrc: SnapshotSource<Agg> + ?Sized,
         EvSrc: EventSource<Agg, Ev> + ?Sized,
         SsSnk: SnapshotSink<Agg> + ?Sized,
+        SsSnk::Err: fmt::Debug,
         Repo: AsRef<SsSrc> + AsRef<EvSrc> + AsRef<SsSnk> + ?Sized,
     {
         let mut agg = self
@@ -256,9 +554,11 @@ where
     ) -> Result<(), LoadRehydrateAndPersistError<SsSrc::Err, EvSrc::Err, SsSnk::Err>>
     where
         Agg: Aggregate + EventSourced<Ev>,
+        Agg::Id: Eq + Hash,
         SsSrc: SnapshotSource<Agg> + ?Sized,
         EvSrc: EventSource<Agg, Ev> + ?Sized,
         SsSnk: SnapshotSink<Agg> + ?Sized,
+        SsSnk::Err: fmt::Debug,
         Repo: AsRef<SsSrc> + AsRef<EvSrc> + AsRef<SsSnk> + ?Sized,
     {
         let mut aggs = self
@@ -274,6 +574,14 @@ where
             .map_err(LoadRehydrateAndPersistError::Persist)
     }
 
+    /// Applies the given events to the [`Aggregate`] and persists them along
+    /// with its snapshot within a [`BestEffort`] [`UnitOfWork`] over the given
+    /// `repo`.
+    ///
+    /// So, events remain persisted (and buffered into the given context) even
+    /// if persisting the snapshot fails afterwards. Use
+    /// [`Basic::apply_events_and_persist_in_unit_of_work`] for persisting them
+    /// atomically.
     pub async fn apply_events_and_persist<EvSnk, SsSnk, Ev, Agg, Evs, Mt, Repo, Ctx>(
         &self,
         agg: &mut HydratedAggregate<Agg>,
@@ -284,30 +592,171 @@ where
     ) -> Result<(), PersistError<EvSnk::Err, SsSnk::Err>>
     where
         Agg: Aggregate + EventSourced<Ev>,
-        Ev: 'static,
+        Ev: MaybeSend + 'static,
         Evs: AsRef<[NumberedEvent<Ev>]>,
         Mt: ?Sized,
         EvSnk: EventSink<Agg, Ev, Mt> + ?Sized,
+        EvSnk::Err: AsVersionConflict,
         SsSnk: SnapshotSink<Agg> + ?Sized,
-        Repo: AsRef<EvSnk> + AsRef<SsSnk> + ?Sized,
+        SsSnk::Err: fmt::Debug,
+        Repo: AsRef<EvSnk> + AsRef<SsSnk> + MaybeSync + ?Sized,
         Ctx: BufferedContext + ?Sized,
+        Ctx::Meta: PerEventMeta<Agg::Id>,
     {
-        let event_sink: &EvSnk = repo.as_ref();
-        let events = event_sink
-            .append_events(agg.id(), events.as_ref(), meta)
-            .await
-            .map_err(PersistError::Events)?;
+        self.apply_events_and_persist_in_unit_of_work::<_, EvSnk, SsSnk, Ev, _, _, _, _>(
+            agg,
+            events,
+            meta,
+            &BestEffort(repo),
+            ctx,
+        )
+        .await
+        .map_err(PersistError::from)
+    }
+
+    /// Applies the given events to the [`Aggregate`] and persists them along
+    /// with its snapshot within a single [`Transaction`] of the given
+    /// [`UnitOfWork`].
+    ///
+    /// Persisted events are buffered into the given context only once the
+    /// [`Transaction`] is committed. On failure, the given [`Aggregate`] should
+    /// be discarded, as it may be out of sync with the storages.
+    pub async fn apply_events_and_persist_in_unit_of_work<
+        UoW,
+        EvSnk,
+        SsSnk,
+        Ev,
+        Agg,
+        Evs,
+        Mt,
+        Ctx,
+    >(
+        &self,
+        agg: &mut HydratedAggregate<Agg>,
+        events: Evs,
+        meta: &Mt,
+        uow: &UoW,
+        ctx: Option<&Ctx>,
+    ) -> Result<(), UnitOfWorkError<UoW::Err, EvSnk::Err, SsSnk::Err>>
+    where
+        Agg: Aggregate + EventSourced<Ev>,
+        Ev: MaybeSend + 'static,
+        Evs: AsRef<[NumberedEvent<Ev>]>,
+        Mt: ?Sized,
+        EvSnk: EventSink<Agg, Ev, Mt> + ?Sized,
+        EvSnk::Err: AsVersionConflict,
+        SsSnk: SnapshotSink<Agg> + ?Sized,
+        SsSnk::Err: fmt::Debug,
+        UoW: UnitOfWork + ?Sized,
+        UoW::Transaction: AsRef<EvSnk> + AsRef<SsSnk>,
+        Ctx: BufferedContext + ?Sized,
+        Ctx::Meta: PerEventMeta<Agg::Id>,
+    {
+        self.apply_streams_and_persist_in_unit_of_work::<UoW, EvSnk, SsSnk, Ev, _, _, _, _>(
+            slice::from_mut(agg),
+            vec![(0, None, events)],
+            meta,
+            uow,
+            ctx,
+        )
+        .await
+    }
 
-        for ev in events {
-            agg.apply(&ev);
-            if let Some(c) = ctx {
-                c.buffer_event(ev)
+    /// Applies the given events streams to the [`Aggregate`]s with the
+    /// specified indices and persists them along with the [`Aggregate`]s'
+    /// snapshots within a single [`Transaction`] of the given [`UnitOfWork`].
+    ///
+    /// Every events stream is appended with the [`Version`] check of its own
+    /// [`Aggregate`]. Events streams initiating a new [`Aggregate`] should be
+    /// accompanied by its state having the first event applied, which is used
+    /// only to provide the ID to append the events with.
+    ///
+    /// Persisted events are buffered into the given context once they're
+    /// known to remain persisted, i.e. once the [`Transaction`] is committed,
+    /// or if it fails within an [`Atomicity::BestEffort`] [`UnitOfWork`]
+    /// without rolling back anything.
+    async fn apply_streams_and_persist_in_unit_of_work<UoW, EvSnk, SsSnk, Ev, Agg, Evs, Mt, Ctx>(
+        &self,
+        aggs: &mut [HydratedAggregate<Agg>],
+        streams: Vec<(usize, Option<Agg>, Evs)>,
+        meta: &Mt,
+        uow: &UoW,
+        ctx: Option<&Ctx>,
+    ) -> Result<(), UnitOfWorkError<UoW::Err, EvSnk::Err, SsSnk::Err>>
+    where
+        Agg: Aggregate + EventSourced<Ev>,
+        Ev: MaybeSend + 'static,
+        Evs: AsRef<[NumberedEvent<Ev>]>,
+        Mt: ?Sized,
+        EvSnk: EventSink<Agg, Ev, Mt> + ?Sized,
+        EvSnk::Err: AsVersionConflict,
+        SsSnk: SnapshotSink<Agg> + ?Sized,
+        SsSnk::Err: fmt::Debug,
+        UoW: UnitOfWork + ?Sized,
+        UoW::Transaction: AsRef<EvSnk> + AsRef<SsSnk>,
+        Ctx: BufferedContext + ?Sized,
+        Ctx::Meta: PerEventMeta<Agg::Id>,
+    {
+        let tx = uow.begin().await.map_err(UnitOfWorkError::Begin)?;
+
+        let mut persisted = vec![];
+        let res = async {
+            let event_sink: &EvSnk = tx.as_ref();
+            for (n, (i, initiated, events)) in streams.iter().enumerate() {
+                let agg = &mut aggs[*i];
+                let id = initiated.as_ref().map_or_else(|| agg.id(), Aggregate::id);
+                persisted.extend(
+                    event_sink
+                        .append_events(id, agg.version(), events.as_ref(), meta)
+                        .await
+                        .map_err(|e| match e.as_version_conflict() {
+                            Some(c) => PersistError::VersionConflict(*c),
+                            None => PersistError::Events(e),
+                        })?
+                        .into_iter()
+                        .inspect(|ev| agg.apply(ev))
+                        .map(|ev| (n, ev)),
+                );
+            }
+
+            let mut touched = streams.iter().map(|(i, ..)| *i).collect::<Vec<_>>();
+            touched.sort_unstable();
+            touched.dedup();
+            for i in touched {
+                self.persist_aggregate::<SsSnk, _, _>(&mut aggs[i], &tx)
+                    .await
+                    .map_err(PersistError::Snapshot)?;
             }
+            Ok(())
         }
+        .await;
 
-        self.persist_aggregate::<SsSnk, _, _>(agg, repo)
-            .await
-            .map_err(PersistError::Snapshot)
+        let res = match res {
+            Ok(()) => tx.commit().await.map_err(UnitOfWorkError::Commit),
+            Err(cause) => Err(match tx.rollback().await {
+                Err(err) => UnitOfWorkError::Rollback { cause, err },
+                Ok(()) => match uow.atomicity() {
+                    Atomicity::Atomic => UnitOfWorkError::RolledBack(cause),
+                    Atomicity::BestEffort => UnitOfWorkError::PartiallyPersisted(cause),
+                },
+            }),
+        };
+
+        let is_persisted = match &res {
+            Ok(()) | Err(UnitOfWorkError::PartiallyPersisted(_)) => true,
+            Err(UnitOfWorkError::Commit(_)) => uow.atomicity() == Atomicity::BestEffort,
+            Err(_) => false,
+        };
+        if let (true, Some(c)) = (is_persisted, ctx) {
+            for (n, ev) in persisted {
+                let (i, initiated, _) = &streams[n];
+                let id = initiated
+                    .as_ref()
+                    .map_or_else(|| aggs[*i].id(), Aggregate::id);
+                c.buffer_event(id, ev)
+            }
+        }
+        res
     }
 
     pub async fn exec_command_and_persist<EvSnk, SsSnk, Cmd, Mt, Repo, Ctx>(
@@ -329,10 +778,24 @@ where
         CommandHandlerOk<Cmd>: AsRef<[NumberedEvent<CommandHandlerEvent<Cmd>>]> + 'static,
         Mt: ?Sized,
         EvSnk: EventSink<Cmd::Aggregate, CommandHandlerEvent<Cmd>, Mt> + ?Sized,
+        EvSnk::Err: AsVersionConflict,
         SsSnk: SnapshotSink<Cmd::Aggregate> + ?Sized,
-        Repo: AsRef<EvSnk> + AsRef<SsSnk> + ?Sized,
+        SsSnk::Err: fmt::Debug,
+        Repo: AsRef<EvSnk> + AsRef<SsSnk> + MaybeSync + ?Sized,
         Ctx: BufferedContext + ?Sized,
+        Ctx::Meta: PerEventMeta<<Cmd::Aggregate as Aggregate>::Id>,
     {
+        if cmd.aggregate_id().is_some() {
+            if let Some(expected) = cmd.expected_version() {
+                let actual = agg
+                    .as_ref()
+                    .map_or(Version::Initial, HydratedAggregate::version);
+                if expected != actual {
+                    return Err(VersionConflict { expected, actual }.into());
+                }
+            }
+        }
+
         let is_new = agg.is_none();
         let mut agg = agg.unwrap_or_default();
         let res = agg.state().handle(cmd, handler_ctx).await;
@@ -340,18 +803,25 @@ where
             Ok(ev) => {
                 let events = ev.as_ref();
                 if !events.is_empty() {
-                    if is_new {
-                        // TODO: reconsider
-                        // For newly initiated `Aggregate` this is required,
-                        // because it has no unique ID to persist it's `Event`s
-                        // with. So, we should apply at least one `Event` to
-                        // make it unique before storing its `Event`s.
-                        agg.apply(events.first().unwrap());
-                    }
-                    self.apply_events_and_persist::<EvSnk, SsSnk, _, _, _, _, _, _>(
-                        &mut agg, events, meta, repo, ctx,
+                    // TODO: reconsider
+                    // Newly initiated `Aggregate` has no unique ID to persist
+                    // its `Event`s with. So, the first `Event` is applied to
+                    // a separate state to make it unique, while the `Event`s
+                    // are applied to the `Aggregate` itself once persisted.
+                    let initiated = is_new.then(|| {
+                        let mut state = Cmd::Aggregate::default();
+                        state.apply(&events[0].data);
+                        state
+                    });
+                    self.apply_streams_and_persist_in_unit_of_work::<_, EvSnk, SsSnk, _, _, _, _, _>(
+                        slice::from_mut(&mut agg),
+                        vec![(0, initiated, events)],
+                        meta,
+                        &BestEffort(repo),
+                        ctx,
                     )
-                    .await?
+                    .await
+                    .map_err(PersistError::from)?
                 }
                 Ok(agg)
             }
@@ -395,9 +865,12 @@ where
         SsSrc: SnapshotSource<Cmd::Aggregate> + ?Sized,
         EvSrc: EventSource<Cmd::Aggregate, CommandHandlerEvent<Cmd>> + ?Sized,
         EvSnk: EventSink<Cmd::Aggregate, CommandHandlerEvent<Cmd>, Mt> + ?Sized,
+        EvSnk::Err: AsVersionConflict,
         SsSnk: SnapshotSink<Cmd::Aggregate> + ?Sized,
-        Repo: AsRef<SsSrc> + AsRef<EvSrc> + AsRef<EvSnk> + AsRef<SsSnk> + ?Sized,
+        SsSnk::Err: fmt::Debug,
+        Repo: AsRef<SsSrc> + AsRef<EvSrc> + AsRef<EvSnk> + AsRef<SsSnk> + MaybeSync + ?Sized,
         Ctx: BufferedContext + ?Sized,
+        Ctx::Meta: PerEventMeta<<Cmd::Aggregate as Aggregate>::Id>,
     {
         let agg = if let Some(id) = cmd.aggregate_id() {
             let agg = self
@@ -423,38 +896,215 @@ where
             .await?;
         Ok(Some(agg))
     }
+
+    /// Loads all the [`Aggregate`]s addressed by the given
+    /// [`MultiAggregateCommand`], executes it, and persists the produced
+    /// events of all the [`Aggregate`]s within a single [`Transaction`] of the
+    /// given [`UnitOfWork`].
+    ///
+    /// Events of every [`Aggregate`] are appended with the [`Version`] check
+    /// of its own stream, so a concurrent modification of any of them rolls
+    /// back the whole [`Transaction`]. Events produced for an [`Aggregate`]
+    /// which doesn't exist yet initialize a new one.
+    ///
+    /// Returns all the loaded and initialized [`Aggregate`]s.
+    pub async fn load_aggregates_exec_command_and_persist<
+        SsSrc,
+        EvSrc,
+        UoW,
+        EvSnk,
+        SsSnk,
+        Cmd,
+        Evs,
+        Mt,
+        Repo,
+        Ctx,
+    >(
+        &self,
+        cmd: Cmd,
+        meta: &Mt,
+        handler_ctx: &MultiCommandHandlerContext<Cmd>,
+        repo: &Repo,
+        uow: &UoW,
+        ctx: Option<&Ctx>,
+    ) -> Result<
+        Vec<HydratedAggregate<Cmd::Aggregate>>,
+        LoadAggregatesExecAndPersistError<
+            <Cmd::Aggregate as Aggregate>::Id,
+            MultiCommandHandlerErr<Cmd>,
+            SsSrc::Err,
+            EvSrc::Err,
+            UoW::Err,
+            EvSnk::Err,
+            SsSnk::Err,
+        >,
+    >
+    where
+        Cmd: MultiAggregateCommand,
+        Cmd::Aggregate:
+            MultiAggregateCommandHandler<Cmd> + EventSourced<MultiCommandHandlerEvent<Cmd>>,
+        <Cmd::Aggregate as Aggregate>::Id: Clone + Eq + Hash,
+        MultiCommandHandlerEvent<Cmd>: 'static,
+        MultiCommandHandlerOk<Cmd>: IntoIterator<Item = (<Cmd::Aggregate as Aggregate>::Id, Evs)>,
+        Evs: AsRef<[NumberedEvent<MultiCommandHandlerEvent<Cmd>>]>,
+        Mt: ?Sized,
+        SsSrc: SnapshotSource<Cmd::Aggregate> + ?Sized,
+        EvSrc: EventSource<Cmd::Aggregate, MultiCommandHandlerEvent<Cmd>> + ?Sized,
+        EvSnk: EventSink<Cmd::Aggregate, MultiCommandHandlerEvent<Cmd>, Mt> + ?Sized,
+        EvSnk::Err: AsVersionConflict,
+        SsSnk: SnapshotSink<Cmd::Aggregate> + ?Sized,
+        SsSnk::Err: fmt::Debug,
+        Repo: AsRef<SsSrc> + AsRef<EvSrc> + ?Sized,
+        UoW: UnitOfWork + ?Sized,
+        UoW::Transaction: AsRef<EvSnk> + AsRef<SsSnk>,
+        Ctx: BufferedContext + ?Sized,
+        Ctx::Meta: PerEventMeta<<Cmd::Aggregate as Aggregate>::Id>,
+    {
+        let ids = cmd.aggregate_ids();
+        let mut aggs = self
+            .load_aggregates_and_rehydrate::<SsSrc, EvSrc, _, _, _>(ids, repo)
+            .await?;
+
+        for id in ids {
+            if let Some(expected) = cmd.expected_version(id) {
+                let actual = aggs
+                    .iter()
+                    .find(|agg| agg.id() == id)
+                    .map_or(Version::Initial, HydratedAggregate::version);
+                if expected != actual {
+                    return Err(LoadAggregatesExecAndPersistError::VersionConflict {
+                        id: id.clone(),
+                        expected,
+                        actual,
+                    });
+                }
+            }
+        }
+
+        let res =
+            <Cmd::Aggregate as MultiAggregateCommandHandler<Cmd>>::handle(cmd, &aggs, handler_ctx)
+                .await
+                .map_err(LoadAggregatesExecAndPersistError::Exec)?;
+
+        let mut streams: Vec<(_, Option<Cmd::Aggregate>, Evs)> = vec![];
+        for (id, events) in res {
+            let evs = events.as_ref();
+            if evs.is_empty() {
+                continue;
+            }
+            let existing = aggs.iter().position(|agg| *agg.id() == id).or_else(|| {
+                streams.iter().find_map(|(i, initiated, _)| {
+                    initiated.as_ref().filter(|s| *s.id() == id).map(|_| *i)
+                })
+            });
+            if let Some(i) = existing {
+                streams.push((i, None, events));
+            } else {
+                // Newly initiated `Aggregate` has no unique ID to persist its
+                // `Event`s with, so the first `Event` is applied to a separate
+                // state to make it unique.
+                // See `Basic::exec_command_and_persist()` for details.
+                let mut initiated = Cmd::Aggregate::default();
+                initiated.apply(&evs[0].data);
+                aggs.push(HydratedAggregate::default());
+                streams.push((aggs.len() - 1, Some(initiated), events));
+            }
+        }
+        if streams.is_empty() {
+            return Ok(aggs);
+        }
+
+        self.apply_streams_and_persist_in_unit_of_work::<UoW, EvSnk, SsSnk, _, _, _, _, _>(
+            &mut aggs, streams, meta, uow, ctx,
+        )
+        .await?;
+        Ok(aggs)
+    }
 }
 
+/// Error of loading an [`Aggregate`] and persisting its snapshot.
 #[derive(Clone, Copy, Debug, Display, Eq, Error, From, PartialEq)]
 pub enum LoadRehydrateAndPersistError<SsSrcErr, EvSrcErr, SsSnkErr> {
+    /// Loading the [`Aggregate`] failed.
     Load(LoadError<SsSrcErr, EvSrcErr>),
+
+    /// Persisting the [`Aggregate`]'s snapshot failed.
     #[display(fmt = "Persisting aggregate snapshot failed: {}", _0)]
     #[from(ignore)]
     Persist(SsSnkErr),
 }
 
+/// Error of persisting an [`Aggregate`]'s events and snapshot.
 #[derive(Clone, Copy, Debug, Display, Eq, Error, PartialEq)]
 pub enum PersistError<EvSnkErr, SsSnkErr> {
+    /// Appending the events failed.
     #[display(fmt = "Persisting events failed: {}", _0)]
     Events(EvSnkErr),
+
+    /// Appending the events failed, because the [`Aggregate`] was modified
+    /// concurrently.
+    #[display(fmt = "Persisting events failed: {}", _0)]
+    VersionConflict(VersionConflict),
+
+    /// Persisting the [`Aggregate`]'s snapshot failed.
     #[display(fmt = "Persisting aggregate snapshot failed: {}", _0)]
     Snapshot(SsSnkErr),
 }
 
-#[derive(Clone, Copy, Debug, Display, Eq, Error, From, PartialEq)]
+/// Error of executing a [`Command`] and persisting its results.
+#[derive(Clone, Copy, Debug, Display, Eq, Error, PartialEq)]
 pub enum ExecAndPersistError<Agg, CmdErr, EvSnkErr, SsSnkErr> {
+    /// Executing the [`Command`] failed on the given [`Aggregate`].
     #[display(fmt = "Executing command failed: {}", _1)]
-    #[from(ignore)]
     Exec(HydratedAggregate<Agg>, #[error(source)] CmdErr),
+
+    /// [`Command::expected_version`] doesn't match the actual [`Version`] of
+    /// the [`Aggregate`].
+    #[display(
+        fmt = "Expected aggregate version {}, but actual is {}",
+        expected,
+        actual
+    )]
+    VersionConflict {
+        /// [`Version`] expected by the [`Command`].
+        expected: Version,
+        /// Actual [`Version`] of the [`Aggregate`].
+        actual: Version,
+    },
+
+    /// Persisting the produced events failed.
     Persist(PersistError<EvSnkErr, SsSnkErr>),
 }
 
+/// Error of loading an [`Aggregate`], executing a [`Command`] on it and
+/// persisting its results.
 #[derive(Clone, Copy, Debug, Display, Eq, Error, From, PartialEq)]
 pub enum LoadExecAndPersistError<Agg, CmdErr, SsSrcErr, EvSrcErr, EvSnkErr, SsSnkErr> {
+    /// Loading the [`Aggregate`] failed.
     Load(LoadError<SsSrcErr, EvSrcErr>),
+
+    /// Executing the [`Command`] failed on the given [`Aggregate`].
     #[display(fmt = "Executing command failed: {}", _1)]
     #[from(ignore)]
     Exec(HydratedAggregate<Agg>, #[error(source)] CmdErr),
+
+    /// [`Command::expected_version`] doesn't match the actual [`Version`] of
+    /// the [`Aggregate`].
+    #[display(
+        fmt = "Expected aggregate version {}, but actual is {}",
+        expected,
+        actual
+    )]
+    #[from(ignore)]
+    VersionConflict {
+        /// [`Version`] expected by the [`Command`].
+        expected: Version,
+        /// Actual [`Version`] of the [`Aggregate`].
+        actual: Version,
+    },
+
+    /// Persisting the produced events failed.
+    #[from(ignore)]
     Persist(PersistError<EvSnkErr, SsSnkErr>),
 }
 
@@ -466,7 +1116,84 @@ impl<Agg, CmdErr, SsSrcErr, EvSrcErr, EvSnkErr, SsSnkErr>
     fn from(err: ExecAndPersistError<Agg, CmdErr, EvSnkErr, SsSnkErr>) -> Self {
         match err {
             ExecAndPersistError::Exec(agg, e) => Self::Exec(agg, e),
+            ExecAndPersistError::VersionConflict { expected, actual } => {
+                Self::VersionConflict { expected, actual }
+            }
             ExecAndPersistError::Persist(e) => Self::Persist(e),
         }
     }
 }
+
+impl<Agg, CmdErr, EvSnkErr, SsSnkErr> From<VersionConflict>
+    for ExecAndPersistError<Agg, CmdErr, EvSnkErr, SsSnkErr>
+{
+    #[inline]
+    fn from(c: VersionConflict) -> Self {
+        Self::VersionConflict {
+            expected: c.expected,
+            actual: c.actual,
+        }
+    }
+}
+
+impl<Agg, CmdErr, EvSnkErr, SsSnkErr> From<PersistError<EvSnkErr, SsSnkErr>>
+    for ExecAndPersistError<Agg, CmdErr, EvSnkErr, SsSnkErr>
+{
+    #[inline]
+    fn from(err: PersistError<EvSnkErr, SsSnkErr>) -> Self {
+        match err {
+            PersistError::VersionConflict(c) => c.into(),
+            e => Self::Persist(e),
+        }
+    }
+}
+
+impl<Agg, CmdErr, SsSrcErr, EvSrcErr, EvSnkErr, SsSnkErr> From<PersistError<EvSnkErr, SsSnkErr>>
+    for LoadExecAndPersistError<Agg, CmdErr, SsSrcErr, EvSrcErr, EvSnkErr, SsSnkErr>
+{
+    #[inline]
+    fn from(err: PersistError<EvSnkErr, SsSnkErr>) -> Self {
+        ExecAndPersistError::from(err).into()
+    }
+}
+
+/// Error of loading [`Aggregate`]s, executing a [`MultiAggregateCommand`] on
+/// them and persisting its results.
+#[derive(Clone, Copy, Debug, Display, Eq, Error, From, PartialEq)]
+pub enum LoadAggregatesExecAndPersistError<
+    Id,
+    CmdErr,
+    SsSrcErr,
+    EvSrcErr,
+    TxErr,
+    EvSnkErr,
+    SsSnkErr,
+> {
+    /// Loading the [`Aggregate`]s failed.
+    Load(LoadError<SsSrcErr, EvSrcErr>),
+
+    /// Executing the [`MultiAggregateCommand`] failed.
+    #[display(fmt = "Executing command failed: {}", _0)]
+    #[from(ignore)]
+    Exec(CmdErr),
+
+    /// [`MultiAggregateCommand::expected_version`] doesn't match the actual
+    /// [`Version`] of one of the [`Aggregate`]s.
+    #[display(
+        fmt = "Expected aggregate version {}, but actual is {}",
+        expected,
+        actual
+    )]
+    #[from(ignore)]
+    VersionConflict {
+        /// ID of the conflicting [`Aggregate`].
+        id: Id,
+        /// [`Version`] expected by the [`MultiAggregateCommand`].
+        expected: Version,
+        /// Actual [`Version`] of the [`Aggregate`].
+        actual: Version,
+    },
+
+    /// Persisting the produced events within a [`UnitOfWork`] failed.
+    Persist(UnitOfWorkError<TxErr, EvSnkErr, SsSnkErr>),
+}
@@ -1,11 +1,19 @@
+#[cfg(not(feature = "send"))]
+use std::cell::RefCell;
+#[cfg(feature = "send")]
+use std::sync::Mutex;
 use std::{
     any::{Any, TypeId},
     borrow::Borrow,
-    cell::RefCell,
     collections::HashMap,
+    ops::DerefMut,
+    sync::Arc,
 };
 
-use cqrs_core::{EventSink, EventSource, NumberedEvent, SnapshotSink, SnapshotSource};
+use cqrs_core::{
+    CausalMeta, EventSink, EventSource, EventSourceWithMeta, MaybeSend, MaybeSync, NumberedEvent,
+    PerEventMeta, SnapshotSink, SnapshotSource, TypedGlobalEventSource,
+};
 
 // TODO: Required for `Borrow`/`AsRef` specialization on `Context` types,
 //       because Rust doesn't allow negative trait bounds at the moment,
@@ -21,24 +29,78 @@ impl<Agg, Err> BorrowableAsContext for (dyn SnapshotSink<Agg, Err = Err> + '_) {
 
 impl<Agg, Ev, Err> BorrowableAsContext for (dyn EventSource<Agg, Ev, Err = Err> + '_) {}
 
+impl<Agg, Ev, Mt, Err> BorrowableAsContext
+    for (dyn EventSourceWithMeta<Agg, Ev, Mt, Err = Err> + '_)
+{
+}
+
+impl<Id, Ev, Mt, Err> BorrowableAsContext
+    for (dyn TypedGlobalEventSource<Id, Ev, Mt, Err = Err> + '_)
+{
+}
+
 impl<Agg, Ev, Mt, Err, Ok> BorrowableAsContext
     for (dyn EventSink<Agg, Ev, Mt, Err = Err, Ok = Ok> + '_)
 {
 }
 
+/// Type-erased buffered events of a [`Context`], which is thread-safe only if
+/// the `send` feature is enabled.
+#[cfg(feature = "send")]
+type BufferedEvents = Mutex<HashMap<TypeId, Box<dyn Any + Send>>>;
+
+/// Type-erased buffered events of a [`Context`], which is thread-safe only if
+/// the `send` feature is enabled.
+#[cfg(not(feature = "send"))]
+type BufferedEvents = RefCell<HashMap<TypeId, Box<dyn Any>>>;
+
 pub struct Context<Impl> {
     implementation: Impl,
-    buffered_events: RefCell<HashMap<TypeId, Box<dyn Any>>>,
+    buffered_events: BufferedEvents,
 }
 
+#[cfg(feature = "send")]
+sa::assert_impl_all!(Context<()>: Send, Sync);
+
 impl<Impl> Context<Impl> {
     #[inline]
     pub fn new(implementation: Impl) -> Self {
         Self {
             implementation,
-            buffered_events: RefCell::new(HashMap::new()),
+            buffered_events: Default::default(),
         }
     }
+
+    #[cfg(feature = "send")]
+    #[inline]
+    fn buffered_events(&self) -> impl DerefMut<Target = HashMap<TypeId, Box<dyn Any + Send>>> + '_ {
+        self.buffered_events.lock().unwrap()
+    }
+
+    #[cfg(not(feature = "send"))]
+    #[inline]
+    fn buffered_events(&self) -> impl DerefMut<Target = HashMap<TypeId, Box<dyn Any>>> + '_ {
+        self.buffered_events.borrow_mut()
+    }
+
+    /// Buffers the given item along with the other items of the same type.
+    fn buffer<T: MaybeSend + 'static>(&self, item: T) {
+        self.buffered_events()
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(<Vec<T>>::new()))
+            .downcast_mut::<Vec<T>>()
+            .unwrap()
+            .push(item)
+    }
+
+    /// Takes all the buffered items of the given type.
+    fn take_buffered<T: 'static>(&self) -> Vec<T> {
+        let items = self.buffered_events().remove(&TypeId::of::<T>());
+        if items.is_none() {
+            return vec![];
+        }
+        *items.unwrap().downcast::<Vec<T>>().unwrap()
+    }
 }
 
 impl<T, Impl> AsRef<T> for Context<Impl>
@@ -52,24 +114,66 @@ where
     }
 }
 
+/// [`Context`] persisting events with the given metadata.
+///
+/// If created via [`ContextWithMeta::new`], events persisted while handling
+/// buffered events (see [`Static::exec_event_handlers`]) are persisted with the
+/// metadata [`caused`] by the metadata of the handled event, so the chains of
+/// causation are propagated automatically.
+///
+/// [`caused`]: CausalMeta::caused
+/// [`Static::exec_event_handlers`]: super::Static::exec_event_handlers
 pub struct ContextWithMeta<Impl, Mt> {
-    ctx: Context<Impl>,
-    meta: Mt,
+    ctx: Arc<Context<Impl>>,
+    meta: Arc<Mt>,
+    caused: Option<fn(&Mt) -> Mt>,
 }
 
 impl<Impl, Mt> ContextWithMeta<Impl, Mt> {
+    /// Creates new [`ContextWithMeta`] persisting events with the given
+    /// metadata, and the ones persisted while handling buffered events with
+    /// the metadata [`caused`] by the metadata of the handled event.
+    ///
+    /// [`caused`]: CausalMeta::caused
+    #[inline]
+    pub fn new(implementation: Impl, meta: Mt) -> Self
+    where
+        Mt: CausalMeta,
+    {
+        Self {
+            caused: Some(Mt::caused),
+            ..Self::new_non_causal(implementation, meta)
+        }
+    }
+
+    /// Creates new [`ContextWithMeta`] persisting all the events with the
+    /// given metadata, including the ones persisted while handling buffered
+    /// events.
     #[inline]
-    pub fn new(implementation: Impl, meta: Mt) -> Self {
+    pub fn new_non_causal(implementation: Impl, meta: Mt) -> Self {
         Self {
-            ctx: Context::new(implementation),
-            meta,
+            ctx: Arc::new(Context::new(implementation)),
+            meta: Arc::new(meta),
+            caused: None,
         }
     }
 
+    /// Returns the metadata events are persisted with within this
+    /// [`ContextWithMeta`].
     #[inline]
     pub fn meta(&self) -> &Mt {
         &self.meta
     }
+
+    /// Returns [`ContextWithMeta`] sharing the implementation and buffered
+    /// events with this one, but persisting events with the given metadata.
+    fn with_meta(&self, meta: Mt) -> Self {
+        Self {
+            ctx: self.ctx.clone(),
+            meta: Arc::new(meta),
+            caused: self.caused,
+        }
+    }
 }
 
 impl<T, Impl, Mt> AsRef<T> for ContextWithMeta<Impl, Mt>
@@ -79,14 +183,53 @@ where
 {
     #[inline]
     fn as_ref(&self) -> &T {
-        self.ctx.as_ref()
+        (*self.ctx).as_ref()
     }
 }
 
-pub trait BufferedContext: self::private::Sealed {
-    fn buffer_event<Ev: 'static>(&self, ev: NumberedEvent<Ev>);
+pub trait BufferedContext: private::Sealed {
+    /// Origin of the buffered events, determining the context the events
+    /// caused by them are persisted within.
+    type Origin: MaybeSend + 'static;
+
+    /// Metadata the events are persisted with within this context.
+    type Meta;
+
+    /// Buffers the given event of the [`Aggregate`] with the given ID, which
+    /// has been persisted with the metadata of this context.
+    ///
+    /// [`Aggregate`]: cqrs_core::Aggregate
+    fn buffer_event<Id, Ev>(&self, id: &Id, ev: NumberedEvent<Ev>)
+    where
+        Id: ?Sized,
+        Ev: MaybeSend + 'static,
+        Self::Meta: PerEventMeta<Id>;
 
     fn take_buffered_events<Ev: 'static>(&self) -> Vec<NumberedEvent<Ev>>;
+
+    /// Takes all the buffered events of type `Ev` along with their
+    /// [`Origin`]s.
+    ///
+    /// [`Origin`]: BufferedContext::Origin
+    fn take_buffered_events_with_origin<Ev: 'static>(
+        &self,
+    ) -> Vec<(NumberedEvent<Ev>, Self::Origin)>;
+
+    /// Buffers back the event taken along with its [`Origin`].
+    ///
+    /// [`Origin`]: BufferedContext::Origin
+    fn rebuffer_event<Ev: MaybeSend + 'static>(&self, ev: NumberedEvent<Ev>, origin: Self::Origin);
+
+    /// Returns the context the events caused by the events of the given
+    /// [`Origin`] should be persisted within.
+    ///
+    /// [`None`] means that the caused events should be persisted within this
+    /// context.
+    ///
+    /// [`Origin`]: BufferedContext::Origin
+    fn caused_by(&self, origin: &Self::Origin) -> Option<Self>
+    where
+        Self: Sized;
 }
 
 /// Preventing users from implementing the [`BufferedContext`] trait.
@@ -99,41 +242,79 @@ mod private {
 }
 
 impl<Impl> BufferedContext for Context<Impl> {
+    type Origin = ();
+
+    type Meta = ();
+
     #[inline]
-    fn buffer_event<Ev: 'static>(&self, ev: NumberedEvent<Ev>) {
-        self.buffered_events
-            .borrow_mut()
-            .entry(TypeId::of::<Ev>())
-            .or_insert_with(|| Box::new(<Vec<NumberedEvent<Ev>>>::new()))
-            .downcast_mut::<Vec<NumberedEvent<Ev>>>()
-            .unwrap()
-            .push(ev)
+    fn buffer_event<Id: ?Sized, Ev: MaybeSend + 'static>(&self, _: &Id, ev: NumberedEvent<Ev>) {
+        self.buffer(ev)
     }
 
     #[inline]
     fn take_buffered_events<Ev: 'static>(&self) -> Vec<NumberedEvent<Ev>> {
-        let events = self
-            .buffered_events
-            .borrow_mut()
-            .remove(&TypeId::of::<Ev>());
-        if events.is_none() {
-            return vec![];
-        }
-        *events
-            .unwrap()
-            .downcast::<Vec<NumberedEvent<Ev>>>()
-            .unwrap()
+        self.take_buffered()
+    }
+
+    #[inline]
+    fn take_buffered_events_with_origin<Ev: 'static>(&self) -> Vec<(NumberedEvent<Ev>, ())> {
+        self.take_buffered()
+            .into_iter()
+            .map(|ev| (ev, ()))
+            .collect()
+    }
+
+    #[inline]
+    fn rebuffer_event<Ev: MaybeSend + 'static>(&self, ev: NumberedEvent<Ev>, _: ()) {
+        self.buffer(ev)
+    }
+
+    #[inline]
+    fn caused_by(&self, _: &()) -> Option<Self> {
+        None
     }
 }
 
-impl<Impl, Mt> BufferedContext for ContextWithMeta<Impl, Mt> {
+impl<Impl, Mt> BufferedContext for ContextWithMeta<Impl, Mt>
+where
+    Mt: MaybeSend + MaybeSync + 'static,
+{
+    /// Metadata the buffered event has been persisted with.
+    type Origin = Arc<Mt>;
+
+    type Meta = Mt;
+
     #[inline]
-    fn buffer_event<Ev: 'static>(&self, ev: NumberedEvent<Ev>) {
-        self.ctx.buffer_event(ev)
+    fn buffer_event<Id, Ev>(&self, id: &Id, ev: NumberedEvent<Ev>)
+    where
+        Id: ?Sized,
+        Ev: MaybeSend + 'static,
+        Mt: PerEventMeta<Id>,
+    {
+        let meta = self.meta.for_event(id, ev.num);
+        self.rebuffer_event(ev, Arc::new(meta))
     }
 
     #[inline]
     fn take_buffered_events<Ev: 'static>(&self) -> Vec<NumberedEvent<Ev>> {
-        self.ctx.take_buffered_events()
+        self.take_buffered_events_with_origin()
+            .into_iter()
+            .map(|(ev, _)| ev)
+            .collect()
+    }
+
+    #[inline]
+    fn take_buffered_events_with_origin<Ev: 'static>(&self) -> Vec<(NumberedEvent<Ev>, Arc<Mt>)> {
+        self.ctx.take_buffered()
+    }
+
+    #[inline]
+    fn rebuffer_event<Ev: MaybeSend + 'static>(&self, ev: NumberedEvent<Ev>, meta: Arc<Mt>) {
+        self.ctx.buffer((ev, meta))
+    }
+
+    #[inline]
+    fn caused_by(&self, meta: &Arc<Mt>) -> Option<Self> {
+        self.caused.map(|caused| self.with_meta(caused(meta)))
     }
 }
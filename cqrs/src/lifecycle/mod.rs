@@ -1,17 +1,24 @@
 mod basic;
 mod context;
 mod r#static;
+mod unit_of_work;
 
-use cqrs_core::{Command, CommandHandler, EventSink, EventSource, SnapshotSink, SnapshotSource};
+use cqrs_core::{
+    Command, CommandHandler, EventSink, EventSource, MultiAggregateCommand,
+    MultiAggregateCommandHandler, SnapshotSink, SnapshotSource,
+};
 
 #[doc(inline)]
 pub use self::{
     basic::{
-        Basic, ExecAndPersistError, LoadError, LoadExecAndPersistError,
-        LoadRehydrateAndPersistError, PersistError,
+        Basic, ExecAndPersistError, LoadAggregatesExecAndPersistError, LoadError,
+        LoadExecAndPersistError, LoadRehydrateAndPersistError, PersistError,
     },
     context::{BorrowableAsContext, BufferedContext, Context, ContextWithMeta},
     r#static::Static,
+    unit_of_work::{
+        Atomicity, BestEffort, BestEffortTransaction, Transaction, UnitOfWork, UnitOfWorkError,
+    },
 };
 
 type CommandHandlerOk<Cmd> = <<Cmd as Command>::Aggregate as CommandHandler<Cmd>>::Ok;
@@ -19,6 +26,15 @@ type CommandHandlerErr<Cmd> = <<Cmd as Command>::Aggregate as CommandHandler<Cmd
 type CommandHandlerEvent<Cmd> = <<Cmd as Command>::Aggregate as CommandHandler<Cmd>>::Event;
 type CommandHandlerContext<Cmd> = <<Cmd as Command>::Aggregate as CommandHandler<Cmd>>::Context;
 
+type MultiCommandHandlerOk<Cmd> =
+    <<Cmd as MultiAggregateCommand>::Aggregate as MultiAggregateCommandHandler<Cmd>>::Ok;
+type MultiCommandHandlerErr<Cmd> =
+    <<Cmd as MultiAggregateCommand>::Aggregate as MultiAggregateCommandHandler<Cmd>>::Err;
+type MultiCommandHandlerEvent<Cmd> =
+    <<Cmd as MultiAggregateCommand>::Aggregate as MultiAggregateCommandHandler<Cmd>>::Event;
+type MultiCommandHandlerContext<Cmd> =
+    <<Cmd as MultiAggregateCommand>::Aggregate as MultiAggregateCommandHandler<Cmd>>::Context;
+
 type EventSinkErr<Impl, Cmd, Mt> =
     <Impl as EventSink<<Cmd as Command>::Aggregate, CommandHandlerEvent<Cmd>, Mt>>::Err;
 type EventSourceErr<Impl, Cmd> =
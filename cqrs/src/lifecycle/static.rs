@@ -1,18 +1,26 @@
-use std::borrow::Borrow;
+use std::{borrow::Borrow, fmt, hash::Hash, iter, num::NonZeroUsize, time::SystemTime};
 
 use async_trait::async_trait;
 use cqrs_core::{
-    Aggregate, Command, CommandHandler, Event, EventSink, EventSource, EventSourced,
-    HydratedAggregate, NumberedEvent, SnapshotSink, SnapshotSource, SnapshotStrategy,
+    Aggregate, AsVersionConflict, BoxTryStream, Command, CommandHandler, Event, EventSink,
+    EventSource, EventSourceWithMeta, EventSourced, HydratedAggregate, MaybeSend, MaybeSync,
+    MultiAggregateCommand, MultiAggregateCommandHandler, NumberedEvent, PerEventMeta, ReadRange,
+    SnapshotSink, SnapshotSource, SnapshotStrategy, TimestampedMeta, Version,
 };
+use futures::{stream, FutureExt as _, Stream, StreamExt as _};
 
-use crate::{CommandBus, EventHandler, EventProcessingConfiguration, RegisteredEvent};
+use crate::{
+    CommandBus, EventHandler, EventHandlersError, EventProcessingConfiguration, ExecutionMode,
+    RegisteredEvent,
+};
 
 use super::{
     Basic, BorrowableAsContext, BufferedContext, CommandHandlerContext, CommandHandlerErr,
     CommandHandlerEvent, CommandHandlerOk, Context, ContextWithMeta, EventSinkErr, EventSourceErr,
-    ExecAndPersistError, LoadError, LoadExecAndPersistError, LoadRehydrateAndPersistError,
-    PersistError, SnapshotSinkErr, SnapshotSourceErr,
+    ExecAndPersistError, LoadAggregatesExecAndPersistError, LoadError, LoadExecAndPersistError,
+    LoadRehydrateAndPersistError, MultiCommandHandlerContext, MultiCommandHandlerErr,
+    MultiCommandHandlerEvent, MultiCommandHandlerOk, PersistError, SnapshotSinkErr,
+    SnapshotSourceErr, UnitOfWork, UnitOfWorkError,
 };
 
 #[derive(Debug)]
@@ -21,6 +29,9 @@ pub struct Static<Snp, Ctx> {
     ctx: Ctx,
 }
 
+#[cfg(feature = "send")]
+sa::assert_impl_all!(Static<(), ContextWithMeta<(), ()>>: Send, Sync);
+
 impl<Snp, Ctx> Static<Snp, Ctx> {
     #[inline]
     pub fn new(snapshot_strategy: Snp, ctx: Ctx) -> Self {
@@ -29,6 +40,16 @@ impl<Snp, Ctx> Static<Snp, Ctx> {
             ctx,
         }
     }
+
+    /// Sets maximum number of [`Aggregate`]s' events streams being read
+    /// concurrently, if the [`EventSource`] doesn't support batch reading.
+    ///
+    /// See [`Basic::with_read_concurrency`] for details.
+    #[inline]
+    pub fn with_read_concurrency(mut self, limit: NonZeroUsize) -> Self {
+        self.basic_lifecycle = self.basic_lifecycle.with_read_concurrency(limit);
+        self
+    }
 }
 
 impl<Snp, Ctx> AsRef<Static<Snp, Ctx>> for Static<Snp, Ctx> {
@@ -80,6 +101,26 @@ impl<Snp, Ctx> Static<Snp, Ctx> {
             .await
     }
 
+    /// Reads events of the [`Aggregate`] within the given [`ReadRange`].
+    ///
+    /// See [`Basic::read_events`] for details.
+    #[inline]
+    pub fn read_events<'a, EvSrc, Ev, Agg>(
+        &'a self,
+        id: &Agg::Id,
+        range: ReadRange,
+    ) -> BoxTryStream<'a, NumberedEvent<Ev>, EvSrc::Err>
+    where
+        Agg: Aggregate + EventSourced<Ev>,
+        Ev: MaybeSend + 'a,
+        EvSrc: EventSource<Agg, Ev> + ?Sized + 'a,
+        EvSrc::Err: 'a,
+        Ctx: AsRef<EvSrc>,
+    {
+        self.basic_lifecycle
+            .read_events::<EvSrc, Ev, _>(id, range, self.ctx.as_ref())
+    }
+
     #[inline]
     pub async fn rehydrate_aggregate<EvSrc, Ev, Agg>(
         &self,
@@ -111,6 +152,47 @@ impl<Snp, Ctx> Static<Snp, Ctx> {
             .await
     }
 
+    /// Rebuilds the [`Aggregate`] as it was at the given [`Version`].
+    ///
+    /// See [`Basic::load_aggregate_at_version`] for details.
+    #[inline]
+    pub async fn load_aggregate_at_version<SsSrc, EvSrc, Ev, Agg>(
+        &self,
+        id: &Agg::Id,
+        ver: Version,
+    ) -> Result<Option<HydratedAggregate<Agg>>, LoadError<SsSrc::Err, EvSrc::Err>>
+    where
+        Agg: Aggregate + EventSourced<Ev>,
+        SsSrc: SnapshotSource<Agg> + ?Sized,
+        EvSrc: EventSource<Agg, Ev> + ?Sized,
+        Ctx: AsRef<SsSrc> + AsRef<EvSrc>,
+    {
+        self.basic_lifecycle
+            .load_aggregate_at_version::<SsSrc, EvSrc, Ev, _, _>(id, ver, &self.ctx)
+            .await
+    }
+
+    /// Rebuilds the [`Aggregate`] as it was at the given moment.
+    ///
+    /// See [`Basic::load_aggregate_at_time`] for details.
+    #[inline]
+    pub async fn load_aggregate_at_time<SsSrc, EvSrc, Ev, Mt, Agg>(
+        &self,
+        id: &Agg::Id,
+        at: SystemTime,
+    ) -> Result<Option<HydratedAggregate<Agg>>, LoadError<SsSrc::Err, EvSrc::Err>>
+    where
+        Agg: Aggregate + EventSourced<Ev>,
+        Mt: TimestampedMeta,
+        SsSrc: SnapshotSource<Agg> + ?Sized,
+        EvSrc: EventSourceWithMeta<Agg, Ev, Mt> + ?Sized,
+        Ctx: AsRef<SsSrc> + AsRef<EvSrc>,
+    {
+        self.basic_lifecycle
+            .load_aggregate_at_time::<SsSrc, EvSrc, Ev, Mt, _, _>(id, at, &self.ctx)
+            .await
+    }
+
     #[inline]
     pub async fn load_aggregates_and_rehydrate<SsSrc, EvSrc, Ev, Agg>(
         &self,
@@ -118,6 +200,7 @@ impl<Snp, Ctx> Static<Snp, Ctx> {
     ) -> Result<Vec<HydratedAggregate<Agg>>, LoadError<SsSrc::Err, EvSrc::Err>>
     where
         Agg: Aggregate + EventSourced<Ev>,
+        Agg::Id: Eq + Hash,
         SsSrc: SnapshotSource<Agg> + ?Sized,
         EvSrc: EventSource<Agg, Ev> + ?Sized,
         Ctx: AsRef<SsSrc> + AsRef<EvSrc>,
@@ -140,6 +223,7 @@ where
     where
         Agg: Aggregate,
         SsSnk: SnapshotSink<Agg> + ?Sized,
+        SsSnk::Err: fmt::Debug,
         Ctx: AsRef<SsSnk>,
     {
         self.basic_lifecycle
@@ -155,6 +239,7 @@ where
     where
         Agg: Aggregate,
         SsSnk: SnapshotSink<Agg> + ?Sized,
+        SsSnk::Err: fmt::Debug,
         Ctx: AsRef<SsSnk>,
     {
         self.basic_lifecycle
@@ -172,6 +257,7 @@ where
         SsSrc: SnapshotSource<Agg> + ?Sized,
         EvSrc: EventSource<Agg, Ev> + ?Sized,
         SsSnk: SnapshotSink<Agg> + ?Sized,
+        SsSnk::Err: fmt::Debug,
         Ctx: AsRef<SsSrc> + AsRef<EvSrc> + AsRef<SsSnk>,
     {
         self.basic_lifecycle
@@ -186,9 +272,11 @@ where
     ) -> Result<(), LoadRehydrateAndPersistError<SsSrc::Err, EvSrc::Err, SsSnk::Err>>
     where
         Agg: Aggregate + EventSourced<Ev>,
+        Agg::Id: Eq + Hash,
         SsSrc: SnapshotSource<Agg> + ?Sized,
         EvSrc: EventSource<Agg, Ev> + ?Sized,
         SsSnk: SnapshotSink<Agg> + ?Sized,
+        SsSnk::Err: fmt::Debug,
         Ctx: AsRef<SsSrc> + AsRef<EvSrc> + AsRef<SsSnk>,
     {
         self.basic_lifecycle
@@ -210,12 +298,14 @@ where
     ) -> Result<(), PersistError<EvSnk::Err, SsSnk::Err>>
     where
         Agg: Aggregate + EventSourced<Ev>,
-        Ev: 'static,
+        Ev: MaybeSend + 'static,
         Evs: AsRef<[NumberedEvent<Ev>]>,
         Mt: ?Sized,
         EvSnk: EventSink<Agg, Ev, Mt> + ?Sized,
+        EvSnk::Err: AsVersionConflict,
         SsSnk: SnapshotSink<Agg> + ?Sized,
-        Impl: Borrow<EvSnk> + Borrow<SsSnk>,
+        SsSnk::Err: fmt::Debug,
+        Impl: Borrow<EvSnk> + Borrow<SsSnk> + MaybeSync,
     {
         self.basic_lifecycle
             .apply_events_and_persist::<EvSnk, SsSnk, Ev, _, _, _, _, _>(
@@ -228,6 +318,37 @@ where
             .await
     }
 
+    #[inline]
+    pub async fn apply_events_and_persist_in_unit_of_work<UoW, EvSnk, SsSnk, Ev, Agg, Evs, Mt>(
+        &self,
+        agg: &mut HydratedAggregate<Agg>,
+        events: Evs,
+        meta: &Mt,
+    ) -> Result<(), UnitOfWorkError<UoW::Err, EvSnk::Err, SsSnk::Err>>
+    where
+        Agg: Aggregate + EventSourced<Ev>,
+        Ev: MaybeSend + 'static,
+        Evs: AsRef<[NumberedEvent<Ev>]>,
+        Mt: ?Sized,
+        EvSnk: EventSink<Agg, Ev, Mt> + ?Sized,
+        EvSnk::Err: AsVersionConflict,
+        SsSnk: SnapshotSink<Agg> + ?Sized,
+        SsSnk::Err: fmt::Debug,
+        UoW: UnitOfWork + ?Sized,
+        UoW::Transaction: AsRef<EvSnk> + AsRef<SsSnk>,
+        Impl: Borrow<UoW>,
+    {
+        self.basic_lifecycle
+            .apply_events_and_persist_in_unit_of_work::<UoW, EvSnk, SsSnk, Ev, _, _, _, _>(
+                agg,
+                events,
+                meta,
+                self.ctx.as_ref(),
+                Some(&self.ctx),
+            )
+            .await
+    }
+
     #[inline]
     pub async fn exec_command_and_persist<EvSnk, SsSnk, Cmd, Mt>(
         &self,
@@ -245,8 +366,10 @@ where
         CommandHandlerOk<Cmd>: AsRef<[NumberedEvent<CommandHandlerEvent<Cmd>>]> + 'static,
         Mt: ?Sized,
         EvSnk: EventSink<Cmd::Aggregate, CommandHandlerEvent<Cmd>, Mt> + ?Sized,
+        EvSnk::Err: AsVersionConflict,
         SsSnk: SnapshotSink<Cmd::Aggregate> + ?Sized,
-        Impl: Borrow<EvSnk> + Borrow<SsSnk>,
+        SsSnk::Err: fmt::Debug,
+        Impl: Borrow<EvSnk> + Borrow<SsSnk> + MaybeSync,
         Self: AsRef<CommandHandlerContext<Cmd>>,
     {
         self.basic_lifecycle
@@ -286,8 +409,10 @@ where
         SsSrc: SnapshotSource<Cmd::Aggregate> + ?Sized,
         EvSrc: EventSource<Cmd::Aggregate, CommandHandlerEvent<Cmd>> + ?Sized,
         EvSnk: EventSink<Cmd::Aggregate, CommandHandlerEvent<Cmd>, Mt> + ?Sized,
+        EvSnk::Err: AsVersionConflict,
         SsSnk: SnapshotSink<Cmd::Aggregate> + ?Sized,
-        Impl: Borrow<SsSrc> + Borrow<EvSrc> + Borrow<EvSnk> + Borrow<SsSnk>,
+        SsSnk::Err: fmt::Debug,
+        Impl: Borrow<SsSrc> + Borrow<EvSrc> + Borrow<EvSnk> + Borrow<SsSnk> + MaybeSync,
         Self: AsRef<CommandHandlerContext<Cmd>>,
     {
         self.basic_lifecycle
@@ -300,11 +425,71 @@ where
             )
             .await
     }
+
+    #[inline]
+    pub async fn load_aggregates_exec_command_and_persist<
+        SsSrc,
+        EvSrc,
+        UoW,
+        EvSnk,
+        SsSnk,
+        Cmd,
+        Evs,
+        Mt,
+    >(
+        &self,
+        cmd: Cmd,
+        meta: &Mt,
+    ) -> Result<
+        Vec<HydratedAggregate<Cmd::Aggregate>>,
+        LoadAggregatesExecAndPersistError<
+            <Cmd::Aggregate as Aggregate>::Id,
+            MultiCommandHandlerErr<Cmd>,
+            SsSrc::Err,
+            EvSrc::Err,
+            UoW::Err,
+            EvSnk::Err,
+            SsSnk::Err,
+        >,
+    >
+    where
+        Cmd: MultiAggregateCommand,
+        Cmd::Aggregate:
+            MultiAggregateCommandHandler<Cmd> + EventSourced<MultiCommandHandlerEvent<Cmd>>,
+        <Cmd::Aggregate as Aggregate>::Id: Clone + Eq + Hash,
+        MultiCommandHandlerEvent<Cmd>: 'static,
+        MultiCommandHandlerOk<Cmd>: IntoIterator<Item = (<Cmd::Aggregate as Aggregate>::Id, Evs)>,
+        Evs: AsRef<[NumberedEvent<MultiCommandHandlerEvent<Cmd>>]>,
+        Mt: ?Sized,
+        SsSrc: SnapshotSource<Cmd::Aggregate> + ?Sized,
+        EvSrc: EventSource<Cmd::Aggregate, MultiCommandHandlerEvent<Cmd>> + ?Sized,
+        EvSnk: EventSink<Cmd::Aggregate, MultiCommandHandlerEvent<Cmd>, Mt> + ?Sized,
+        EvSnk::Err: AsVersionConflict,
+        SsSnk: SnapshotSink<Cmd::Aggregate> + ?Sized,
+        SsSnk::Err: fmt::Debug,
+        UoW: UnitOfWork + ?Sized,
+        UoW::Transaction: AsRef<EvSnk> + AsRef<SsSnk>,
+        Impl: Borrow<SsSrc> + Borrow<EvSrc> + Borrow<UoW>,
+        Self: AsRef<MultiCommandHandlerContext<Cmd>>,
+    {
+        self.basic_lifecycle
+            .load_aggregates_exec_command_and_persist::<SsSrc, EvSrc, UoW, EvSnk, SsSnk, _, _, _, _, _>(
+                cmd,
+                meta,
+                self.as_ref(),
+                &self.ctx,
+                self.ctx.as_ref(),
+                Some(&self.ctx),
+            )
+            .await
+    }
 }
 
 impl<Snp, Impl, Mt> Static<Snp, ContextWithMeta<Impl, Mt>>
 where
     Snp: SnapshotStrategy,
+    Impl: MaybeSend,
+    Mt: MaybeSend + MaybeSync + 'static,
 {
     #[inline]
     pub async fn apply_events_and_persist<EvSnk, SsSnk, Ev, Agg, Evs>(
@@ -314,11 +499,14 @@ where
     ) -> Result<(), PersistError<EvSnk::Err, SsSnk::Err>>
     where
         Agg: Aggregate + EventSourced<Ev>,
-        Ev: 'static,
+        Ev: MaybeSend + 'static,
         Evs: AsRef<[NumberedEvent<Ev>]>,
         EvSnk: EventSink<Agg, Ev, Mt> + ?Sized,
+        Mt: PerEventMeta<Agg::Id>,
+        EvSnk::Err: AsVersionConflict,
         SsSnk: SnapshotSink<Agg> + ?Sized,
-        Impl: Borrow<EvSnk> + Borrow<SsSnk>,
+        SsSnk::Err: fmt::Debug,
+        Impl: Borrow<EvSnk> + Borrow<SsSnk> + MaybeSync,
     {
         self.basic_lifecycle
             .apply_events_and_persist::<EvSnk, SsSnk, Ev, _, _, _, _, _>(
@@ -331,6 +519,36 @@ where
             .await
     }
 
+    #[inline]
+    pub async fn apply_events_and_persist_in_unit_of_work<UoW, EvSnk, SsSnk, Ev, Agg, Evs>(
+        &self,
+        agg: &mut HydratedAggregate<Agg>,
+        events: Evs,
+    ) -> Result<(), UnitOfWorkError<UoW::Err, EvSnk::Err, SsSnk::Err>>
+    where
+        Agg: Aggregate + EventSourced<Ev>,
+        Ev: MaybeSend + 'static,
+        Evs: AsRef<[NumberedEvent<Ev>]>,
+        EvSnk: EventSink<Agg, Ev, Mt> + ?Sized,
+        Mt: PerEventMeta<Agg::Id>,
+        EvSnk::Err: AsVersionConflict,
+        SsSnk: SnapshotSink<Agg> + ?Sized,
+        SsSnk::Err: fmt::Debug,
+        UoW: UnitOfWork + ?Sized,
+        UoW::Transaction: AsRef<EvSnk> + AsRef<SsSnk>,
+        Impl: Borrow<UoW>,
+    {
+        self.basic_lifecycle
+            .apply_events_and_persist_in_unit_of_work::<UoW, EvSnk, SsSnk, Ev, _, _, _, _>(
+                agg,
+                events,
+                self.ctx.meta(),
+                self.ctx.as_ref(),
+                Some(&self.ctx),
+            )
+            .await
+    }
+
     #[inline]
     pub async fn exec_command_and_persist<EvSnk, SsSnk, Cmd>(
         &self,
@@ -346,8 +564,11 @@ where
         CommandHandlerEvent<Cmd>: 'static,
         CommandHandlerOk<Cmd>: AsRef<[NumberedEvent<CommandHandlerEvent<Cmd>>]> + 'static,
         EvSnk: EventSink<Cmd::Aggregate, CommandHandlerEvent<Cmd>, Mt> + ?Sized,
+        Mt: PerEventMeta<<Cmd::Aggregate as Aggregate>::Id>,
+        EvSnk::Err: AsVersionConflict,
         SsSnk: SnapshotSink<Cmd::Aggregate> + ?Sized,
-        Impl: Borrow<EvSnk> + Borrow<SsSnk>,
+        SsSnk::Err: fmt::Debug,
+        Impl: Borrow<EvSnk> + Borrow<SsSnk> + MaybeSync,
         Self: AsRef<CommandHandlerContext<Cmd>>,
     {
         self.basic_lifecycle
@@ -385,8 +606,11 @@ where
         SsSrc: SnapshotSource<Cmd::Aggregate> + ?Sized,
         EvSrc: EventSource<Cmd::Aggregate, CommandHandlerEvent<Cmd>> + ?Sized,
         EvSnk: EventSink<Cmd::Aggregate, CommandHandlerEvent<Cmd>, Mt> + ?Sized,
+        Mt: PerEventMeta<<Cmd::Aggregate as Aggregate>::Id>,
+        EvSnk::Err: AsVersionConflict,
         SsSnk: SnapshotSink<Cmd::Aggregate> + ?Sized,
-        Impl: Borrow<SsSrc> + Borrow<EvSrc> + Borrow<EvSnk> + Borrow<SsSnk>,
+        SsSnk::Err: fmt::Debug,
+        Impl: Borrow<SsSrc> + Borrow<EvSrc> + Borrow<EvSnk> + Borrow<SsSnk> + MaybeSync,
         Self: AsRef<CommandHandlerContext<Cmd>>,
     {
         self.basic_lifecycle
@@ -399,29 +623,167 @@ where
             )
             .await
     }
+
+    #[inline]
+    pub async fn load_aggregates_exec_command_and_persist<
+        SsSrc,
+        EvSrc,
+        UoW,
+        EvSnk,
+        SsSnk,
+        Cmd,
+        Evs,
+    >(
+        &self,
+        cmd: Cmd,
+    ) -> Result<
+        Vec<HydratedAggregate<Cmd::Aggregate>>,
+        LoadAggregatesExecAndPersistError<
+            <Cmd::Aggregate as Aggregate>::Id,
+            MultiCommandHandlerErr<Cmd>,
+            SsSrc::Err,
+            EvSrc::Err,
+            UoW::Err,
+            EvSnk::Err,
+            SsSnk::Err,
+        >,
+    >
+    where
+        Cmd: MultiAggregateCommand,
+        Cmd::Aggregate:
+            MultiAggregateCommandHandler<Cmd> + EventSourced<MultiCommandHandlerEvent<Cmd>>,
+        <Cmd::Aggregate as Aggregate>::Id: Clone + Eq + Hash,
+        MultiCommandHandlerEvent<Cmd>: 'static,
+        MultiCommandHandlerOk<Cmd>: IntoIterator<Item = (<Cmd::Aggregate as Aggregate>::Id, Evs)>,
+        Evs: AsRef<[NumberedEvent<MultiCommandHandlerEvent<Cmd>>]>,
+        SsSrc: SnapshotSource<Cmd::Aggregate> + ?Sized,
+        EvSrc: EventSource<Cmd::Aggregate, MultiCommandHandlerEvent<Cmd>> + ?Sized,
+        EvSnk: EventSink<Cmd::Aggregate, MultiCommandHandlerEvent<Cmd>, Mt> + ?Sized,
+        Mt: PerEventMeta<<Cmd::Aggregate as Aggregate>::Id>,
+        EvSnk::Err: AsVersionConflict,
+        SsSnk: SnapshotSink<Cmd::Aggregate> + ?Sized,
+        SsSnk::Err: fmt::Debug,
+        UoW: UnitOfWork + ?Sized,
+        UoW::Transaction: AsRef<EvSnk> + AsRef<SsSnk>,
+        Impl: Borrow<SsSrc> + Borrow<EvSrc> + Borrow<UoW>,
+        Self: AsRef<MultiCommandHandlerContext<Cmd>>,
+    {
+        self.basic_lifecycle
+            .load_aggregates_exec_command_and_persist::<SsSrc, EvSrc, UoW, EvSnk, SsSnk, _, _, _, _, _>(
+                cmd,
+                self.ctx.meta(),
+                self.as_ref(),
+                &self.ctx,
+                self.ctx.as_ref(),
+                Some(&self.ctx),
+            )
+            .await
+    }
 }
 
 impl<Snp, Ctx> Static<Snp, Ctx> {
+    /// Executes [`EventHandler`]s of all the buffered events of type `Ev`,
+    /// according to the [`ExecutionMode`] of the given
+    /// [`EventProcessingConfiguration`].
+    ///
+    /// Events are processed in order. Failure of any [`EventHandler`] doesn't
+    /// prevent others from execution, and all the failures are returned at
+    /// once, unless the [`EventHandler`] is registered with
+    /// [`FailurePolicy::fail_fast`]. Its failure stops the execution, and
+    /// the failed event is buffered back along with the ones not handled yet,
+    /// so the next execution delivers it again to all its [`EventHandler`]s
+    /// (including the ones which have already succeeded).
+    ///
+    /// [`EventHandler`]s of every event are given a lifecycle sharing this
+    /// one's context, but persisting events with the metadata caused by the
+    /// metadata of the handled event, if any (see [`ContextWithMeta`] for
+    /// details).
+    ///
+    /// [`FailurePolicy::fail_fast`]: crate::FailurePolicy::fail_fast
     pub async fn exec_event_handlers<Ev, Err>(
         &self,
         cfg: &EventProcessingConfiguration,
-    ) -> Result<(), Err>
+    ) -> Result<(), EventHandlersError<Err>>
     where
-        Ev: RegisteredEvent,
-        Snp: 'static,
-        Ctx: BufferedContext + 'static,
+        Ev: RegisteredEvent + MaybeSend + MaybeSync,
+        Snp: Clone + MaybeSync + 'static,
+        Ctx: BufferedContext + MaybeSync + 'static,
         Err: 'static,
     {
-        Ok(for ev in self.ctx.take_buffered_events::<Ev>() {
-            // TODO: execute handlers concurrently?
-            for handler in cfg.iter_event_handlers_of::<Ev, Self, Err>(&ev.data) {
-                handler.on(&ev.data, &self).await?
+        let mut errors = vec![];
+        let mut events = self
+            .ctx
+            .take_buffered_events_with_origin::<Ev>()
+            .into_iter();
+        while let Some((ev, origin)) = events.next() {
+            let caused = self.ctx.caused_by(&origin).map(|ctx| Self {
+                basic_lifecycle: self.basic_lifecycle.clone(),
+                ctx,
+            });
+            let lifecycle = caused.as_ref().unwrap_or(self);
+            let results = cfg
+                .iter_event_handlers_of::<Ev, Self, Err>(&ev.data)
+                .map(|handler| {
+                    let fails_fast = handler.fails_fast();
+                    handler
+                        .on(&ev.data, lifecycle)
+                        .map(move |res| (res, fails_fast))
+                });
+            let failed_fast = match cfg.execution_mode() {
+                ExecutionMode::Sequential => {
+                    collect_errors(stream::iter(results).then(|res| res), &mut errors).await
+                }
+                ExecutionMode::Concurrent(limit) => {
+                    collect_errors(stream::iter(results).buffered(limit.get()), &mut errors).await
+                }
+                ExecutionMode::ConcurrentUnordered => {
+                    collect_errors(
+                        results.collect::<stream::FuturesUnordered<_>>(),
+                        &mut errors,
+                    )
+                    .await
+                }
+            };
+            if failed_fast {
+                let newer = self.ctx.take_buffered_events_with_origin::<Ev>();
+                for (ev, origin) in iter::once((ev, origin)).chain(events).chain(newer) {
+                    self.ctx.rebuffer_event(ev, origin);
+                }
+                break;
             }
-        })
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.into())
+        }
+    }
+}
+
+/// Collects failures of [`EventHandler`]s from the given `results`, until
+/// the one registered with [`FailurePolicy::fail_fast`] fails.
+///
+/// Returns whether the collecting has been stopped by such a failure.
+///
+/// [`FailurePolicy::fail_fast`]: crate::FailurePolicy::fail_fast
+async fn collect_errors<Err>(
+    results: impl Stream<Item = (Result<(), Err>, bool)>,
+    errors: &mut Vec<Err>,
+) -> bool {
+    futures::pin_mut!(results);
+    while let Some((res, fails_fast)) = results.next().await {
+        if let Err(e) = res {
+            errors.push(e);
+            if fails_fast {
+                return true;
+            }
+        }
     }
+    false
 }
 
-#[async_trait(?Send)]
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
 impl<Snp, Impl, Mt, Cmd> CommandBus<Cmd> for Static<Snp, ContextWithMeta<Impl, Mt>>
 where
     Snp: SnapshotStrategy,
@@ -432,7 +794,11 @@ where
     Impl: SnapshotSource<Cmd::Aggregate>
         + EventSource<Cmd::Aggregate, CommandHandlerEvent<Cmd>>
         + EventSink<Cmd::Aggregate, CommandHandlerEvent<Cmd>, Mt>
-        + SnapshotSink<Cmd::Aggregate>,
+        + SnapshotSink<Cmd::Aggregate>
+        + MaybeSend,
+    EventSinkErr<Impl, Cmd, Mt>: AsVersionConflict,
+    SnapshotSinkErr<Impl, Cmd>: fmt::Debug,
+    Mt: PerEventMeta<<Cmd::Aggregate as Aggregate>::Id> + MaybeSend + MaybeSync + 'static,
     Self: AsRef<CommandHandlerContext<Cmd>>,
 {
     type Err = LoadExecAndPersistError<
@@ -472,7 +838,6 @@ impl<'a, Snp, Impl, Mt, Cmd> AsRef<DynCommandBus<'a, Cmd, Impl, Mt>>
     for Static<Snp, ContextWithMeta<Impl, Mt>>
 where
     Snp: SnapshotStrategy + 'a,
-    Mt: 'a,
     Cmd: Command,
     Cmd::Aggregate: CommandHandler<Cmd> + EventSourced<CommandHandlerEvent<Cmd>>,
     CommandHandlerEvent<Cmd>: 'static,
@@ -481,7 +846,11 @@ where
         + EventSource<Cmd::Aggregate, CommandHandlerEvent<Cmd>>
         + EventSink<Cmd::Aggregate, CommandHandlerEvent<Cmd>, Mt>
         + SnapshotSink<Cmd::Aggregate>
+        + MaybeSend
         + 'a,
+    EventSinkErr<Impl, Cmd, Mt>: AsVersionConflict,
+    SnapshotSinkErr<Impl, Cmd>: fmt::Debug,
+    Mt: PerEventMeta<<Cmd::Aggregate as Aggregate>::Id> + MaybeSend + MaybeSync + 'static,
     Self: AsRef<CommandHandlerContext<Cmd>>,
 {
     #[inline]
@@ -0,0 +1,178 @@
+//! Unit of work spanning multiple persisting operations.
+
+use std::convert::Infallible;
+
+use async_trait::async_trait;
+use cqrs_core::{MaybeSend, MaybeSync};
+use derive_more::{Display, Error};
+
+use super::PersistError;
+
+/// Guarantees given by a [`UnitOfWork`] about its [`Transaction`]s.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Atomicity {
+    /// All the operations performed within a [`Transaction`] are either
+    /// committed or discarded together.
+    ///
+    /// Backends sharing a single database transaction between their event,
+    /// snapshot and outbox storages should provide this guarantee.
+    Atomic,
+
+    /// Operations performed within a [`Transaction`] are committed one by
+    /// one, so a failed operation may leave the previous ones committed, and
+    /// rolling back is a no-op.
+    BestEffort,
+}
+
+/// Factory of [`Transaction`]s grouping persisting operations into a single
+/// unit of work.
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+pub trait UnitOfWork {
+    /// Type of [`Transaction`] provided by this [`UnitOfWork`].
+    ///
+    /// It should give access to the storages (like [`EventSink`] and
+    /// [`SnapshotSink`]) operating within this [`Transaction`] via [`AsRef`].
+    ///
+    /// [`EventSink`]: cqrs_core::EventSink
+    /// [`SnapshotSink`]: cqrs_core::SnapshotSink
+    type Transaction: Transaction<Err = Self::Err>;
+    /// Type of the error if beginning or finishing a [`Transaction`] fails.
+    type Err;
+
+    /// Returns [`Atomicity`] guaranteed by [`Transaction`]s of this
+    /// [`UnitOfWork`].
+    fn atomicity(&self) -> Atomicity;
+
+    /// Begins a new [`Transaction`].
+    async fn begin(&self) -> Result<Self::Transaction, Self::Err>;
+}
+
+/// Transaction of a [`UnitOfWork`].
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+pub trait Transaction: Sized {
+    /// Type of the error if finishing this [`Transaction`] fails.
+    type Err;
+
+    /// Commits all the operations performed within this [`Transaction`].
+    async fn commit(self) -> Result<(), Self::Err>;
+
+    /// Discards all the operations performed within this [`Transaction`], if
+    /// its [`UnitOfWork`] is [`Atomicity::Atomic`].
+    async fn rollback(self) -> Result<(), Self::Err>;
+}
+
+/// [`UnitOfWork`] over storages without transactions support, giving only
+/// [`Atomicity::BestEffort`] guarantees.
+///
+/// Its [`Transaction`]s simply provide access to the wrapped storages, so
+/// every operation is committed as soon as it's performed.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct BestEffort<Repo>(pub Repo);
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl<Repo> UnitOfWork for BestEffort<Repo>
+where
+    Repo: Clone + MaybeSend + MaybeSync,
+{
+    type Transaction = BestEffortTransaction<Repo>;
+    type Err = Infallible;
+
+    #[inline]
+    fn atomicity(&self) -> Atomicity {
+        Atomicity::BestEffort
+    }
+
+    #[inline]
+    async fn begin(&self) -> Result<Self::Transaction, Self::Err> {
+        Ok(BestEffortTransaction(self.0.clone()))
+    }
+}
+
+/// [`Transaction`] of the [`BestEffort`] [`UnitOfWork`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct BestEffortTransaction<Repo>(Repo);
+
+impl<T, Repo> AsRef<T> for BestEffortTransaction<Repo>
+where
+    T: ?Sized,
+    Repo: AsRef<T>,
+{
+    #[inline]
+    fn as_ref(&self) -> &T {
+        self.0.as_ref()
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl<Repo: MaybeSend> Transaction for BestEffortTransaction<Repo> {
+    type Err = Infallible;
+
+    #[inline]
+    async fn commit(self) -> Result<(), Self::Err> {
+        Ok(())
+    }
+
+    #[inline]
+    async fn rollback(self) -> Result<(), Self::Err> {
+        Ok(())
+    }
+}
+
+/// Error of persisting within a [`UnitOfWork`], describing the state the
+/// storages are left in.
+#[derive(Clone, Copy, Debug, Display, Eq, Error, PartialEq)]
+pub enum UnitOfWorkError<TxErr, EvSnkErr, SsSnkErr> {
+    /// Beginning a [`Transaction`] failed, so nothing was persisted.
+    #[display(fmt = "Beginning unit of work failed: {}", _0)]
+    Begin(TxErr),
+
+    /// Persisting failed and the [`Transaction`] was rolled back, so nothing
+    /// was persisted.
+    #[display(fmt = "{}, unit of work is rolled back", _0)]
+    RolledBack(PersistError<EvSnkErr, SsSnkErr>),
+
+    /// Persisting failed within an [`Atomicity::BestEffort`] [`UnitOfWork`],
+    /// so everything persisted before the failure remains committed.
+    ///
+    /// I.e. [`PersistError::Snapshot`] means that the events are persisted.
+    #[display(fmt = "{}, unit of work is partially persisted", _0)]
+    PartiallyPersisted(PersistError<EvSnkErr, SsSnkErr>),
+
+    /// Persisting failed, and rolling back the [`Transaction`] failed too, so
+    /// the state of the storages is unknown.
+    #[display(fmt = "{}, and rolling back unit of work failed: {}", cause, err)]
+    Rollback {
+        /// Error of persisting which caused the rollback.
+        #[error(not(source))]
+        cause: PersistError<EvSnkErr, SsSnkErr>,
+        /// Error of rolling back.
+        err: TxErr,
+    },
+
+    /// Committing the [`Transaction`] failed.
+    ///
+    /// Nothing is persisted, unless the [`UnitOfWork`] is
+    /// [`Atomicity::BestEffort`].
+    #[display(fmt = "Committing unit of work failed: {}", _0)]
+    Commit(TxErr),
+}
+
+impl<EvSnkErr, SsSnkErr> From<UnitOfWorkError<Infallible, EvSnkErr, SsSnkErr>>
+    for PersistError<EvSnkErr, SsSnkErr>
+{
+    /// Extracts the [`PersistError`] of a [`UnitOfWork`] which never fails on
+    /// its own, like the [`BestEffort`] one.
+    #[inline]
+    fn from(err: UnitOfWorkError<Infallible, EvSnkErr, SsSnkErr>) -> Self {
+        match err {
+            UnitOfWorkError::RolledBack(e)
+            | UnitOfWorkError::PartiallyPersisted(e)
+            | UnitOfWorkError::Rollback { cause: e, .. } => e,
+            UnitOfWorkError::Begin(e) | UnitOfWorkError::Commit(e) => match e {},
+        }
+    }
+}
@@ -0,0 +1,605 @@
+//! In-memory storages of [`Event`]s and [`Aggregate`]s' snapshots.
+//!
+//! Intended for tests and prototyping, as nothing is persisted across process
+//! restarts.
+//!
+//! [`Event`]: cqrs_core::Event
+
+use std::{
+    collections::{HashMap, VecDeque},
+    convert::{Infallible, TryFrom as _},
+    fmt,
+    hash::Hash,
+    num::NonZeroUsize,
+    sync::{Mutex, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
+    task::{Poll, Waker},
+    time::SystemTime,
+};
+
+use async_trait::async_trait;
+use cqrs_core::{
+    Aggregate, AggregateType, BoxTryStream, Event, EventFilter, EventNumber, EventSink,
+    EventSource, EventSourceWithMeta, EventSourced, GlobalEvent, GlobalPosition, MaybeSend,
+    MaybeSync, NumberedEvent, NumberedEventWithMeta, PerEventMeta, ReadDirection, ReadRange, Since,
+    SnapshotRetention, SnapshotSink, SnapshotSource, SnapshotVersion, TypedGlobalEventSource,
+    Version, VersionConflict,
+};
+use futures::stream;
+
+/// [`Event`] stored in the [`EventStore`] along with its [`Aggregate`]'s ID
+/// and metadata.
+///
+/// [`Event`]: cqrs_core::Event
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StoredEvent<Id, Ev, Mt> {
+    /// Position of the [`Event`] in the global order of all the [`Event`]s
+    /// stored in the [`EventStore`], starting from `1`.
+    ///
+    /// [`Event`]: cqrs_core::Event
+    pub position: u64,
+
+    /// ID of the [`Aggregate`] the [`Event`] belongs to.
+    ///
+    /// [`Event`]: cqrs_core::Event
+    pub id: Id,
+
+    /// The [`Event`] itself, numbered within its [`Aggregate`]'s stream.
+    ///
+    /// [`Event`]: cqrs_core::Event
+    pub event: NumberedEvent<Ev>,
+
+    /// Metadata the [`Event`] was appended with.
+    ///
+    /// [`Event`]: cqrs_core::Event
+    pub meta: Mt,
+}
+
+/// Contents of the [`EventStore`], guarded by a single lock.
+struct Log<Id, Ev, Mt> {
+    /// All the stored [`Event`]s in their global order.
+    ///
+    /// [`Event`]: cqrs_core::Event
+    events: Vec<StoredEvent<Id, Ev, Mt>>,
+
+    /// Indices of the [`Aggregate`]s' [`Event`]s in the `events`.
+    ///
+    /// [`Event`]: cqrs_core::Event
+    streams: HashMap<Id, Vec<usize>>,
+}
+
+/// In-memory [`EventSource`] and [`EventSink`] of a single [`Aggregate`] type.
+///
+/// Appending checks the expected [`Version`] of the [`Aggregate`] atomically,
+/// and assigns to every [`Event`] both its [`EventNumber`] within the
+/// [`Aggregate`]'s stream and its position in the global order of all the
+/// stored [`Event`]s, along with the metadata individualized for it.
+///
+/// Implements [`TypedGlobalEventSource`] too, reading the streams of its single
+/// [`Aggregate`] type, where the position of an [`Event`] in the global order
+/// is its [`GlobalPosition`].
+///
+/// [`Event`]: cqrs_core::Event
+/// [`EventNumber`]: cqrs_core::EventNumber
+pub struct EventStore<Agg: Aggregate, Ev, Mt = ()> {
+    log: RwLock<Log<Agg::Id, Ev, Mt>>,
+
+    /// [`Waker`]s of the subscriptions waiting for new [`Event`]s to be
+    /// appended.
+    ///
+    /// [`Event`]: cqrs_core::Event
+    subscribers: Mutex<Vec<Waker>>,
+}
+
+impl<Agg: Aggregate, Ev, Mt> Default for EventStore<Agg, Ev, Mt> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            log: RwLock::new(Log {
+                events: vec![],
+                streams: HashMap::new(),
+            }),
+            subscribers: Mutex::new(vec![]),
+        }
+    }
+}
+
+impl<Agg: Aggregate, Ev, Mt> fmt::Debug for EventStore<Agg, Ev, Mt> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventStore")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl<Agg: Aggregate, Ev, Mt> AsRef<Self> for EventStore<Agg, Ev, Mt> {
+    #[inline(always)]
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+impl<Agg: Aggregate, Ev, Mt> EventStore<Agg, Ev, Mt> {
+    /// Creates a new empty [`EventStore`].
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the total number of [`Event`]s stored in this [`EventStore`].
+    ///
+    /// [`Event`]: cqrs_core::Event
+    pub fn len(&self) -> usize {
+        self.read_log().events.len()
+    }
+
+    /// Indicates whether this [`EventStore`] has no [`Event`]s stored.
+    ///
+    /// [`Event`]: cqrs_core::Event
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns all the [`Event`]s stored in this [`EventStore`] after the given
+    /// global position, in their global order.
+    ///
+    /// [`Event`]: cqrs_core::Event
+    pub fn read_all(&self, after: u64) -> Vec<StoredEvent<Agg::Id, Ev, Mt>>
+    where
+        Agg::Id: Clone,
+        Ev: Clone,
+        Mt: Clone,
+    {
+        let after = usize::try_from(after).unwrap_or(usize::MAX);
+        self.read_log().events.iter().skip(after).cloned().collect()
+    }
+
+    fn read_log(&self) -> RwLockReadGuard<'_, Log<Agg::Id, Ev, Mt>> {
+        self.log.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write_log(&self) -> RwLockWriteGuard<'_, Log<Agg::Id, Ev, Mt>> {
+        self.log.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Wakes all the subscriptions waiting for new [`Event`]s.
+    ///
+    /// [`Event`]: cqrs_core::Event
+    fn notify_subscribers(&self) {
+        let wakers = std::mem::take(
+            &mut *self
+                .subscribers
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner),
+        );
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+}
+
+impl<Id, Ev, Mt> Log<Id, Ev, Mt>
+where
+    Id: Eq + Hash,
+{
+    /// Returns the stored [`Event`]s of the given [`Aggregate`]'s stream after
+    /// the given [`Since`] point.
+    ///
+    /// [`Event`]: cqrs_core::Event
+    fn stored(&self, id: &Id, since: Since) -> impl Iterator<Item = &StoredEvent<Id, Ev, Mt>> {
+        let skip = match since {
+            Since::BeginningOfStream => 0,
+            Since::Event(num) => usize::try_from(num).unwrap_or(usize::MAX),
+        };
+        self.streams
+            .get(id)
+            .into_iter()
+            .flatten()
+            .skip(skip)
+            .map(move |i| &self.events[*i])
+    }
+
+    /// Returns the stored [`Event`]s of the given [`Aggregate`]'s stream
+    /// within the given [`ReadRange`], in its [`ReadDirection`].
+    ///
+    /// As [`EventNumber`]s of a stream are sequential, the range is sliced out
+    /// of the stream directly, so only the returned [`Event`]s are visited.
+    ///
+    /// [`Event`]: cqrs_core::Event
+    fn stored_range(&self, id: &Id, range: ReadRange) -> Vec<&StoredEvent<Id, Ev, Mt>> {
+        let stream = self.streams.get(id).map_or(&[][..], Vec::as_slice);
+        let to_index = |num: EventNumber| usize::try_from(num).unwrap_or(usize::MAX);
+        let start = match range.since {
+            Since::BeginningOfStream => 0,
+            Since::Event(num) => to_index(num),
+        }
+        .min(stream.len());
+        let end = range
+            .until
+            .map_or(stream.len(), to_index)
+            .clamp(start, stream.len());
+        let stream = stream[start..end].iter();
+        let limit = range.limit.unwrap_or(usize::MAX);
+        match range.direction {
+            ReadDirection::Forward => stream.take(limit).map(|i| &self.events[*i]).collect(),
+            ReadDirection::Backward => stream.rev().take(limit).map(|i| &self.events[*i]).collect(),
+        }
+    }
+
+    /// Returns the [`Event`]s of all the streams after the given
+    /// [`GlobalPosition`], which match the given [`EventFilter`].
+    ///
+    /// [`Event`]: cqrs_core::Event
+    fn global<'a>(
+        &'a self,
+        after: GlobalPosition,
+        filter: &'a EventFilter,
+        aggregate_type: AggregateType,
+    ) -> impl Iterator<Item = GlobalEvent<Id, Ev, Mt>> + 'a
+    where
+        Id: Clone,
+        Ev: Event + Clone,
+        Mt: Clone,
+    {
+        let skip = usize::try_from(after.get()).unwrap_or(usize::MAX);
+        self.events
+            .iter()
+            .skip(skip)
+            .filter(move |stored| filter.matches(aggregate_type, stored.event.data.event_type()))
+            .map(move |stored| GlobalEvent {
+                position: GlobalPosition::new(stored.position),
+                aggregate_type,
+                id: stored.id.clone(),
+                event: stored.event.clone(),
+                meta: stored.meta.clone(),
+            })
+    }
+
+    /// Returns the [`Event`]s of the given [`Aggregate`]'s stream after the
+    /// given [`Since`] point.
+    ///
+    /// [`Event`]: cqrs_core::Event
+    fn stream(&self, id: &Id, since: Since) -> impl Iterator<Item = NumberedEvent<Ev>> + '_
+    where
+        Ev: Clone,
+    {
+        self.stored(id, since).map(|stored| stored.event.clone())
+    }
+}
+
+impl<Agg, Ev, Mt> EventSource<Agg, Ev> for EventStore<Agg, Ev, Mt>
+where
+    Agg: Aggregate + EventSourced<Ev>,
+    Agg::Id: Eq + Hash,
+    Ev: Clone + MaybeSend + MaybeSync,
+    Mt: MaybeSend + MaybeSync,
+{
+    type Err = Infallible;
+
+    fn read_events(
+        &self,
+        id: &Agg::Id,
+        since: Since,
+    ) -> BoxTryStream<'_, NumberedEvent<Ev>, Self::Err> {
+        let events = self
+            .read_log()
+            .stream(id, since)
+            .map(Ok)
+            .collect::<Vec<_>>();
+        Box::pin(stream::iter(events))
+    }
+
+    /// Reads [`Event`]s of all the given [`Aggregate`]s under a single lock,
+    /// so they're consistent with each other.
+    ///
+    /// [`Event`]: cqrs_core::Event
+    fn read_events_batch<'a>(
+        &'a self,
+        reqs: &'a [(&'a Agg::Id, Since)],
+        _: NonZeroUsize,
+    ) -> BoxTryStream<'a, (usize, NumberedEvent<Ev>), Self::Err> {
+        let log = self.read_log();
+        let events = reqs
+            .iter()
+            .enumerate()
+            .flat_map(|(i, (id, since))| log.stream(id, *since).map(move |ev| Ok((i, ev))))
+            .collect::<Vec<_>>();
+        Box::pin(stream::iter(events))
+    }
+
+    /// Reads [`Event`]s within the given [`ReadRange`] without buffering
+    /// the [`Event`]s beyond it.
+    ///
+    /// [`Event`]: cqrs_core::Event
+    fn read_events_range<'a>(
+        &'a self,
+        id: &Agg::Id,
+        range: ReadRange,
+    ) -> BoxTryStream<'a, NumberedEvent<Ev>, Self::Err>
+    where
+        Ev: MaybeSend + 'a,
+        Self::Err: 'a,
+    {
+        let events = self
+            .read_log()
+            .stored_range(id, range)
+            .into_iter()
+            .map(|stored| Ok(stored.event.clone()))
+            .collect::<Vec<_>>();
+        Box::pin(stream::iter(events))
+    }
+}
+
+impl<Agg, Ev, Mt> EventSourceWithMeta<Agg, Ev, Mt> for EventStore<Agg, Ev, Mt>
+where
+    Agg: Aggregate + EventSourced<Ev>,
+    Agg::Id: Eq + Hash,
+    Ev: Clone + MaybeSend + MaybeSync,
+    Mt: Clone + MaybeSend + MaybeSync,
+{
+    fn read_events_with_meta(
+        &self,
+        id: &Agg::Id,
+        since: Since,
+    ) -> BoxTryStream<'_, NumberedEventWithMeta<Ev, Mt>, Self::Err> {
+        let events = self
+            .read_log()
+            .stored(id, since)
+            .map(|stored| {
+                Ok(NumberedEventWithMeta {
+                    num: stored.event.num,
+                    data: stored.event.data.clone(),
+                    meta: stored.meta.clone(),
+                })
+            })
+            .collect::<Vec<_>>();
+        Box::pin(stream::iter(events))
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl<Agg, Ev, Mt> EventSink<Agg, Ev, Mt> for EventStore<Agg, Ev, Mt>
+where
+    Agg: Aggregate,
+    Agg::Id: Clone + Eq + Hash,
+    Ev: Clone + MaybeSend + MaybeSync,
+    Mt: PerEventMeta<Agg::Id> + MaybeSend + MaybeSync,
+{
+    type Err = VersionConflict;
+    type Ok = Vec<NumberedEvent<Ev>>;
+
+    async fn append_events(
+        &self,
+        id: &Agg::Id,
+        expected_ver: Version,
+        events: &[NumberedEvent<Ev>],
+        meta: &Mt,
+    ) -> Result<Self::Ok, Self::Err> {
+        let mut log = self.write_log();
+        let Log {
+            events: stored,
+            streams,
+        } = &mut *log;
+
+        let stream = streams.entry(id.clone()).or_default();
+        let mut ver = Version::new(stream.len() as u64);
+        if ver != expected_ver {
+            return Err(VersionConflict {
+                expected: expected_ver,
+                actual: ver,
+            });
+        }
+
+        let appended = events
+            .iter()
+            .map(|ev| {
+                ver.incr();
+                let event = NumberedEvent {
+                    num: ver.event_number().unwrap(),
+                    data: ev.data.clone(),
+                };
+                stream.push(stored.len());
+                stored.push(StoredEvent {
+                    position: stored.len() as u64 + 1,
+                    id: id.clone(),
+                    event: event.clone(),
+                    meta: meta.for_event(id, event.num),
+                });
+                event
+            })
+            .collect();
+        drop(log);
+
+        self.notify_subscribers();
+        Ok(appended)
+    }
+}
+
+impl<Agg, Ev, Mt> TypedGlobalEventSource<Agg::Id, Ev, Mt> for EventStore<Agg, Ev, Mt>
+where
+    Agg: Aggregate,
+    Agg::Id: Clone + Eq + Hash,
+    Ev: Event + Clone + MaybeSend + MaybeSync,
+    Mt: Clone + MaybeSend + MaybeSync,
+{
+    type Err = Infallible;
+
+    fn read_all_events(
+        &self,
+        after: GlobalPosition,
+        filter: EventFilter,
+    ) -> BoxTryStream<'_, GlobalEvent<Agg::Id, Ev, Mt>, Self::Err> {
+        let aggregate_type = Agg::default().aggregate_type();
+        let events = self
+            .read_log()
+            .global(after, &filter, aggregate_type)
+            .map(Ok)
+            .collect::<Vec<_>>();
+        Box::pin(stream::iter(events))
+    }
+
+    /// Subscribes to the [`Event`]s, reading them in batches under a single
+    /// lock, and waiting for new ones to be appended once all the stored
+    /// [`Event`]s are read.
+    ///
+    /// [`Event`]: cqrs_core::Event
+    fn subscribe_all_events(
+        &self,
+        mut after: GlobalPosition,
+        filter: EventFilter,
+    ) -> BoxTryStream<'_, GlobalEvent<Agg::Id, Ev, Mt>, Self::Err> {
+        let aggregate_type = Agg::default().aggregate_type();
+        let mut pending = VecDeque::new();
+        Box::pin(stream::poll_fn(move |cx| {
+            if pending.is_empty() {
+                let log = self.read_log();
+                pending.extend(log.global(after, &filter, aggregate_type));
+                after = GlobalPosition::new(log.events.len() as u64);
+                if pending.is_empty() {
+                    // Registering while still holding the lock guarantees no
+                    // appended events are missed, as appending requires
+                    // the exclusive lock.
+                    let mut subscribers = self
+                        .subscribers
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner);
+                    if !subscribers.iter().any(|w| w.will_wake(cx.waker())) {
+                        subscribers.push(cx.waker().clone());
+                    }
+                    return Poll::Pending;
+                }
+            }
+            Poll::Ready(pending.pop_front().map(Ok))
+        }))
+    }
+}
+
+/// In-memory [`SnapshotSource`] and [`SnapshotSink`] of a single [`Aggregate`]
+/// type, keeping only the latest snapshot of every [`Aggregate`].
+///
+/// Snapshots are stored along with the [`Aggregate::SNAPSHOT_VERSION`] they're
+/// persisted with, and the moment of persisting.
+pub struct StateStore<Agg: Aggregate> {
+    snapshots: RwLock<HashMap<Agg::Id, StoredSnapshot<Agg>>>,
+}
+
+/// Snapshot stored in a [`StateStore`] along with its [`Version`],
+/// [`SnapshotVersion`] and the moment of persisting.
+type StoredSnapshot<Agg> = (Agg, Version, SnapshotVersion, SystemTime);
+
+impl<Agg: Aggregate> Default for StateStore<Agg> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            snapshots: RwLock::new(HashMap::new()),
+        }
+    }
+}
+
+impl<Agg: Aggregate> fmt::Debug for StateStore<Agg> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let len = self
+            .snapshots
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len();
+        f.debug_struct("StateStore").field("len", &len).finish()
+    }
+}
+
+impl<Agg: Aggregate> AsRef<Self> for StateStore<Agg> {
+    #[inline(always)]
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+impl<Agg: Aggregate> StateStore<Agg> {
+    /// Creates a new empty [`StateStore`].
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl<Agg> SnapshotSource<Agg> for StateStore<Agg>
+where
+    Agg: Aggregate + Clone,
+    Agg::Id: Eq + Hash,
+{
+    type Err = Infallible;
+
+    async fn load_snapshots(&self, ids: &[Agg::Id]) -> Result<Vec<StoredSnapshot<Agg>>, Self::Err> {
+        let snapshots = self
+            .snapshots
+            .read()
+            .unwrap_or_else(PoisonError::into_inner);
+        Ok(ids
+            .iter()
+            .filter_map(|id| snapshots.get(id).cloned())
+            .collect())
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl<Agg> SnapshotSink<Agg> for StateStore<Agg>
+where
+    Agg: Aggregate + Clone,
+    Agg::Id: Clone + Eq + Hash,
+{
+    type Err = Infallible;
+
+    /// Persists the given snapshots, unless newer ones of the same format are
+    /// stored already.
+    async fn persist_snapshots(&self, aggs: &[(&Agg, Version)]) -> Result<(), Self::Err> {
+        let mut snapshots = self
+            .snapshots
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        let now = SystemTime::now();
+        for (agg, ver) in aggs {
+            let snapshot = || ((*agg).clone(), *ver, Agg::SNAPSHOT_VERSION, now);
+            let _ = snapshots
+                .entry(agg.id().clone())
+                .and_modify(|stored| {
+                    if stored.1 < *ver || stored.2 != Agg::SNAPSHOT_VERSION {
+                        *stored = snapshot();
+                    }
+                })
+                .or_insert_with(snapshot);
+        }
+        Ok(())
+    }
+
+    /// Removes the stored snapshots older than required by
+    /// [`SnapshotRetention::KeepSince`].
+    ///
+    /// [`SnapshotRetention::KeepAll`] and [`SnapshotRetention::KeepLatest`]
+    /// remove nothing, as only the latest snapshot of every [`Aggregate`] is
+    /// stored, and both of them retain it.
+    async fn prune_snapshots(
+        &self,
+        ids: &[&Agg::Id],
+        retention: SnapshotRetention,
+    ) -> Result<(), Self::Err> {
+        let since = match retention {
+            SnapshotRetention::KeepAll => return Ok(()),
+            // At least one latest snapshot is kept, which is the only one.
+            SnapshotRetention::KeepLatest(_) => return Ok(()),
+            SnapshotRetention::KeepSince(ver) => ver,
+        };
+        let mut snapshots = self
+            .snapshots
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        for id in ids {
+            if matches!(snapshots.get(*id), Some((_, ver, ..)) if *ver < since) {
+                let _ = snapshots.remove(*id);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,300 @@
+//! Repository combining separate storages of [`Event`]s and snapshots.
+//!
+//! [`Event`]: cqrs_core::Event
+
+use std::{num::NonZeroUsize, time::SystemTime};
+
+use async_trait::async_trait;
+use cqrs_core::{
+    Aggregate, BoxTryStream, EventFilter, EventSink, EventSource, EventSourceWithMeta,
+    EventSourced, GlobalEvent, GlobalPosition, MaybeSend, MaybeSync, NumberedEvent,
+    NumberedEventWithMeta, ReadRange, Since, SnapshotRetention, SnapshotSink, SnapshotSource,
+    SnapshotVersion, TypedGlobalEventSource, Version,
+};
+
+use crate::lifecycle::BorrowableAsContext;
+
+/// Repository of [`Aggregate`]s combining an [`Event`] store and a snapshot
+/// store of different types.
+///
+/// Implements [`EventSource`], [`TypedGlobalEventSource`] and [`EventSink`] by
+/// delegating to its [`Event`] store, and [`SnapshotSource`] and
+/// [`SnapshotSink`] by delegating to its snapshot store, so it can be used as
+/// a single repository by [`Basic`] and [`Static`] lifecycles, and as an
+/// implementation of a [`Context`].
+///
+/// [`Basic`]: crate::lifecycle::Basic
+/// [`Context`]: crate::lifecycle::Context
+/// [`Event`]: cqrs_core::Event
+/// [`Static`]: crate::lifecycle::Static
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Repository<EvStore, SsStore> {
+    event_store: EvStore,
+    snapshot_store: SsStore,
+}
+
+impl<EvStore, SsStore> Repository<EvStore, SsStore> {
+    /// Creates a new [`Repository`] out of the given [`Event`] store and
+    /// snapshot store.
+    ///
+    /// [`Event`]: cqrs_core::Event
+    #[inline]
+    pub fn new(event_store: EvStore, snapshot_store: SsStore) -> Self {
+        Self {
+            event_store,
+            snapshot_store,
+        }
+    }
+
+    /// Replaces the [`Event`] store of this [`Repository`] with the given one.
+    ///
+    /// [`Event`]: cqrs_core::Event
+    #[inline]
+    pub fn with_event_store<NewEvStore>(
+        self,
+        event_store: NewEvStore,
+    ) -> Repository<NewEvStore, SsStore> {
+        Repository {
+            event_store,
+            snapshot_store: self.snapshot_store,
+        }
+    }
+
+    /// Replaces the snapshot store of this [`Repository`] with the given one.
+    #[inline]
+    pub fn with_snapshot_store<NewSsStore>(
+        self,
+        snapshot_store: NewSsStore,
+    ) -> Repository<EvStore, NewSsStore> {
+        Repository {
+            event_store: self.event_store,
+            snapshot_store,
+        }
+    }
+
+    /// Returns the [`Event`] store of this [`Repository`].
+    ///
+    /// [`Event`]: cqrs_core::Event
+    #[inline]
+    pub fn event_store(&self) -> &EvStore {
+        &self.event_store
+    }
+
+    /// Returns the snapshot store of this [`Repository`].
+    #[inline]
+    pub fn snapshot_store(&self) -> &SsStore {
+        &self.snapshot_store
+    }
+
+    /// Splits this [`Repository`] into its [`Event`] store and snapshot store.
+    ///
+    /// [`Event`]: cqrs_core::Event
+    #[inline]
+    pub fn into_parts(self) -> (EvStore, SsStore) {
+        (self.event_store, self.snapshot_store)
+    }
+}
+
+impl<EvStore, SsStore> AsRef<Self> for Repository<EvStore, SsStore> {
+    #[inline(always)]
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+impl<EvStore, SsStore> BorrowableAsContext for Repository<EvStore, SsStore> {}
+
+impl<Agg, Ev, EvStore, SsStore> EventSource<Agg, Ev> for Repository<EvStore, SsStore>
+where
+    Agg: Aggregate + EventSourced<Ev>,
+    EvStore: EventSource<Agg, Ev>,
+    SsStore: MaybeSync,
+{
+    type Err = EvStore::Err;
+
+    #[inline]
+    fn read_events(
+        &self,
+        id: &Agg::Id,
+        since: Since,
+    ) -> BoxTryStream<'_, NumberedEvent<Ev>, Self::Err> {
+        self.event_store.read_events(id, since)
+    }
+
+    #[inline]
+    fn read_events_batch<'a>(
+        &'a self,
+        reqs: &'a [(&'a Agg::Id, Since)],
+        concurrency_limit: NonZeroUsize,
+    ) -> BoxTryStream<'a, (usize, NumberedEvent<Ev>), Self::Err> {
+        self.event_store.read_events_batch(reqs, concurrency_limit)
+    }
+
+    #[inline]
+    fn read_events_range<'a>(
+        &'a self,
+        id: &Agg::Id,
+        range: ReadRange,
+    ) -> BoxTryStream<'a, NumberedEvent<Ev>, Self::Err>
+    where
+        Ev: MaybeSend + 'a,
+        Self::Err: 'a,
+    {
+        self.event_store.read_events_range(id, range)
+    }
+}
+
+impl<Agg, Ev, Mt, EvStore, SsStore> EventSourceWithMeta<Agg, Ev, Mt>
+    for Repository<EvStore, SsStore>
+where
+    Agg: Aggregate + EventSourced<Ev>,
+    EvStore: EventSourceWithMeta<Agg, Ev, Mt>,
+    SsStore: MaybeSync,
+{
+    #[inline]
+    fn read_events_with_meta(
+        &self,
+        id: &Agg::Id,
+        since: Since,
+    ) -> BoxTryStream<'_, NumberedEventWithMeta<Ev, Mt>, Self::Err> {
+        self.event_store.read_events_with_meta(id, since)
+    }
+}
+
+impl<Id, Ev, Mt, EvStore, SsStore> TypedGlobalEventSource<Id, Ev, Mt>
+    for Repository<EvStore, SsStore>
+where
+    EvStore: TypedGlobalEventSource<Id, Ev, Mt>,
+    SsStore: MaybeSync,
+{
+    type Err = EvStore::Err;
+
+    #[inline]
+    fn read_all_events(
+        &self,
+        after: GlobalPosition,
+        filter: EventFilter,
+    ) -> BoxTryStream<'_, GlobalEvent<Id, Ev, Mt>, Self::Err> {
+        self.event_store.read_all_events(after, filter)
+    }
+
+    #[inline]
+    fn subscribe_all_events(
+        &self,
+        after: GlobalPosition,
+        filter: EventFilter,
+    ) -> BoxTryStream<'_, GlobalEvent<Id, Ev, Mt>, Self::Err> {
+        self.event_store.subscribe_all_events(after, filter)
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl<Agg, Ev, Mt, EvStore, SsStore> EventSink<Agg, Ev, Mt> for Repository<EvStore, SsStore>
+where
+    Agg: Aggregate,
+    Ev: MaybeSync,
+    Mt: MaybeSync + ?Sized,
+    EvStore: EventSink<Agg, Ev, Mt>,
+    SsStore: MaybeSync,
+{
+    type Err = EvStore::Err;
+    type Ok = EvStore::Ok;
+
+    #[inline]
+    async fn append_events(
+        &self,
+        id: &Agg::Id,
+        expected_ver: Version,
+        events: &[NumberedEvent<Ev>],
+        meta: &Mt,
+    ) -> Result<Self::Ok, Self::Err> {
+        self.event_store
+            .append_events(id, expected_ver, events, meta)
+            .await
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl<Agg, EvStore, SsStore> SnapshotSource<Agg> for Repository<EvStore, SsStore>
+where
+    Agg: Aggregate,
+    EvStore: MaybeSync,
+    SsStore: SnapshotSource<Agg>,
+{
+    type Err = SsStore::Err;
+
+    #[inline]
+    async fn load_snapshot(
+        &self,
+        id: &Agg::Id,
+    ) -> Result<Option<(Agg, Version, SnapshotVersion, SystemTime)>, Self::Err>
+    where
+        Agg: 'async_trait,
+    {
+        self.snapshot_store.load_snapshot(id).await
+    }
+
+    #[inline]
+    async fn load_snapshots(
+        &self,
+        ids: &[Agg::Id],
+    ) -> Result<Vec<(Agg, Version, SnapshotVersion, SystemTime)>, Self::Err> {
+        self.snapshot_store.load_snapshots(ids).await
+    }
+
+    #[inline]
+    async fn load_snapshot_at(
+        &self,
+        id: &Agg::Id,
+        ver: Version,
+    ) -> Result<Option<(Agg, Version, SnapshotVersion, SystemTime)>, Self::Err>
+    where
+        Agg: 'async_trait,
+    {
+        self.snapshot_store.load_snapshot_at(id, ver).await
+    }
+
+    #[inline]
+    async fn load_snapshot_taken_until(
+        &self,
+        id: &Agg::Id,
+        at: SystemTime,
+    ) -> Result<Option<(Agg, Version, SnapshotVersion, SystemTime)>, Self::Err>
+    where
+        Agg: 'async_trait,
+    {
+        self.snapshot_store.load_snapshot_taken_until(id, at).await
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl<Agg, EvStore, SsStore> SnapshotSink<Agg> for Repository<EvStore, SsStore>
+where
+    Agg: Aggregate,
+    EvStore: MaybeSync,
+    SsStore: SnapshotSink<Agg>,
+{
+    type Err = SsStore::Err;
+
+    #[inline]
+    async fn persist_snapshot(&self, agg: &Agg, ver: Version) -> Result<(), Self::Err> {
+        self.snapshot_store.persist_snapshot(agg, ver).await
+    }
+
+    #[inline]
+    async fn persist_snapshots(&self, aggs: &[(&Agg, Version)]) -> Result<(), Self::Err> {
+        self.snapshot_store.persist_snapshots(aggs).await
+    }
+
+    #[inline]
+    async fn prune_snapshots(
+        &self,
+        ids: &[&Agg::Id],
+        retention: SnapshotRetention,
+    ) -> Result<(), Self::Err> {
+        self.snapshot_store.prune_snapshots(ids, retention).await
+    }
+}
@@ -0,0 +1,190 @@
+//! Retrying of [`Command`]s dispatching.
+
+use std::{future::Future, num::NonZeroUsize};
+
+use async_trait::async_trait;
+use cqrs_core::{Command, MaybeSend, MaybeSync};
+
+use crate::{lifecycle::LoadExecAndPersistError, CommandBus};
+
+/// Waiting strategy between retry attempts of [`RetryingCommandBus`].
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+pub trait Backoff {
+    /// Waits before performing the given retry `attempt` (starting from `1`).
+    async fn wait(&self, attempt: usize);
+}
+
+/// Retries immediately without any waiting.
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl Backoff for () {
+    #[inline]
+    async fn wait(&self, _: usize) {}
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl<F, Fut> Backoff for F
+where
+    F: Fn(usize) -> Fut + MaybeSync,
+    Fut: Future<Output = ()> + MaybeSend,
+{
+    #[inline]
+    async fn wait(&self, attempt: usize) {
+        (self)(attempt).await
+    }
+}
+
+/// Condition deciding which errors of a [`CommandBus`] should be retried.
+pub trait RetryCondition<Err: ?Sized> {
+    /// Indicates whether the failed dispatching should be retried.
+    fn should_retry(&self, err: &Err) -> bool;
+}
+
+impl<F, Err> RetryCondition<Err> for F
+where
+    F: Fn(&Err) -> bool,
+    Err: ?Sized,
+{
+    #[inline]
+    fn should_retry(&self, err: &Err) -> bool {
+        (self)(err)
+    }
+}
+
+/// [`RetryCondition`] retrying only on a lost optimistic concurrency race
+/// (`VersionConflict`).
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct OnVersionConflict;
+
+impl<Agg, CmdErr, SsSrcErr, EvSrcErr, EvSnkErr, SsSnkErr>
+    RetryCondition<LoadExecAndPersistError<Agg, CmdErr, SsSrcErr, EvSrcErr, EvSnkErr, SsSnkErr>>
+    for OnVersionConflict
+{
+    #[inline]
+    fn should_retry(
+        &self,
+        err: &LoadExecAndPersistError<Agg, CmdErr, SsSrcErr, EvSrcErr, EvSnkErr, SsSnkErr>,
+    ) -> bool {
+        matches!(err, LoadExecAndPersistError::VersionConflict { .. })
+    }
+}
+
+/// Policy of retrying [`Command`]s dispatching by [`RetryingCommandBus`].
+#[derive(Clone, Copy, Debug)]
+pub struct RetryPolicy<B = (), R = OnVersionConflict> {
+    max_attempts: NonZeroUsize,
+    backoff: B,
+    condition: R,
+}
+
+impl RetryPolicy {
+    /// Creates new [`RetryPolicy`] performing at most `max_attempts` attempts
+    /// (including the first one) without any [`Backoff`], and retrying
+    /// [`OnVersionConflict`] only.
+    #[inline]
+    pub fn new(max_attempts: NonZeroUsize) -> Self {
+        Self {
+            max_attempts,
+            backoff: (),
+            condition: OnVersionConflict,
+        }
+    }
+}
+
+impl<B, R> RetryPolicy<B, R> {
+    /// Sets the [`Backoff`] to wait with between retry attempts.
+    #[inline]
+    pub fn with_backoff<NewB: Backoff>(self, backoff: NewB) -> RetryPolicy<NewB, R> {
+        RetryPolicy {
+            max_attempts: self.max_attempts,
+            backoff,
+            condition: self.condition,
+        }
+    }
+
+    /// Sets the [`RetryCondition`] deciding which errors are retryable.
+    #[inline]
+    pub fn retry_if<NewR>(self, condition: NewR) -> RetryPolicy<B, NewR> {
+        RetryPolicy {
+            max_attempts: self.max_attempts,
+            backoff: self.backoff,
+            condition,
+        }
+    }
+
+    /// Returns the maximum number of dispatching attempts (including the first
+    /// one).
+    #[inline]
+    pub fn max_attempts(&self) -> NonZeroUsize {
+        self.max_attempts
+    }
+}
+
+/// [`CommandBus`] wrapper retrying failed [`Command`]s dispatching according
+/// to its [`RetryPolicy`].
+///
+/// Every retry attempt dispatches the [`Command`] anew, so the [`Aggregate`] is
+/// reloaded and the [`CommandHandler`] is re-run on its fresh state.
+///
+/// [`Command`]s with an explicit [`Command::expected_version`] are never
+/// retried, as reloading the [`Aggregate`] cannot satisfy them.
+///
+/// [`Aggregate`]: cqrs_core::Aggregate
+/// [`CommandHandler`]: cqrs_core::CommandHandler
+#[derive(Clone, Copy, Debug)]
+pub struct RetryingCommandBus<Bus, B = (), R = OnVersionConflict> {
+    bus: Bus,
+    policy: RetryPolicy<B, R>,
+}
+
+impl<Bus, B, R> RetryingCommandBus<Bus, B, R> {
+    /// Wraps the given [`CommandBus`] to retry with the given [`RetryPolicy`].
+    #[inline]
+    pub fn new(bus: Bus, policy: RetryPolicy<B, R>) -> Self {
+        Self { bus, policy }
+    }
+
+    /// Returns the wrapped [`CommandBus`].
+    #[inline]
+    pub fn inner(&self) -> &Bus {
+        &self.bus
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl<Cmd, Bus, B, R> CommandBus<Cmd> for RetryingCommandBus<Bus, B, R>
+where
+    Cmd: Command + Clone,
+    Bus: CommandBus<Cmd> + MaybeSync,
+    Bus::Err: MaybeSend,
+    Bus::Ok: MaybeSend,
+    B: Backoff + MaybeSync,
+    R: RetryCondition<Bus::Err> + MaybeSync,
+{
+    type Err = Bus::Err;
+    type Ok = Bus::Ok;
+
+    async fn dispatch(&self, cmd: Cmd) -> Result<Self::Ok, Self::Err>
+    where
+        Cmd: 'async_trait,
+    {
+        let is_retryable = cmd.expected_version().is_none();
+        let mut attempt = 1;
+        loop {
+            match self.bus.dispatch(cmd.clone()).await {
+                Err(e)
+                    if is_retryable
+                        && attempt < self.policy.max_attempts.get()
+                        && self.policy.condition.should_retry(&e) =>
+                {
+                    self.policy.backoff.wait(attempt).await;
+                    attempt += 1;
+                }
+                res => return res,
+            }
+        }
+    }
+}
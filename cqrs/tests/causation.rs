@@ -0,0 +1,237 @@
+use std::{
+    any::TypeId,
+    borrow::Borrow,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use async_trait::async_trait;
+use cqrs::{
+    lifecycle::{BorrowableAsContext, ContextWithMeta, Static},
+    memory::{EventStore, StateStore},
+    Aggregate, AggregateType, DeriveId, Event, EventHandler, EventMeta, EventNumber,
+    EventProcessingConfiguration, EventSourced, EventType, GenerateId, HydratedAggregate,
+    NeverSnapshot, NumberedEvent, PerEventMeta as _, RegisteredEvent, Version,
+};
+use futures::executor::block_on;
+
+/// Test ID generated sequentially.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Id(u64);
+
+impl GenerateId for Id {
+    fn generate_id() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(1);
+        Self(NEXT.fetch_add(1, Ordering::SeqCst))
+    }
+}
+
+impl DeriveId<u8> for Id {
+    fn derive_id(&self, id: &u8, num: EventNumber) -> Self {
+        Self(self.0 * 1_000_000 + u64::from(*id) * 1000 + u128::from(num) as u64)
+    }
+}
+
+/// Test actor initiating the changes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Actor {
+    User,
+}
+
+type Meta = EventMeta<Id, Actor>;
+
+/// Test aggregate counting applied increments.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+struct Counter {
+    id: u8,
+    value: u32,
+}
+
+impl Aggregate for Counter {
+    type Id = u8;
+
+    fn aggregate_type(&self) -> AggregateType {
+        "counter"
+    }
+
+    fn id(&self) -> &Self::Id {
+        &self.id
+    }
+}
+
+/// Test event incrementing the [`Counter`] with the given ID.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+struct Incremented(u8);
+
+impl Event for Incremented {
+    fn event_type(&self) -> EventType {
+        "incremented"
+    }
+}
+
+impl RegisteredEvent for Incremented {
+    fn type_id(&self) -> TypeId {
+        TypeId::of::<Self>()
+    }
+}
+
+impl EventSourced<Incremented> for Counter {
+    fn apply(&mut self, ev: &Incremented) {
+        self.id = ev.0;
+        self.value += 1;
+    }
+}
+
+/// Repository combining in-memory storages of [`Counter`]s.
+#[derive(Debug, Default)]
+struct Repo {
+    events: EventStore<Counter, Incremented, Meta>,
+    states: StateStore<Counter>,
+}
+
+impl Borrow<EventStore<Counter, Incremented, Meta>> for Repo {
+    fn borrow(&self) -> &EventStore<Counter, Incremented, Meta> {
+        &self.events
+    }
+}
+
+impl Borrow<StateStore<Counter>> for Repo {
+    fn borrow(&self) -> &StateStore<Counter> {
+        &self.states
+    }
+}
+
+impl BorrowableAsContext for Repo {}
+
+type Lifecycle = Static<NeverSnapshot, ContextWithMeta<Repo, Meta>>;
+
+/// [`EventHandler`] incrementing the next [`Counter`] on every increment of
+/// the [`Counter`]s with IDs lower than `3`.
+struct IncrementNext;
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl EventHandler<Incremented> for IncrementNext {
+    type Context = Lifecycle;
+    type Err = String;
+
+    async fn on(&self, ev: &Incremented, lifecycle: &Lifecycle) -> Result<(), String> {
+        if ev.0 >= 3 {
+            return Ok(());
+        }
+        increment(lifecycle, ev.0 + 1).await
+    }
+}
+
+async fn increment(lifecycle: &Lifecycle, id: u8) -> Result<(), String> {
+    increment_times(lifecycle, id, 1).await
+}
+
+async fn increment_times(lifecycle: &Lifecycle, id: u8, times: u8) -> Result<(), String> {
+    let mut agg = HydratedAggregate::from_version(Counter { id, value: 0 }, Version::Initial);
+    let events = (1..=times)
+        .map(|n| NumberedEvent {
+            num: num(n),
+            data: Incremented(id),
+        })
+        .collect::<Vec<_>>();
+    lifecycle
+        .apply_events_and_persist::<EventStore<_, _, _>, StateStore<_>, _, _, _>(&mut agg, events)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+fn num(n: u8) -> EventNumber {
+    EventNumber::new(n).unwrap()
+}
+
+/// Reads the IDs of all the stored [`Counter`]s' events along with their
+/// metadata.
+fn stored(lifecycle: &Lifecycle) -> Vec<(u8, Meta)> {
+    let repo: &Repo = lifecycle.as_ref();
+    repo.events
+        .read_all(0)
+        .into_iter()
+        .map(|ev| (ev.id, ev.meta))
+        .collect()
+}
+
+#[test]
+fn propagates_causation_to_events_persisted_by_handlers() {
+    let mut cfg = EventProcessingConfiguration::new();
+    cfg.register_event_handler::<Incremented, Incremented, Lifecycle, String, _>(IncrementNext);
+    let cfg = cfg.build();
+    let root = Meta::new().with_actor(Actor::User);
+    let lifecycle = Static::new(
+        NeverSnapshot,
+        ContextWithMeta::new(Repo::default(), root.clone()),
+    );
+
+    block_on(increment(&lifecycle, 1)).unwrap();
+    block_on(lifecycle.exec_event_handlers::<Incremented, String>(&cfg)).unwrap();
+    block_on(lifecycle.exec_event_handlers::<Incremented, String>(&cfg)).unwrap();
+    block_on(increment(&lifecycle, 7)).unwrap();
+
+    let stored = stored(&lifecycle);
+    assert_eq!(
+        stored.iter().map(|(id, _)| *id).collect::<Vec<_>>(),
+        vec![1, 2, 3, 7],
+    );
+    let metas = stored.into_iter().map(|(_, m)| m).collect::<Vec<_>>();
+    assert_eq!(metas[0], root.for_event(&1, num(1)));
+    assert_eq!(metas[3], root.for_event(&7, num(1)));
+    assert_ne!(metas[0].event_id, metas[3].event_id);
+    for (cause, caused) in metas.iter().zip(&metas[1..3]) {
+        assert_ne!(caused.event_id, cause.event_id);
+        assert_eq!(caused.causation_id, Some(cause.event_id));
+        assert_eq!(caused.correlation_id, root.event_id);
+        assert_eq!(caused.actor, Some(Actor::User));
+    }
+}
+
+#[test]
+fn gives_own_id_to_each_event_persisted_at_once() {
+    let root = Meta::new();
+    let lifecycle = Static::new(
+        NeverSnapshot,
+        ContextWithMeta::new(Repo::default(), root.clone()),
+    );
+
+    block_on(increment_times(&lifecycle, 1, 3)).unwrap();
+
+    let metas = stored(&lifecycle)
+        .into_iter()
+        .map(|(_, m)| m)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        metas,
+        (1..=3)
+            .map(|n| root.for_event(&1, num(n)))
+            .collect::<Vec<_>>(),
+    );
+    assert_ne!(metas[0].event_id, metas[1].event_id);
+    assert_ne!(metas[1].event_id, metas[2].event_id);
+    assert_ne!(metas[0].event_id, metas[2].event_id);
+}
+
+#[test]
+fn persists_events_of_handlers_with_non_causal_meta() {
+    let mut cfg = EventProcessingConfiguration::new();
+    cfg.register_event_handler::<Incremented, Incremented, Lifecycle, String, _>(IncrementNext);
+    let cfg = cfg.build();
+    let root = Meta::new().with_actor(Actor::User);
+    let lifecycle = Static::new(
+        NeverSnapshot,
+        ContextWithMeta::new_non_causal(Repo::default(), root.clone()),
+    );
+
+    block_on(increment(&lifecycle, 1)).unwrap();
+    block_on(lifecycle.exec_event_handlers::<Incremented, String>(&cfg)).unwrap();
+    block_on(lifecycle.exec_event_handlers::<Incremented, String>(&cfg)).unwrap();
+
+    assert_eq!(
+        stored(&lifecycle),
+        (1..=3)
+            .map(|id| (id, root.for_event(&id, num(1))))
+            .collect::<Vec<_>>(),
+    );
+}
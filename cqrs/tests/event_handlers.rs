@@ -0,0 +1,328 @@
+use std::{
+    any::TypeId,
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use async_trait::async_trait;
+use cqrs::{
+    lifecycle::{BufferedContext as _, Context, Static},
+    DeadLetterSink, Event, EventHandler, EventNumber, EventProcessingConfiguration, EventType,
+    ExecutionMode, FailurePolicy, NumberedEvent, RegisteredEvent,
+};
+use futures::executor::block_on;
+
+/// Test event with a number.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct TestEvent(u8);
+
+impl Event for TestEvent {
+    fn event_type(&self) -> EventType {
+        "test"
+    }
+}
+
+impl RegisteredEvent for TestEvent {
+    fn type_id(&self) -> TypeId {
+        TypeId::of::<Self>()
+    }
+}
+
+type TestLifecycle = Static<(), Context<()>>;
+
+/// [`EventHandler`] always failing with its `ID`.
+struct Failing<const ID: u8>;
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl<const ID: u8> EventHandler<TestEvent> for Failing<ID> {
+    type Context = ();
+    type Err = (u8, u8);
+
+    async fn on(&self, ev: &TestEvent, _: &()) -> Result<(), Self::Err> {
+        Err((ID, ev.0))
+    }
+}
+
+/// [`EventHandler`] recording all the handled events.
+#[derive(Clone, Default)]
+struct Recording(Arc<Mutex<Vec<u8>>>);
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl EventHandler<TestEvent> for Recording {
+    type Context = ();
+    type Err = (u8, u8);
+
+    async fn on(&self, ev: &TestEvent, _: &()) -> Result<(), Self::Err> {
+        self.0.lock().unwrap().push(ev.0);
+        Ok(())
+    }
+}
+
+/// [`EventHandler`] failing the given number of times before succeeding.
+struct Flaky(AtomicUsize);
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl EventHandler<TestEvent> for Flaky {
+    type Context = ();
+    type Err = (u8, u8);
+
+    async fn on(&self, ev: &TestEvent, _: &()) -> Result<(), Self::Err> {
+        let left = self.0.load(Ordering::SeqCst);
+        if left == 0 {
+            return Ok(());
+        }
+        self.0.store(left - 1, Ordering::SeqCst);
+        Err((0, ev.0))
+    }
+}
+
+/// [`DeadLetterSink`] storing all the pushed events.
+#[derive(Clone, Default)]
+struct DeadLetters(Arc<Mutex<Vec<(TestEvent, &'static str, (u8, u8))>>>);
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl DeadLetterSink<TestEvent, (u8, u8)> for DeadLetters {
+    async fn push(
+        &self,
+        event: &TestEvent,
+        handler: &'static str,
+        err: (u8, u8),
+    ) -> Result<(), (u8, u8)> {
+        self.0.lock().unwrap().push((*event, handler, err));
+        Ok(())
+    }
+}
+
+fn lifecycle_with_event() -> TestLifecycle {
+    let ctx = Context::new(());
+    ctx.buffer_event(
+        &(),
+        NumberedEvent {
+            num: EventNumber::MIN_VALUE,
+            data: TestEvent(1),
+        },
+    );
+    Static::new((), ctx)
+}
+
+fn exec_with(mode: ExecutionMode) -> (Result<(), Vec<(u8, u8)>>, Vec<u8>) {
+    let recorded = Recording::default();
+    let mut cfg = EventProcessingConfiguration::new();
+    cfg.set_execution_mode(mode);
+    cfg.register_event_handler::<TestEvent, TestEvent, TestLifecycle, (u8, u8), _>(Failing::<1>);
+    cfg.register_event_handler::<TestEvent, TestEvent, TestLifecycle, (u8, u8), _>(
+        recorded.clone(),
+    );
+    cfg.register_event_handler::<TestEvent, TestEvent, TestLifecycle, (u8, u8), _>(Failing::<2>);
+    let cfg = cfg.build();
+
+    let ctx = Context::new(());
+    for n in 1..=2u8 {
+        ctx.buffer_event(
+            &(),
+            NumberedEvent {
+                num: EventNumber::new(n).unwrap(),
+                data: TestEvent(n),
+            },
+        );
+    }
+    let lifecycle = Static::new((), ctx);
+
+    let res = block_on(lifecycle.exec_event_handlers::<TestEvent, (u8, u8)>(&cfg)).map_err(|e| {
+        let mut errs = e.into_errors();
+        errs.sort_unstable();
+        errs
+    });
+    let recorded = recorded.0.lock().unwrap().clone();
+    (res, recorded)
+}
+
+#[test]
+fn aggregates_errors_of_all_handlers_in_every_mode() {
+    for mode in [
+        ExecutionMode::Sequential,
+        ExecutionMode::Concurrent(NonZeroUsize::new(2).unwrap()),
+        ExecutionMode::ConcurrentUnordered,
+    ] {
+        let (res, recorded) = exec_with(mode);
+
+        assert_eq!(
+            res,
+            Err(vec![(1, 1), (1, 2), (2, 1), (2, 2)]),
+            "mode: {:?}",
+            mode,
+        );
+        assert_eq!(recorded, vec![1, 2], "mode: {:?}", mode);
+    }
+}
+
+#[test]
+fn stops_on_failure_of_fail_fast_handler_in_every_mode() {
+    for mode in [
+        ExecutionMode::Sequential,
+        ExecutionMode::Concurrent(NonZeroUsize::new(2).unwrap()),
+        ExecutionMode::ConcurrentUnordered,
+    ] {
+        let mut cfg = EventProcessingConfiguration::new();
+        cfg.set_execution_mode(mode);
+        cfg.register_event_handler_with_policy::<TestEvent, TestEvent, TestLifecycle, (u8, u8), _>(
+            Failing::<1>,
+            FailurePolicy::fail_fast(),
+        );
+        let cfg = cfg.build();
+        let recorded = Recording::default();
+        let mut recording_cfg = EventProcessingConfiguration::new();
+        recording_cfg.register_event_handler::<TestEvent, TestEvent, TestLifecycle, (u8, u8), _>(
+            recorded.clone(),
+        );
+        let recording_cfg = recording_cfg.build();
+
+        let ctx = Context::new(());
+        for n in 1..=3u8 {
+            ctx.buffer_event(
+                &(),
+                NumberedEvent {
+                    num: EventNumber::new(n).unwrap(),
+                    data: TestEvent(n),
+                },
+            );
+        }
+        let lifecycle = Static::new((), ctx);
+
+        let res = block_on(lifecycle.exec_event_handlers::<TestEvent, (u8, u8)>(&cfg));
+        assert_eq!(
+            res.map_err(|e| e.into_errors()),
+            Err(vec![(1, 1)]),
+            "mode: {:?}",
+            mode,
+        );
+
+        block_on(lifecycle.exec_event_handlers::<TestEvent, (u8, u8)>(&recording_cfg)).unwrap();
+        assert_eq!(
+            *recorded.0.lock().unwrap(),
+            vec![1, 2, 3],
+            "mode: {:?}",
+            mode
+        );
+    }
+}
+
+#[test]
+fn redelivers_event_failed_by_fail_fast_handler() {
+    let recorded = Recording::default();
+    let mut cfg = EventProcessingConfiguration::new();
+    cfg.register_event_handler_with_policy::<TestEvent, TestEvent, TestLifecycle, (u8, u8), _>(
+        Flaky(AtomicUsize::new(1)),
+        FailurePolicy::fail_fast(),
+    );
+    cfg.register_event_handler::<TestEvent, TestEvent, TestLifecycle, (u8, u8), _>(
+        recorded.clone(),
+    );
+    let cfg = cfg.build();
+    let lifecycle = lifecycle_with_event();
+
+    let res = block_on(lifecycle.exec_event_handlers::<TestEvent, (u8, u8)>(&cfg));
+    assert_eq!(res.map_err(|e| e.into_errors()), Err(vec![(0, 1)]));
+    // Handlers of the same event may run in any order, so the recording one
+    // may have handled it before the failure.
+    let handled = recorded.0.lock().unwrap().len();
+
+    let res = block_on(lifecycle.exec_event_handlers::<TestEvent, (u8, u8)>(&cfg));
+    assert_eq!(res, Ok(()));
+    assert_eq!(*recorded.0.lock().unwrap(), vec![1; handled + 1]);
+
+    let res = block_on(lifecycle.exec_event_handlers::<TestEvent, (u8, u8)>(&cfg));
+    assert_eq!(res, Ok(()));
+    assert_eq!(*recorded.0.lock().unwrap(), vec![1; handled + 1]);
+}
+
+#[test]
+fn succeeds_without_buffered_events() {
+    let mut cfg = EventProcessingConfiguration::new();
+    cfg.register_event_handler::<TestEvent, TestEvent, TestLifecycle, (u8, u8), _>(Failing::<1>);
+    let cfg = cfg.build();
+    let lifecycle = Static::new((), Context::new(()));
+
+    let res = block_on(lifecycle.exec_event_handlers::<TestEvent, (u8, u8)>(&cfg));
+
+    assert_eq!(res, Ok(()));
+}
+
+#[test]
+fn skips_failed_handler() {
+    let mut cfg = EventProcessingConfiguration::new();
+    cfg.register_event_handler_with_policy::<TestEvent, TestEvent, TestLifecycle, (u8, u8), _>(
+        Failing::<1>,
+        FailurePolicy::skip_and_log(),
+    );
+    let cfg = cfg.build();
+
+    let res = block_on(lifecycle_with_event().exec_event_handlers::<TestEvent, (u8, u8)>(&cfg));
+
+    assert_eq!(res, Ok(()));
+}
+
+#[test]
+fn retries_failed_handler_with_backoff() {
+    let waited = Arc::new(Mutex::new(vec![]));
+    let backoff = {
+        let waited = waited.clone();
+        move |attempt| {
+            waited.lock().unwrap().push(attempt);
+            async {}
+        }
+    };
+    let mut cfg = EventProcessingConfiguration::new();
+    cfg.register_event_handler_with_policy::<TestEvent, TestEvent, TestLifecycle, (u8, u8), _>(
+        Flaky(AtomicUsize::new(2)),
+        FailurePolicy::retry(3, backoff),
+    );
+    let cfg = cfg.build();
+
+    let res = block_on(lifecycle_with_event().exec_event_handlers::<TestEvent, (u8, u8)>(&cfg));
+
+    assert_eq!(res, Ok(()));
+    assert_eq!(*waited.lock().unwrap(), vec![1, 2]);
+}
+
+#[test]
+fn fails_once_retries_are_exhausted() {
+    let mut cfg = EventProcessingConfiguration::new();
+    cfg.register_event_handler_with_policy::<TestEvent, TestEvent, TestLifecycle, (u8, u8), _>(
+        Flaky(AtomicUsize::new(3)),
+        FailurePolicy::retry(3, ()),
+    );
+    let cfg = cfg.build();
+
+    let res = block_on(lifecycle_with_event().exec_event_handlers::<TestEvent, (u8, u8)>(&cfg));
+
+    assert_eq!(res.map_err(|e| e.into_errors()), Err(vec![(0, 1)]));
+}
+
+#[test]
+fn pushes_failed_event_to_dead_letter_sink() {
+    let dead_letters = DeadLetters::default();
+    let mut cfg = EventProcessingConfiguration::new();
+    cfg.register_event_handler_with_policy::<TestEvent, TestEvent, TestLifecycle, (u8, u8), _>(
+        Failing::<1>,
+        FailurePolicy::dead_letter(dead_letters.clone()).with_retries(2, ()),
+    );
+    let cfg = cfg.build();
+
+    let res = block_on(lifecycle_with_event().exec_event_handlers::<TestEvent, (u8, u8)>(&cfg));
+
+    assert_eq!(res, Ok(()));
+    let dead_letters = dead_letters.0.lock().unwrap();
+    assert_eq!(dead_letters.len(), 1);
+    let (ev, handler, err) = dead_letters[0];
+    assert_eq!(ev, TestEvent(1));
+    assert!(handler.ends_with("Failing<1>"), "handler: {}", handler);
+    assert_eq!(err, (1, 1));
+}
@@ -0,0 +1,364 @@
+use std::{convert::Infallible, sync::Mutex, time::SystemTime};
+
+use async_trait::async_trait;
+use cqrs::{
+    lifecycle::{Basic, Context, ExecAndPersistError, LoadExecAndPersistError},
+    Aggregate, AggregateType, BoxTryStream, Command, CommandHandler, Event, EventNumber, EventSink,
+    EventSource, EventSourced, EventType, HydratedAggregate, NeverSnapshot, NumberedEvent, Since,
+    SnapshotSink, SnapshotSource, SnapshotVersion, Version, VersionConflict,
+};
+use futures::{executor::block_on, stream};
+
+/// Test aggregate counting applied increments.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+struct Counter {
+    value: u32,
+}
+
+impl Counter {
+    /// Constant ID of test aggregate.
+    const ID: u8 = 1;
+}
+
+impl Aggregate for Counter {
+    type Id = u8;
+
+    fn aggregate_type(&self) -> AggregateType {
+        "counter"
+    }
+
+    fn id(&self) -> &Self::Id {
+        &Self::ID
+    }
+}
+
+/// Test event incrementing the [`Counter`].
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+struct Incremented;
+
+impl Event for Incremented {
+    fn event_type(&self) -> EventType {
+        "incremented"
+    }
+}
+
+impl EventSourced<Incremented> for Counter {
+    fn apply(&mut self, _: &Incremented) {
+        self.value += 1;
+    }
+}
+
+/// Test command incrementing an existing [`Counter`].
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+struct Increment {
+    expected_version: Option<Version>,
+}
+
+impl Command for Increment {
+    type Aggregate = Counter;
+
+    fn aggregate_id(&self) -> Option<&u8> {
+        Some(&Counter::ID)
+    }
+
+    fn expected_version(&self) -> Option<Version> {
+        self.expected_version
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl CommandHandler<Increment> for Counter {
+    type Context = ();
+    type Err = Infallible;
+    type Event = Incremented;
+    type Ok = Vec<NumberedEvent<Incremented>>;
+
+    async fn handle(&self, _: Increment, _: &()) -> Result<Self::Ok, Infallible> {
+        Ok(vec![NumberedEvent {
+            num: EventNumber::MIN_VALUE,
+            data: Incremented,
+        }])
+    }
+}
+
+/// In-memory repository of a single [`Counter`] without snapshots.
+#[derive(Debug, Default)]
+struct Repo {
+    events: Mutex<Vec<NumberedEvent<Incremented>>>,
+}
+
+impl Repo {
+    /// Creates new [`Repo`] with the given number of already stored events.
+    fn with_events(count: u8) -> Self {
+        let repo = Self::default();
+        repo.events
+            .lock()
+            .unwrap()
+            .extend((1..=count).map(|n| NumberedEvent {
+                num: EventNumber::new(n).unwrap(),
+                data: Incremented,
+            }));
+        repo
+    }
+}
+
+impl AsRef<Repo> for Repo {
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl SnapshotSource<Counter> for Repo {
+    type Err = Infallible;
+
+    async fn load_snapshots(
+        &self,
+        _: &[u8],
+    ) -> Result<Vec<(Counter, Version, SnapshotVersion, SystemTime)>, Infallible> {
+        Ok(vec![])
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl SnapshotSink<Counter> for Repo {
+    type Err = Infallible;
+
+    async fn persist_snapshots(&self, _: &[(&Counter, Version)]) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+impl EventSource<Counter, Incremented> for Repo {
+    type Err = Infallible;
+
+    fn read_events(
+        &self,
+        _: &u8,
+        since: Since,
+    ) -> BoxTryStream<'_, NumberedEvent<Incremented>, Infallible> {
+        let events = self
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|ev| match since {
+                Since::BeginningOfStream => true,
+                Since::Event(num) => ev.num > num,
+            })
+            .copied()
+            .map(Ok)
+            .collect::<Vec<_>>();
+        Box::pin(stream::iter(events))
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl EventSink<Counter, Incremented, ()> for Repo {
+    type Err = VersionConflict;
+    type Ok = Vec<NumberedEvent<Incremented>>;
+
+    async fn append_events(
+        &self,
+        _: &u8,
+        expected: Version,
+        events: &[NumberedEvent<Incremented>],
+        _: &(),
+    ) -> Result<Self::Ok, VersionConflict> {
+        let mut stored = self.events.lock().unwrap();
+        let mut ver = Version::new(stored.len() as u64);
+        if ver != expected {
+            return Err(VersionConflict {
+                expected,
+                actual: ver,
+            });
+        }
+        let appended = events
+            .iter()
+            .map(|ev| {
+                ver.incr();
+                NumberedEvent {
+                    num: ver.event_number().unwrap(),
+                    data: ev.data,
+                }
+            })
+            .collect::<Vec<_>>();
+        stored.extend_from_slice(&appended);
+        Ok(appended)
+    }
+}
+
+fn increment(
+    lifecycle: &Basic<NeverSnapshot>,
+    repo: &Repo,
+    expected_version: Option<Version>,
+) -> Result<
+    Option<HydratedAggregate<Counter>>,
+    LoadExecAndPersistError<
+        Counter,
+        Infallible,
+        Infallible,
+        Infallible,
+        VersionConflict,
+        Infallible,
+    >,
+> {
+    block_on(
+        lifecycle.load_aggregate_exec_command_and_persist::<
+            Repo,
+            Repo,
+            Repo,
+            Repo,
+            _,
+            _,
+            _,
+            Context<()>,
+        >(Increment { expected_version }, &(), &(), repo, None),
+    )
+}
+
+#[test]
+fn executes_command_when_expected_version_matches() {
+    let lifecycle = Basic::new(NeverSnapshot);
+    let repo = Repo::with_events(1);
+
+    let agg = increment(&lifecycle, &repo, Some(Version::new(1u8)))
+        .unwrap()
+        .unwrap();
+    assert_eq!(agg.version(), Version::new(2u8));
+
+    let agg = increment(&lifecycle, &repo, Some(Version::new(2u8)))
+        .unwrap()
+        .unwrap();
+    assert_eq!(agg.version(), Version::new(3u8));
+    assert_eq!(agg.state().value, 3);
+}
+
+#[test]
+fn executes_command_without_expected_version() {
+    let lifecycle = Basic::new(NeverSnapshot);
+    let repo = Repo::with_events(1);
+
+    let _ = increment(&lifecycle, &repo, None).unwrap();
+    let agg = increment(&lifecycle, &repo, None).unwrap().unwrap();
+    assert_eq!(agg.version(), Version::new(3u8));
+}
+
+#[test]
+fn rejects_command_on_version_conflict() {
+    let lifecycle = Basic::new(NeverSnapshot);
+    let repo = Repo::with_events(1);
+
+    let _ = increment(&lifecycle, &repo, Some(Version::new(1u8))).unwrap();
+    let err = increment(&lifecycle, &repo, Some(Version::new(1u8))).unwrap_err();
+
+    assert_eq!(
+        err,
+        LoadExecAndPersistError::VersionConflict {
+            expected: Version::new(1u8),
+            actual: Version::new(2u8),
+        },
+    );
+    assert_eq!(repo.events.lock().unwrap().len(), 2);
+}
+
+#[test]
+fn rejects_events_of_stale_aggregate() {
+    let lifecycle = Basic::new(NeverSnapshot);
+    let repo = Repo::with_events(1);
+
+    let stale = increment(&lifecycle, &repo, None).unwrap();
+    let _ = increment(&lifecycle, &repo, None).unwrap();
+
+    let err = block_on(
+        lifecycle.exec_command_and_persist::<Repo, Repo, _, _, _, Context<()>>(
+            Increment::default(),
+            stale,
+            &(),
+            &(),
+            &repo,
+            None,
+        ),
+    )
+    .unwrap_err();
+
+    assert_eq!(
+        err,
+        ExecAndPersistError::VersionConflict {
+            expected: Version::new(2u8),
+            actual: Version::new(3u8),
+        },
+    );
+    assert_eq!(repo.events.lock().unwrap().len(), 3);
+}
+
+#[test]
+fn applies_events_of_new_aggregate_once() {
+    let lifecycle = Basic::new(NeverSnapshot);
+    let repo = Repo::default();
+
+    let agg = block_on(
+        lifecycle.exec_command_and_persist::<Repo, Repo, _, _, _, Context<()>>(
+            Increment::default(),
+            None,
+            &(),
+            &(),
+            &repo,
+            None,
+        ),
+    )
+    .unwrap();
+
+    assert_eq!(agg.version(), Version::new(1u8));
+    assert_eq!(agg.state().value, 1);
+    assert_eq!(repo.events.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn loads_nothing_without_snapshot_and_events() {
+    let lifecycle = Basic::new(NeverSnapshot);
+    let repo = Repo::default();
+
+    let agg = block_on(
+        lifecycle.load_aggregate_and_rehydrate::<Repo, Repo, Incremented, Counter, _>(
+            &Counter::ID,
+            &repo,
+        ),
+    )
+    .unwrap();
+    assert_eq!(agg, None);
+
+    assert_eq!(increment(&lifecycle, &repo, None).unwrap(), None);
+    assert!(repo.events.lock().unwrap().is_empty());
+}
+
+#[test]
+fn rehydrates_from_events_without_snapshot() {
+    let lifecycle = Basic::new(NeverSnapshot);
+    let repo = Repo::with_events(3);
+
+    let agg = block_on(
+        lifecycle.load_aggregate_and_rehydrate::<Repo, Repo, Incremented, Counter, _>(
+            &Counter::ID,
+            &repo,
+        ),
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(agg.version(), Version::new(3u8));
+    assert_eq!(agg.snapshot_version(), None);
+    assert_eq!(agg.state().value, 3);
+
+    let aggs = block_on(
+        lifecycle.load_aggregates_and_rehydrate::<Repo, Repo, Incremented, Counter, _>(
+            &[Counter::ID],
+            &repo,
+        ),
+    )
+    .unwrap();
+    assert_eq!(aggs, vec![agg]);
+}
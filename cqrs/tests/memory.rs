@@ -0,0 +1,658 @@
+use std::{
+    convert::Infallible,
+    num::NonZeroUsize,
+    time::{Duration, SystemTime},
+};
+
+use async_trait::async_trait;
+use cqrs::{
+    lifecycle::{Basic, Context, Static},
+    memory::{EventStore, StateStore},
+    Aggregate, AggregateType, AlwaysSnapshot, Event, EventFilter, EventNumber, EventSink as _,
+    EventSource as _, EventSourceWithMeta as _, EventSourced, EventType, GlobalPosition,
+    HydratedAggregate, NumberedEvent, NumberedEventWithMeta, PerEventMeta, ReadRange, Repository,
+    Since, SnapshotRetention, SnapshotSink, SnapshotSource, SnapshotVersion, TimestampedMeta,
+    TypedGlobalEventSource as _, Version, VersionConflict,
+};
+use futures::{executor::block_on, FutureExt as _, TryStreamExt as _};
+
+/// Test aggregate counting applied increments.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+struct Counter {
+    id: u8,
+    value: u32,
+}
+
+impl Aggregate for Counter {
+    type Id = u8;
+
+    fn aggregate_type(&self) -> AggregateType {
+        "counter"
+    }
+
+    fn id(&self) -> &Self::Id {
+        &self.id
+    }
+}
+
+/// Test event incrementing the [`Counter`] with the given ID.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+struct Incremented(u8);
+
+impl Event for Incremented {
+    fn event_type(&self) -> EventType {
+        "incremented"
+    }
+}
+
+impl EventSourced<Incremented> for Counter {
+    fn apply(&mut self, ev: &Incremented) {
+        self.id = ev.0;
+        self.value += 1;
+    }
+}
+
+/// Repository combining in-memory storages of [`Counter`]s.
+#[derive(Debug, Default)]
+struct Repo {
+    events: EventStore<Counter, Incremented>,
+    states: StateStore<Counter>,
+}
+
+impl AsRef<EventStore<Counter, Incremented>> for Repo {
+    fn as_ref(&self) -> &EventStore<Counter, Incremented> {
+        &self.events
+    }
+}
+
+impl AsRef<StateStore<Counter>> for Repo {
+    fn as_ref(&self) -> &StateStore<Counter> {
+        &self.states
+    }
+}
+
+fn increments(id: u8, count: u8) -> Vec<NumberedEvent<Incremented>> {
+    (1..=count)
+        .map(|n| NumberedEvent {
+            num: EventNumber::new(n).unwrap(),
+            data: Incremented(id),
+        })
+        .collect()
+}
+
+/// Loads the stored snapshots of the given [`Counter`]s, omitting the moments
+/// they were persisted at.
+fn snapshots(store: &StateStore<Counter>, ids: &[u8]) -> Vec<(Counter, Version, SnapshotVersion)> {
+    block_on(store.load_snapshots(ids))
+        .unwrap()
+        .into_iter()
+        .map(|(agg, ver, snapshot_ver, _)| (agg, ver, snapshot_ver))
+        .collect()
+}
+
+#[test]
+fn numbers_events_per_stream_and_globally() {
+    let store = EventStore::<Counter, Incremented>::new();
+
+    block_on(store.append_events(&1, Version::Initial, &increments(1, 2), &())).unwrap();
+    let appended =
+        block_on(store.append_events(&2, Version::Initial, &increments(2, 1), &())).unwrap();
+    block_on(store.append_events(&1, Version::new(2u8), &increments(1, 1), &())).unwrap();
+
+    assert_eq!(appended, increments(2, 1));
+    let all = store
+        .read_all(0)
+        .into_iter()
+        .map(|ev| (ev.position, ev.id, ev.event.num))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        all,
+        vec![
+            (1, 1, EventNumber::new(1u8).unwrap()),
+            (2, 1, EventNumber::new(2u8).unwrap()),
+            (3, 2, EventNumber::new(1u8).unwrap()),
+            (4, 1, EventNumber::new(3u8).unwrap()),
+        ],
+    );
+    assert_eq!(store.read_all(3).len(), 1);
+}
+
+#[test]
+fn rejects_appending_with_stale_version() {
+    let store = EventStore::<Counter, Incremented>::new();
+    block_on(store.append_events(&1, Version::Initial, &increments(1, 2), &())).unwrap();
+
+    let err =
+        block_on(store.append_events(&1, Version::new(1u8), &increments(1, 1), &())).unwrap_err();
+
+    assert_eq!(
+        err,
+        VersionConflict {
+            expected: Version::new(1u8),
+            actual: Version::new(2u8),
+        },
+    );
+    assert_eq!(store.len(), 2);
+}
+
+#[test]
+fn reads_events_since_given_number() {
+    let store = EventStore::<Counter, Incremented>::new();
+    block_on(store.append_events(&1, Version::Initial, &increments(1, 3), &())).unwrap();
+    block_on(store.append_events(&2, Version::Initial, &increments(2, 1), &())).unwrap();
+
+    let events = block_on(
+        store
+            .read_events(&1, Since::Event(EventNumber::MIN_VALUE))
+            .try_collect::<Vec<_>>(),
+    )
+    .unwrap();
+    let batch = block_on(
+        store
+            .read_events_batch(
+                &[
+                    (&2, Since::BeginningOfStream),
+                    (&3, Since::BeginningOfStream),
+                ],
+                NonZeroUsize::new(1).unwrap(),
+            )
+            .try_collect::<Vec<_>>(),
+    )
+    .unwrap();
+
+    assert_eq!(events, increments(1, 3)[1..].to_vec());
+    assert_eq!(batch, vec![(0, increments(2, 1)[0])]);
+}
+
+#[test]
+fn reads_events_within_range() {
+    let store = EventStore::<Counter, Incremented>::new();
+    block_on(store.append_events(&1, Version::Initial, &increments(1, 5), &())).unwrap();
+    let read = |range| block_on(store.read_events_range(&1, range).try_collect::<Vec<_>>());
+
+    let bounded =
+        read(ReadRange::since(EventNumber::MIN_VALUE).until(EventNumber::new(3u8).unwrap()))
+            .unwrap();
+    let latest = read(ReadRange::default().limit(2).backward()).unwrap();
+    let reversed = read(
+        ReadRange::since(EventNumber::MIN_VALUE)
+            .until(EventNumber::new(4u8).unwrap())
+            .backward(),
+    )
+    .unwrap();
+    let beyond = read(ReadRange::since(EventNumber::new(7u8).unwrap())).unwrap();
+
+    let all = increments(1, 5);
+    assert_eq!(bounded, all[1..3].to_vec());
+    assert_eq!(latest, vec![all[4], all[3]]);
+    assert_eq!(reversed, vec![all[3], all[2], all[1]]);
+    assert!(beyond.is_empty());
+}
+
+/// Test metadata noting the number of the [`Event`] it's individualized for.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Note(&'static str, Option<EventNumber>);
+
+impl PerEventMeta<u8> for Note {
+    fn for_event(&self, _: &u8, num: EventNumber) -> Self {
+        Self(self.0, Some(num))
+    }
+}
+
+#[test]
+fn reads_events_with_meta_individualized_for_each_of_them() {
+    let store = EventStore::<Counter, Incremented, Note>::new();
+    let first = Note("first", None);
+    let second = Note("second", None);
+    block_on(store.append_events(&1, Version::Initial, &increments(1, 2), &first)).unwrap();
+    block_on(store.append_events(&1, Version::new(2u8), &increments(1, 1), &second)).unwrap();
+
+    let events = block_on(
+        store
+            .read_events_with_meta(&1, Since::BeginningOfStream)
+            .try_collect::<Vec<_>>(),
+    )
+    .unwrap();
+
+    let num = |n: u8| EventNumber::new(n).unwrap();
+    assert_eq!(
+        events,
+        vec![
+            NumberedEventWithMeta {
+                num: num(1),
+                data: Incremented(1),
+                meta: Note("first", Some(num(1))),
+            },
+            NumberedEventWithMeta {
+                num: num(2),
+                data: Incremented(1),
+                meta: Note("first", Some(num(2))),
+            },
+            NumberedEventWithMeta {
+                num: num(3),
+                data: Incremented(1),
+                meta: Note("second", Some(num(3))),
+            },
+        ],
+    );
+}
+
+#[test]
+fn reads_all_events_in_global_order() {
+    let store = EventStore::<Counter, Incremented>::new();
+    block_on(store.append_events(&1, Version::Initial, &increments(1, 2), &())).unwrap();
+    block_on(store.append_events(&2, Version::Initial, &increments(2, 1), &())).unwrap();
+    let read = |after, filter| {
+        block_on(
+            store
+                .read_all_events(GlobalPosition::new(after), filter)
+                .map_ok(|ev| (ev.position.get(), ev.aggregate_type, ev.id))
+                .try_collect::<Vec<_>>(),
+        )
+        .unwrap()
+    };
+
+    assert_eq!(
+        read(1, EventFilter::new().with_aggregate_types(vec!["counter"])),
+        vec![(2, "counter", 1), (3, "counter", 2)],
+    );
+    assert_eq!(
+        read(0, EventFilter::new().with_event_types(vec!["decremented"])),
+        vec![],
+    );
+}
+
+#[test]
+fn subscribes_to_all_events() {
+    let store = EventStore::<Counter, Incremented>::new();
+    block_on(store.append_events(&1, Version::Initial, &increments(1, 1), &())).unwrap();
+    let mut subscription = store.subscribe_all_events(
+        GlobalPosition::BEGINNING,
+        EventFilter::new().with_event_types(vec!["incremented"]),
+    );
+
+    let caught_up = block_on(subscription.try_next()).unwrap().unwrap();
+    assert_eq!((caught_up.position.get(), caught_up.id), (1, 1));
+    assert!(subscription.try_next().now_or_never().is_none());
+
+    block_on(store.append_events(&2, Version::Initial, &increments(2, 1), &())).unwrap();
+    let live = block_on(subscription.try_next()).unwrap().unwrap();
+    assert_eq!((live.position.get(), live.id), (2, 2));
+}
+
+#[test]
+fn keeps_latest_snapshot_only() {
+    let store = StateStore::<Counter>::new();
+    let newer = Counter { id: 1, value: 2 };
+    let older = Counter { id: 1, value: 1 };
+    let before = SystemTime::now();
+
+    block_on(store.persist_snapshot(&newer, Version::new(2u8))).unwrap();
+    block_on(store.persist_snapshot(&older, Version::new(1u8))).unwrap();
+
+    let (_, _, _, taken_at) = block_on(store.load_snapshot(&1)).unwrap().unwrap();
+    assert!(taken_at >= before);
+
+    assert_eq!(
+        snapshots(&store, &[1, 2]),
+        vec![(newer, Version::new(2u8), Counter::SNAPSHOT_VERSION)],
+    );
+}
+
+#[test]
+fn prunes_snapshots_older_than_given_version() {
+    let store = StateStore::<Counter>::new();
+    block_on(store.persist_snapshot(&Counter { id: 1, value: 2 }, Version::new(2u8))).unwrap();
+    block_on(store.persist_snapshot(&Counter { id: 2, value: 5 }, Version::new(5u8))).unwrap();
+
+    block_on(store.prune_snapshots(&[&1, &2], SnapshotRetention::KeepSince(Version::new(3u8))))
+        .unwrap();
+
+    assert_eq!(
+        snapshots(&store, &[1, 2]),
+        vec![(Counter { id: 2, value: 5 }, Version::new(5u8), 1)],
+    );
+}
+
+#[test]
+fn keeps_latest_snapshot_when_pruning_by_count_or_keeping_all() {
+    let store = StateStore::<Counter>::new();
+    block_on(store.persist_snapshot(&Counter { id: 1, value: 2 }, Version::new(2u8))).unwrap();
+
+    for retention in [
+        SnapshotRetention::KeepAll,
+        SnapshotRetention::KeepLatest(NonZeroUsize::new(1).unwrap()),
+    ] {
+        block_on(store.prune_snapshots(&[&1], retention)).unwrap();
+
+        assert_eq!(
+            snapshots(&store, &[1]),
+            vec![(Counter { id: 1, value: 2 }, Version::new(2u8), 1)],
+            "retention: {:?}",
+            retention,
+        );
+    }
+}
+
+/// [`StateStore`] failing to prune snapshots.
+#[derive(Debug, Default)]
+struct UnprunableStore(StateStore<Counter>);
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl SnapshotSink<Counter> for UnprunableStore {
+    type Err = &'static str;
+
+    async fn persist_snapshots(&self, aggs: &[(&Counter, Version)]) -> Result<(), Self::Err> {
+        self.0.persist_snapshots(aggs).await.map_err(|e| match e {})
+    }
+
+    async fn prune_snapshots(&self, _: &[&u8], _: SnapshotRetention) -> Result<(), Self::Err> {
+        Err("pruning failed")
+    }
+}
+
+impl AsRef<UnprunableStore> for UnprunableStore {
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+#[test]
+fn persists_snapshot_despite_pruning_failure() {
+    let store = UnprunableStore::default();
+    let lifecycle = Basic::new(AlwaysSnapshot)
+        .with_snapshot_retention(SnapshotRetention::KeepSince(Version::new(3u8)));
+
+    let mut agg = HydratedAggregate::from_version(Counter { id: 7, value: 2 }, Version::new(2u8));
+    block_on(lifecycle.persist_aggregate::<UnprunableStore, _, _>(&mut agg, &store)).unwrap();
+
+    let mut aggs = [HydratedAggregate::from_version(
+        Counter { id: 8, value: 1 },
+        Version::new(1u8),
+    )];
+    block_on(lifecycle.persist_aggregates::<UnprunableStore, _, _>(&mut aggs, &store)).unwrap();
+
+    assert_eq!(agg.snapshot_version(), Some(Version::new(2u8)));
+    assert_eq!(aggs[0].snapshot_version(), Some(Version::new(1u8)));
+    assert_eq!(
+        snapshots(&store.0, &[7, 8]),
+        vec![
+            (Counter { id: 7, value: 2 }, Version::new(2u8), 1),
+            (Counter { id: 8, value: 1 }, Version::new(1u8), 1),
+        ],
+    );
+}
+
+#[test]
+fn prunes_snapshots_after_persisting_in_lifecycle() {
+    let repo = Repo::default();
+    let lifecycle = Basic::new(AlwaysSnapshot)
+        .with_snapshot_retention(SnapshotRetention::KeepSince(Version::new(3u8)));
+    let ctx = Context::new(());
+
+    let mut agg = HydratedAggregate::from_version(Counter { id: 7, value: 0 }, Version::Initial);
+    block_on(
+        lifecycle.apply_events_and_persist::<EventStore<_, _>, StateStore<_>, _, _, _, _, _, _>(
+            &mut agg,
+            increments(7, 2),
+            &(),
+            &repo,
+            Some(&ctx),
+        ),
+    )
+    .unwrap();
+
+    assert_eq!(agg.snapshot_version(), Some(Version::new(2u8)));
+    assert_eq!(snapshots(&repo.states, &[7]), vec![]);
+}
+
+#[test]
+fn serves_lifecycle_roundtrip() {
+    let repo = Repo::default();
+    let lifecycle = Basic::new(AlwaysSnapshot);
+    let ctx = Context::new(());
+
+    let mut agg = HydratedAggregate::from_version(Counter { id: 7, value: 0 }, Version::Initial);
+    block_on(
+        lifecycle.apply_events_and_persist::<EventStore<_, _>, StateStore<_>, _, _, _, _, _, _>(
+            &mut agg,
+            increments(7, 2),
+            &(),
+            &repo,
+            Some(&ctx),
+        ),
+    )
+    .unwrap();
+
+    let loaded = block_on(
+        lifecycle
+            .load_aggregate_and_rehydrate::<StateStore<_>, EventStore<_, _>, _, _, _>(&7, &repo),
+    )
+    .unwrap()
+    .unwrap();
+
+    assert_eq!(*loaded.state(), Counter { id: 7, value: 2 });
+    assert_eq!(loaded.version(), Version::new(2u8));
+    assert_eq!(loaded.snapshot_version(), Some(Version::new(2u8)));
+    assert!(loaded.snapshot_taken_at().is_some());
+    assert_eq!(repo.events.read_all(0)[0].id, 7);
+}
+
+#[test]
+fn matches_snapshots_to_aggregates_in_batch() {
+    let repo = Repo::default();
+    for (id, count) in [(1, 3), (2, 2), (3, 4)].iter().copied() {
+        block_on(
+            repo.events
+                .append_events(&id, Version::Initial, &increments(id, count), &()),
+        )
+        .unwrap();
+    }
+    block_on(
+        repo.states
+            .persist_snapshot(&Counter { id: 3, value: 2 }, Version::new(2u8)),
+    )
+    .unwrap();
+    block_on(
+        repo.states
+            .persist_snapshot(&Counter { id: 1, value: 3 }, Version::new(3u8)),
+    )
+    .unwrap();
+    let lifecycle = Basic::new(AlwaysSnapshot);
+
+    let loaded = block_on(
+        lifecycle.load_aggregates_and_rehydrate::<StateStore<_>, EventStore<_, _>, _, _, _>(
+            &[3, 2, 1, 4],
+            &repo,
+        ),
+    )
+    .unwrap()
+    .into_iter()
+    .map(|agg| (*agg.state(), agg.version(), agg.snapshot_version()))
+    .collect::<Vec<_>>();
+
+    assert_eq!(
+        loaded,
+        vec![
+            (
+                Counter { id: 3, value: 4 },
+                Version::new(4u8),
+                Some(Version::new(2u8))
+            ),
+            (Counter { id: 2, value: 2 }, Version::new(2u8), None),
+            (
+                Counter { id: 1, value: 3 },
+                Version::new(3u8),
+                Some(Version::new(3u8))
+            ),
+        ],
+    );
+}
+
+#[test]
+fn loads_aggregate_at_version_without_persisting() {
+    let repo = Repo::default();
+    block_on(
+        repo.events
+            .append_events(&7, Version::Initial, &increments(7, 5), &()),
+    )
+    .unwrap();
+    block_on(
+        repo.states
+            .persist_snapshot(&Counter { id: 7, value: 2 }, Version::new(2u8)),
+    )
+    .unwrap();
+    let lifecycle = Basic::new(AlwaysSnapshot);
+    let load_at = |ver: u8| {
+        block_on(
+            lifecycle.load_aggregate_at_version::<StateStore<_>, EventStore<_, _>, _, _, _>(
+                &7,
+                Version::new(ver),
+                &repo,
+            ),
+        )
+        .unwrap()
+    };
+
+    let at_four = load_at(4).unwrap();
+    let at_one = load_at(1).unwrap();
+
+    assert_eq!(*at_four.state(), Counter { id: 7, value: 4 });
+    assert_eq!(at_four.version(), Version::new(4u8));
+    assert_eq!(at_four.snapshot_version(), Some(Version::new(2u8)));
+    assert_eq!(*at_one.state(), Counter { id: 7, value: 1 });
+    assert_eq!(at_one.snapshot_version(), None);
+    assert_eq!(load_at(0), None);
+    assert_eq!(
+        snapshots(&repo.states, &[7]),
+        vec![(
+            Counter { id: 7, value: 2 },
+            Version::new(2u8),
+            Counter::SNAPSHOT_VERSION,
+        )],
+    );
+}
+
+/// Test metadata holding the moment of [`Event`]s only.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Stamp(SystemTime);
+
+impl TimestampedMeta for Stamp {
+    fn timestamp(&self) -> SystemTime {
+        self.0
+    }
+}
+
+impl PerEventMeta<u8> for Stamp {
+    fn for_event(&self, _: &u8, _: EventNumber) -> Self {
+        *self
+    }
+}
+
+#[test]
+fn loads_aggregate_at_time() {
+    let start = SystemTime::UNIX_EPOCH;
+    let events = EventStore::<Counter, Incremented, Stamp>::new();
+    for &(ver, count, secs) in &[(0u8, 2, 0), (2, 1, 10), (3, 1, 20)] {
+        let at = Stamp(start + Duration::from_secs(secs));
+        block_on(events.append_events(&7, Version::new(ver), &increments(7, count), &at)).unwrap();
+    }
+    // Snapshot state differs from the events one, so its usage is observable.
+    let states = StateStore::<Counter>::new();
+    block_on(states.persist_snapshot(&Counter { id: 7, value: 30 }, Version::new(3u8))).unwrap();
+    let lifecycle = Static::new(
+        AlwaysSnapshot,
+        Context::new(Repository::new(events, states)),
+    );
+    let load_at = |secs| {
+        block_on(
+            lifecycle
+                .load_aggregate_at_time::<Repository<_, _>, Repository<_, _>, _, Stamp, Counter>(
+                    &7,
+                    start + Duration::from_secs(secs),
+                ),
+        )
+        .unwrap()
+        .map(|agg| (agg.version(), agg.state().value))
+    };
+
+    assert_eq!(load_at(15), Some((Version::new(3u8), 30)));
+    assert_eq!(load_at(0), Some((Version::new(2u8), 2)));
+    assert_eq!(load_at(60), Some((Version::new(4u8), 31)));
+}
+
+/// Test snapshot source retaining every snapshot of the [`Counter`]s along
+/// with the moment it has been taken at.
+struct History(Vec<(Counter, Version, SystemTime)>);
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl SnapshotSource<Counter> for History {
+    type Err = Infallible;
+
+    async fn load_snapshots(
+        &self,
+        ids: &[u8],
+    ) -> Result<Vec<(Counter, Version, SnapshotVersion, SystemTime)>, Infallible> {
+        Ok(ids
+            .iter()
+            .filter_map(|id| self.0.iter().rev().find(|(agg, ..)| agg.id == *id))
+            .map(|&(agg, ver, at)| (agg, ver, Counter::SNAPSHOT_VERSION, at))
+            .collect())
+    }
+
+    async fn load_snapshot_taken_until(
+        &self,
+        id: &u8,
+        at: SystemTime,
+    ) -> Result<Option<(Counter, Version, SnapshotVersion, SystemTime)>, Infallible> {
+        Ok(self
+            .0
+            .iter()
+            .rev()
+            .find(|(agg, _, taken_at)| agg.id == *id && *taken_at <= at)
+            .map(|&(agg, ver, at)| (agg, ver, Counter::SNAPSHOT_VERSION, at)))
+    }
+}
+
+#[test]
+fn loads_aggregate_at_time_from_snapshot_taken_until_then() {
+    let start = SystemTime::UNIX_EPOCH;
+    let events = EventStore::<Counter, Incremented, Stamp>::new();
+    for &(ver, count, secs) in &[(0u8, 2, 0), (2, 1, 10), (3, 1, 20)] {
+        let at = Stamp(start + Duration::from_secs(secs));
+        block_on(events.append_events(&7, Version::new(ver), &increments(7, count), &at)).unwrap();
+    }
+    // Snapshots states differ from the events ones, so their usage is
+    // observable.
+    let snapshots = History(
+        [(2u8, 20, 5), (3, 30, 15), (4, 40, 25)]
+            .iter()
+            .map(|&(ver, value, secs)| {
+                let agg = Counter { id: 7, value };
+                (agg, Version::new(ver), start + Duration::from_secs(secs))
+            })
+            .collect(),
+    );
+    let lifecycle = Static::new(
+        AlwaysSnapshot,
+        Context::new(Repository::new(events, snapshots)),
+    );
+    let load_at = |secs| {
+        block_on(
+            lifecycle
+                .load_aggregate_at_time::<Repository<_, _>, Repository<_, _>, _, Stamp, Counter>(
+                    &7,
+                    start + Duration::from_secs(secs),
+                ),
+        )
+        .unwrap()
+        .map(|agg| (agg.version(), agg.state().value))
+    };
+
+    assert_eq!(load_at(7), Some((Version::new(2u8), 20)));
+    assert_eq!(load_at(12), Some((Version::new(3u8), 21)));
+    assert_eq!(load_at(17), Some((Version::new(3u8), 30)));
+    assert_eq!(load_at(60), Some((Version::new(4u8), 40)));
+}
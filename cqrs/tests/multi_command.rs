@@ -0,0 +1,480 @@
+use std::{convert::Infallible, mem, sync::Mutex, time::SystemTime};
+
+use async_trait::async_trait;
+use cqrs::{
+    lifecycle::{
+        Atomicity, Basic, BufferedContext as _, Context, LoadAggregatesExecAndPersistError,
+        PersistError, Transaction, UnitOfWork, UnitOfWorkError,
+    },
+    Aggregate, AggregateType, BoxTryStream, Event, EventNumber, EventSink, EventSource,
+    EventSourced, EventType, HydratedAggregate, MultiAggregateCommand,
+    MultiAggregateCommandHandler, NeverSnapshot, NumberedEvent, Since, SnapshotSink,
+    SnapshotSource, SnapshotVersion, Version, VersionConflict,
+};
+use futures::{executor::block_on, stream};
+
+/// Test aggregate of an account with a balance.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+struct Account {
+    id: u8,
+    balance: u32,
+
+    /// Number of times this [`Account`] was opened, which should never exceed
+    /// `1`, unless [`AccountEvent::Opened`] is applied repeatedly.
+    openings: u8,
+}
+
+impl Aggregate for Account {
+    type Id = u8;
+
+    fn aggregate_type(&self) -> AggregateType {
+        "account"
+    }
+
+    fn id(&self) -> &Self::Id {
+        &self.id
+    }
+}
+
+/// Test event of the [`Account`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum AccountEvent {
+    Opened(u8),
+    Credited(u32),
+    Debited(u32),
+}
+
+impl Event for AccountEvent {
+    fn event_type(&self) -> EventType {
+        match self {
+            Self::Opened(_) => "opened",
+            Self::Credited(_) => "credited",
+            Self::Debited(_) => "debited",
+        }
+    }
+}
+
+impl EventSourced<AccountEvent> for Account {
+    fn apply(&mut self, ev: &AccountEvent) {
+        match ev {
+            AccountEvent::Opened(id) => {
+                self.id = *id;
+                self.openings += 1;
+            }
+            AccountEvent::Credited(amount) => self.balance += amount,
+            AccountEvent::Debited(amount) => self.balance -= amount,
+        }
+    }
+}
+
+/// Test command transferring money between two [`Account`]s, opening the
+/// receiving one if it doesn't exist.
+#[derive(Debug, Clone, Copy)]
+struct Transfer {
+    ids: [u8; 2],
+    amount: u32,
+    expected: Option<(u8, Version)>,
+}
+
+impl Transfer {
+    fn new(from: u8, to: u8, amount: u32) -> Self {
+        Self {
+            ids: [from, to],
+            amount,
+            expected: None,
+        }
+    }
+}
+
+impl MultiAggregateCommand for Transfer {
+    type Aggregate = Account;
+
+    fn aggregate_ids(&self) -> &[u8] {
+        &self.ids
+    }
+
+    fn expected_version(&self, id: &u8) -> Option<Version> {
+        self.expected
+            .and_then(|(exp_id, ver)| (exp_id == *id).then(|| ver))
+    }
+}
+
+type Streams = Vec<(u8, Vec<NumberedEvent<AccountEvent>>)>;
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl MultiAggregateCommandHandler<Transfer> for Account {
+    type Context = ();
+    type Event = AccountEvent;
+    type Err = &'static str;
+    type Ok = Streams;
+
+    async fn handle(
+        cmd: Transfer,
+        aggs: &[HydratedAggregate<Self>],
+        _: &(),
+    ) -> Result<Streams, &'static str> {
+        let [from, to] = cmd.ids;
+        let from = aggs
+            .iter()
+            .find(|agg| *agg.id() == from)
+            .ok_or("no source account")?;
+        if from.state().balance < cmd.amount {
+            return Err("insufficient balance");
+        }
+
+        let to_events = match aggs.iter().find(|agg| *agg.id() == to) {
+            Some(agg) => vec![NumberedEvent {
+                num: agg.version().next_event(),
+                data: AccountEvent::Credited(cmd.amount),
+            }],
+            None => vec![
+                NumberedEvent {
+                    num: EventNumber::MIN_VALUE,
+                    data: AccountEvent::Opened(to),
+                },
+                NumberedEvent {
+                    num: EventNumber::new(2u8).unwrap(),
+                    data: AccountEvent::Credited(cmd.amount),
+                },
+            ],
+        };
+        Ok(vec![
+            (
+                *from.id(),
+                vec![NumberedEvent {
+                    num: from.version().next_event(),
+                    data: AccountEvent::Debited(cmd.amount),
+                }],
+            ),
+            (to, to_events),
+        ])
+    }
+}
+
+/// In-memory storage of [`Account`]s' events, optionally failing to append
+/// the events of the given [`Account`] with a [`VersionConflict`].
+#[derive(Debug, Default)]
+struct Store {
+    events: Mutex<Vec<(u8, NumberedEvent<AccountEvent>)>>,
+    conflicting: Option<u8>,
+}
+
+impl Store {
+    /// Creates new [`Store`] with opened [`Account`]s having the given
+    /// balances.
+    fn with_accounts(balances: &[(u8, u32)]) -> Self {
+        let store = Self::default();
+        for (id, balance) in balances {
+            store.events.lock().unwrap().extend([
+                (
+                    *id,
+                    NumberedEvent {
+                        num: EventNumber::MIN_VALUE,
+                        data: AccountEvent::Opened(*id),
+                    },
+                ),
+                (
+                    *id,
+                    NumberedEvent {
+                        num: EventNumber::new(2u8).unwrap(),
+                        data: AccountEvent::Credited(*balance),
+                    },
+                ),
+            ]);
+        }
+        store
+    }
+
+    fn stream_len(&self, id: u8) -> usize {
+        self.events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(ev_id, _)| *ev_id == id)
+            .count()
+    }
+}
+
+impl AsRef<Store> for Store {
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl SnapshotSource<Account> for Store {
+    type Err = Infallible;
+
+    async fn load_snapshots(
+        &self,
+        _: &[u8],
+    ) -> Result<Vec<(Account, Version, SnapshotVersion, SystemTime)>, Infallible> {
+        Ok(vec![])
+    }
+}
+
+impl EventSource<Account, AccountEvent> for Store {
+    type Err = Infallible;
+
+    fn read_events(
+        &self,
+        id: &u8,
+        since: Since,
+    ) -> BoxTryStream<'_, NumberedEvent<AccountEvent>, Infallible> {
+        let events = self
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(ev_id, ev)| {
+                ev_id == id
+                    && match since {
+                        Since::BeginningOfStream => true,
+                        Since::Event(num) => ev.num > num,
+                    }
+            })
+            .map(|(_, ev)| Ok(*ev))
+            .collect::<Vec<_>>();
+        Box::pin(stream::iter(events))
+    }
+}
+
+/// [`UnitOfWork`] over the [`Store`], staging all the appended events until
+/// commit.
+struct Atomic<'s>(&'s Store);
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl<'s> UnitOfWork for Atomic<'s> {
+    type Transaction = Staged<'s>;
+    type Err = Infallible;
+
+    fn atomicity(&self) -> Atomicity {
+        Atomicity::Atomic
+    }
+
+    async fn begin(&self) -> Result<Staged<'s>, Infallible> {
+        Ok(Staged {
+            store: self.0,
+            events: Mutex::default(),
+        })
+    }
+}
+
+/// [`Transaction`] of the [`Atomic`] [`UnitOfWork`].
+struct Staged<'s> {
+    store: &'s Store,
+    events: Mutex<Vec<(u8, NumberedEvent<AccountEvent>)>>,
+}
+
+impl AsRef<Self> for Staged<'_> {
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl Transaction for Staged<'_> {
+    type Err = Infallible;
+
+    async fn commit(self) -> Result<(), Infallible> {
+        self.store
+            .events
+            .lock()
+            .unwrap()
+            .extend(mem::take(&mut *self.events.lock().unwrap()));
+        Ok(())
+    }
+
+    async fn rollback(self) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl EventSink<Account, AccountEvent, ()> for Staged<'_> {
+    type Err = VersionConflict;
+    type Ok = Vec<NumberedEvent<AccountEvent>>;
+
+    async fn append_events(
+        &self,
+        id: &u8,
+        expected: Version,
+        events: &[NumberedEvent<AccountEvent>],
+        _: &(),
+    ) -> Result<Self::Ok, VersionConflict> {
+        let staged = self
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(ev_id, _)| ev_id == id)
+            .count();
+        let mut ver = Version::new((self.store.stream_len(*id) + staged) as u64);
+        if ver != expected || self.store.conflicting == Some(*id) {
+            return Err(VersionConflict {
+                expected,
+                actual: ver.next(),
+            });
+        }
+        let appended = events
+            .iter()
+            .map(|ev| {
+                ver.incr();
+                NumberedEvent {
+                    num: ver.event_number().unwrap(),
+                    data: ev.data,
+                }
+            })
+            .collect::<Vec<_>>();
+        self.events
+            .lock()
+            .unwrap()
+            .extend(appended.iter().map(|ev| (*id, *ev)));
+        Ok(appended)
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl SnapshotSink<Account> for Staged<'_> {
+    type Err = Infallible;
+
+    async fn persist_snapshots(&self, _: &[(&Account, Version)]) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+type TransferError = LoadAggregatesExecAndPersistError<
+    u8,
+    &'static str,
+    Infallible,
+    Infallible,
+    Infallible,
+    VersionConflict,
+    Infallible,
+>;
+
+fn transfer(
+    store: &Store,
+    cmd: Transfer,
+    ctx: &Context<()>,
+) -> Result<Vec<HydratedAggregate<Account>>, TransferError> {
+    block_on(
+        Basic::new(NeverSnapshot).load_aggregates_exec_command_and_persist::<
+            Store,
+            Store,
+            _,
+            Staged<'_>,
+            Staged<'_>,
+            _,
+            _,
+            _,
+            _,
+            _,
+        >(cmd, &(), &(), store, &Atomic(store), Some(ctx)),
+    )
+}
+
+#[test]
+fn persists_events_of_all_aggregates() {
+    let store = Store::with_accounts(&[(1, 10), (2, 0)]);
+    let ctx = Context::new(());
+
+    let aggs = transfer(&store, Transfer::new(1, 2, 4), &ctx).unwrap();
+
+    let balances = aggs
+        .iter()
+        .map(|agg| (*agg.id(), agg.state().balance, agg.version()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        balances,
+        vec![(1, 6, Version::new(3u8)), (2, 4, Version::new(3u8))],
+    );
+    assert_eq!(store.stream_len(1), 3);
+    assert_eq!(store.stream_len(2), 3);
+    assert_eq!(ctx.take_buffered_events::<AccountEvent>().len(), 2);
+}
+
+#[test]
+fn initializes_missing_aggregate() {
+    let store = Store::with_accounts(&[(1, 10)]);
+    let ctx = Context::new(());
+
+    let aggs = transfer(&store, Transfer::new(1, 3, 4), &ctx).unwrap();
+
+    assert_eq!(aggs.len(), 2);
+    assert_eq!(
+        *aggs[1].state(),
+        Account {
+            id: 3,
+            balance: 4,
+            openings: 1,
+        },
+    );
+    assert_eq!(aggs[1].version(), Version::new(2u8));
+    assert_eq!(store.stream_len(3), 2);
+}
+
+#[test]
+fn checks_expected_version_of_each_aggregate() {
+    let store = Store::with_accounts(&[(1, 10), (2, 0)]);
+    let ctx = Context::new(());
+    let cmd = Transfer {
+        expected: Some((2, Version::new(1u8))),
+        ..Transfer::new(1, 2, 4)
+    };
+
+    let err = transfer(&store, cmd, &ctx).unwrap_err();
+
+    assert_eq!(
+        err,
+        LoadAggregatesExecAndPersistError::VersionConflict {
+            id: 2,
+            expected: Version::new(1u8),
+            actual: Version::new(2u8),
+        },
+    );
+    assert_eq!(store.events.lock().unwrap().len(), 4);
+}
+
+#[test]
+fn does_not_persist_events_of_failed_command() {
+    let store = Store::with_accounts(&[(1, 10), (2, 0)]);
+    let ctx = Context::new(());
+
+    let err = transfer(&store, Transfer::new(1, 2, 11), &ctx).unwrap_err();
+
+    assert_eq!(
+        err,
+        LoadAggregatesExecAndPersistError::Exec("insufficient balance"),
+    );
+    assert_eq!(store.events.lock().unwrap().len(), 4);
+}
+
+#[test]
+fn rolls_back_all_streams_on_conflict() {
+    let store = Store {
+        conflicting: Some(2),
+        ..Store::with_accounts(&[(1, 10), (2, 0)])
+    };
+    let ctx = Context::new(());
+
+    let err = transfer(&store, Transfer::new(1, 2, 4), &ctx).unwrap_err();
+
+    assert_eq!(
+        err,
+        LoadAggregatesExecAndPersistError::Persist(UnitOfWorkError::RolledBack(
+            PersistError::VersionConflict(VersionConflict {
+                expected: Version::new(2u8),
+                actual: Version::new(3u8),
+            }),
+        )),
+    );
+    assert_eq!(store.events.lock().unwrap().len(), 4);
+    assert!(ctx.take_buffered_events::<AccountEvent>().is_empty());
+}
@@ -0,0 +1,223 @@
+use std::{convert::Infallible, sync::Mutex, time::SystemTime};
+
+use async_trait::async_trait;
+use cqrs::{
+    lifecycle::{Basic, Context, Static},
+    Aggregate, AggregateType, AlwaysSnapshot, BoxTryStream, Event, EventNumber, EventSink,
+    EventSource, EventSourced, EventType, HydratedAggregate, NumberedEvent, Repository, Since,
+    SnapshotSink, SnapshotSource, SnapshotVersion, Version, VersionConflict,
+};
+use futures::{executor::block_on, stream};
+
+/// Test aggregate counting applied increments.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+struct Counter {
+    value: u32,
+}
+
+impl Aggregate for Counter {
+    type Id = u8;
+
+    fn aggregate_type(&self) -> AggregateType {
+        "counter"
+    }
+
+    fn id(&self) -> &Self::Id {
+        &1
+    }
+}
+
+/// Test event incrementing the [`Counter`].
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+struct Incremented;
+
+impl Event for Incremented {
+    fn event_type(&self) -> EventType {
+        "incremented"
+    }
+}
+
+impl EventSourced<Incremented> for Counter {
+    fn apply(&mut self, _: &Incremented) {
+        self.value += 1;
+    }
+}
+
+/// Storage of [`Counter`]'s events only.
+#[derive(Debug, Default)]
+struct Events(Mutex<Vec<NumberedEvent<Incremented>>>);
+
+impl EventSource<Counter, Incremented> for Events {
+    type Err = Infallible;
+
+    fn read_events(
+        &self,
+        _: &u8,
+        since: Since,
+    ) -> BoxTryStream<'_, NumberedEvent<Incremented>, Infallible> {
+        let events = self
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|ev| match since {
+                Since::BeginningOfStream => true,
+                Since::Event(num) => ev.num > num,
+            })
+            .copied()
+            .map(Ok)
+            .collect::<Vec<_>>();
+        Box::pin(stream::iter(events))
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl EventSink<Counter, Incremented, ()> for Events {
+    type Err = VersionConflict;
+    type Ok = Vec<NumberedEvent<Incremented>>;
+
+    async fn append_events(
+        &self,
+        _: &u8,
+        expected: Version,
+        events: &[NumberedEvent<Incremented>],
+        _: &(),
+    ) -> Result<Self::Ok, VersionConflict> {
+        let mut ver = Version::new(self.0.lock().unwrap().len() as u64);
+        if ver != expected {
+            return Err(VersionConflict {
+                expected,
+                actual: ver,
+            });
+        }
+        let appended = events
+            .iter()
+            .map(|ev| {
+                ver.incr();
+                NumberedEvent {
+                    num: ver.event_number().unwrap(),
+                    data: ev.data,
+                }
+            })
+            .collect::<Vec<_>>();
+        self.0.lock().unwrap().extend_from_slice(&appended);
+        Ok(appended)
+    }
+}
+
+/// Storage of [`Counter`]'s snapshots only.
+#[derive(Debug, Default)]
+struct Snapshots(Mutex<Option<(Counter, Version, SnapshotVersion, SystemTime)>>);
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl SnapshotSource<Counter> for Snapshots {
+    type Err = Infallible;
+
+    async fn load_snapshots(
+        &self,
+        _: &[u8],
+    ) -> Result<Vec<(Counter, Version, SnapshotVersion, SystemTime)>, Infallible> {
+        Ok(self.0.lock().unwrap().iter().copied().collect())
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl SnapshotSink<Counter> for Snapshots {
+    type Err = Infallible;
+
+    async fn persist_snapshots(&self, aggs: &[(&Counter, Version)]) -> Result<(), Infallible> {
+        if let Some((agg, ver)) = aggs.last() {
+            *self.0.lock().unwrap() =
+                Some((**agg, *ver, Counter::SNAPSHOT_VERSION, SystemTime::now()));
+        }
+        Ok(())
+    }
+}
+
+type Repo = Repository<Events, Snapshots>;
+
+fn increments(count: u8) -> Vec<NumberedEvent<Incremented>> {
+    (1..=count)
+        .map(|n| NumberedEvent {
+            num: EventNumber::new(n).unwrap(),
+            data: Incremented,
+        })
+        .collect()
+}
+
+#[test]
+fn delegates_to_separate_stores_in_basic_lifecycle() {
+    let repo = Repository::new(Events::default(), ()).with_snapshot_store(Snapshots::default());
+    let lifecycle = Basic::new(AlwaysSnapshot);
+    let ctx = Context::new(());
+
+    let mut agg = HydratedAggregate::default();
+    block_on(
+        lifecycle.apply_events_and_persist::<Repo, Repo, _, _, _, _, _, _>(
+            &mut agg,
+            increments(2),
+            &(),
+            &repo,
+            Some(&ctx),
+        ),
+    )
+    .unwrap();
+
+    assert_eq!(repo.event_store().0.lock().unwrap().len(), 2);
+    assert_eq!(
+        repo.snapshot_store()
+            .0
+            .lock()
+            .unwrap()
+            .map(|(agg, ver, snapshot_ver, _)| (agg, ver, snapshot_ver)),
+        Some((Counter { value: 2 }, Version::new(2u8), 1)),
+    );
+}
+
+#[test]
+fn serves_as_context_of_static_lifecycle() {
+    let (events, snapshots) = (Events::default(), Snapshots::default());
+    events.0.lock().unwrap().extend(increments(3));
+    *snapshots.0.lock().unwrap() = Some((
+        Counter { value: 2 },
+        Version::new(2u8),
+        1,
+        SystemTime::now(),
+    ));
+    let lifecycle = Static::new(
+        AlwaysSnapshot,
+        Context::new(Repository::new(events, snapshots)),
+    );
+
+    let agg = block_on(lifecycle.load_aggregate_and_rehydrate::<Repo, Repo, _, Counter>(&1))
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(*agg.state(), Counter { value: 3 });
+    assert_eq!(agg.snapshot_version(), Some(Version::new(2u8)));
+}
+
+#[test]
+fn discards_snapshot_of_outdated_format() {
+    let (events, snapshots) = (Events::default(), Snapshots::default());
+    events.0.lock().unwrap().extend(increments(3));
+    *snapshots.0.lock().unwrap() = Some((
+        Counter { value: 10 },
+        Version::new(2u8),
+        0,
+        SystemTime::now(),
+    ));
+    let repo = Repository::new(events, snapshots);
+    let lifecycle = Basic::new(AlwaysSnapshot);
+
+    let agg =
+        block_on(lifecycle.load_aggregate_and_rehydrate::<Repo, Repo, _, Counter, _>(&1, &repo))
+            .unwrap()
+            .unwrap();
+
+    assert_eq!(*agg.state(), Counter { value: 3 });
+    assert_eq!(agg.snapshot_version(), None);
+}
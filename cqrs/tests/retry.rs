@@ -0,0 +1,220 @@
+use std::{
+    convert::Infallible,
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    task::Poll,
+};
+
+use async_trait::async_trait;
+use cqrs::{
+    lifecycle::LoadExecAndPersistError, Aggregate, AggregateType, Command, CommandBus, RetryPolicy,
+    RetryingCommandBus, Version,
+};
+use futures::{executor::block_on, future};
+
+/// Test aggregate with no state.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+struct TestAggregate;
+
+impl Aggregate for TestAggregate {
+    type Id = u8;
+
+    fn aggregate_type(&self) -> AggregateType {
+        "test"
+    }
+
+    fn id(&self) -> &Self::Id {
+        &1
+    }
+}
+
+/// Test command with an optional expected version.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+struct TestCommand(Option<Version>);
+
+impl Command for TestCommand {
+    type Aggregate = TestAggregate;
+
+    fn aggregate_id(&self) -> Option<&u8> {
+        Some(&1)
+    }
+
+    fn expected_version(&self) -> Option<Version> {
+        self.0
+    }
+}
+
+type TestError =
+    LoadExecAndPersistError<TestAggregate, &'static str, Infallible, Infallible, (), Infallible>;
+
+/// [`CommandBus`] failing with the given error for the first `failures` calls.
+struct FlakyBus {
+    failures: usize,
+    error: TestError,
+    calls: AtomicUsize,
+}
+
+impl FlakyBus {
+    fn new(failures: usize, error: TestError) -> Self {
+        Self {
+            failures,
+            error,
+            calls: AtomicUsize::new(0),
+        }
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl CommandBus<TestCommand> for FlakyBus {
+    type Err = TestError;
+    type Ok = usize;
+
+    async fn dispatch(&self, _: TestCommand) -> Result<usize, TestError> {
+        let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+        if call <= self.failures {
+            Err(self.error)
+        } else {
+            Ok(call)
+        }
+    }
+}
+
+/// [`CommandBus`] always failing with a version conflict and logging its
+/// calls.
+struct LoggingBus<'a> {
+    log: &'a Mutex<Vec<String>>,
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl CommandBus<TestCommand> for LoggingBus<'_> {
+    type Err = TestError;
+    type Ok = usize;
+
+    async fn dispatch(&self, _: TestCommand) -> Result<usize, TestError> {
+        self.log.lock().unwrap().push("dispatch".into());
+        Err(conflict())
+    }
+}
+
+fn conflict() -> TestError {
+    LoadExecAndPersistError::VersionConflict {
+        expected: Version::Initial,
+        actual: Version::new(1u8),
+    }
+}
+
+#[test]
+fn retries_on_version_conflict() {
+    let bus = RetryingCommandBus::new(
+        FlakyBus::new(2, conflict()),
+        RetryPolicy::new(NonZeroUsize::new(3).unwrap()),
+    );
+
+    assert_eq!(block_on(bus.dispatch(TestCommand(None))), Ok(3));
+}
+
+#[test]
+fn gives_up_after_max_attempts() {
+    let bus = RetryingCommandBus::new(
+        FlakyBus::new(5, conflict()),
+        RetryPolicy::new(NonZeroUsize::new(3).unwrap()),
+    );
+
+    assert_eq!(block_on(bus.dispatch(TestCommand(None))), Err(conflict()));
+    assert_eq!(bus.inner().calls.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn does_not_retry_non_retryable_errors() {
+    let err = LoadExecAndPersistError::Exec(Default::default(), "invalid");
+    let bus = RetryingCommandBus::new(
+        FlakyBus::new(1, err),
+        RetryPolicy::new(NonZeroUsize::new(3).unwrap()),
+    );
+
+    assert_eq!(block_on(bus.dispatch(TestCommand(None))), Err(err));
+    assert_eq!(bus.inner().calls.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn does_not_retry_commands_with_expected_version() {
+    let bus = RetryingCommandBus::new(
+        FlakyBus::new(1, conflict()),
+        RetryPolicy::new(NonZeroUsize::new(3).unwrap()),
+    );
+
+    let res = block_on(bus.dispatch(TestCommand(Some(Version::Initial))));
+
+    assert_eq!(res, Err(conflict()));
+    assert_eq!(bus.inner().calls.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn applies_custom_condition_and_backoff() {
+    let waited = Mutex::new(vec![]);
+    let policy = RetryPolicy::new(NonZeroUsize::new(3).unwrap())
+        .retry_if(|_: &TestError| true)
+        .with_backoff(|attempt| {
+            waited.lock().unwrap().push(attempt);
+            async {}
+        });
+    let err = LoadExecAndPersistError::Exec(Default::default(), "flaky");
+    let bus = RetryingCommandBus::new(FlakyBus::new(2, err), policy);
+
+    assert_eq!(block_on(bus.dispatch(TestCommand(None))), Ok(3));
+    assert_eq!(*waited.lock().unwrap(), vec![1, 2]);
+}
+
+#[test]
+fn waits_for_backoff_before_each_retry() {
+    let log = Mutex::new(vec![]);
+    let policy = RetryPolicy::new(NonZeroUsize::new(3).unwrap()).with_backoff(|attempt| {
+        let log = &log;
+        async move {
+            // Yields once, so the retry would overtake a non-awaited wait.
+            let mut yielded = false;
+            future::poll_fn(|cx| {
+                if yielded {
+                    Poll::Ready(())
+                } else {
+                    yielded = true;
+                    cx.waker().wake_by_ref();
+                    Poll::Pending
+                }
+            })
+            .await;
+            log.lock().unwrap().push(format!("wait {}", attempt));
+        }
+    });
+    let bus = RetryingCommandBus::new(LoggingBus { log: &log }, policy);
+
+    assert_eq!(block_on(bus.dispatch(TestCommand(None))), Err(conflict()));
+    assert_eq!(
+        *log.lock().unwrap(),
+        vec!["dispatch", "wait 1", "dispatch", "wait 2", "dispatch"],
+    );
+}
+
+#[test]
+fn neither_retries_nor_waits_for_commands_with_expected_version() {
+    let waited = Mutex::new(vec![]);
+    let policy = RetryPolicy::new(NonZeroUsize::new(3).unwrap())
+        .retry_if(|_: &TestError| true)
+        .with_backoff(|attempt| {
+            waited.lock().unwrap().push(attempt);
+            async {}
+        });
+    let err = LoadExecAndPersistError::Exec(Default::default(), "flaky");
+    let bus = RetryingCommandBus::new(FlakyBus::new(1, err), policy);
+
+    let res = block_on(bus.dispatch(TestCommand(Some(Version::new(1u8)))));
+
+    assert_eq!(res, Err(err));
+    assert_eq!(bus.inner().calls.load(Ordering::SeqCst), 1);
+    assert!(waited.lock().unwrap().is_empty());
+}
@@ -0,0 +1,330 @@
+use std::{convert::Infallible, mem, sync::Mutex};
+
+use async_trait::async_trait;
+use cqrs::{
+    lifecycle::{
+        Atomicity, Basic, BestEffort, BufferedContext as _, Context, PersistError, Transaction,
+        UnitOfWork, UnitOfWorkError,
+    },
+    Aggregate, AggregateType, AlwaysSnapshot, Event, EventNumber, EventSink, EventSourced,
+    EventType, HydratedAggregate, NumberedEvent, SnapshotSink, Version, VersionConflict,
+};
+use futures::executor::block_on;
+
+/// Test aggregate counting applied increments.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+struct Counter {
+    value: u32,
+}
+
+impl Aggregate for Counter {
+    type Id = u8;
+
+    fn aggregate_type(&self) -> AggregateType {
+        "counter"
+    }
+
+    fn id(&self) -> &Self::Id {
+        &1
+    }
+}
+
+/// Test event incrementing the [`Counter`].
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+struct Incremented;
+
+impl Event for Incremented {
+    fn event_type(&self) -> EventType {
+        "incremented"
+    }
+}
+
+impl EventSourced<Incremented> for Counter {
+    fn apply(&mut self, _: &Incremented) {
+        self.value += 1;
+    }
+}
+
+/// Error of persisting snapshots into the [`Store`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct SnapshotFailed;
+
+/// In-memory storage of a single [`Counter`], optionally failing to persist
+/// its snapshots.
+#[derive(Debug, Default)]
+struct Store {
+    events: Mutex<Vec<NumberedEvent<Incremented>>>,
+    snapshots: Mutex<Vec<(Counter, Version)>>,
+    fail_snapshots: bool,
+}
+
+impl Store {
+    fn failing_snapshots() -> Self {
+        Self {
+            fail_snapshots: true,
+            ..Self::default()
+        }
+    }
+
+    fn append(
+        &self,
+        stored: usize,
+        expected: Version,
+        events: &[NumberedEvent<Incremented>],
+    ) -> Result<Vec<NumberedEvent<Incremented>>, VersionConflict> {
+        let mut ver = Version::new(stored as u64);
+        if ver != expected {
+            return Err(VersionConflict {
+                expected,
+                actual: ver,
+            });
+        }
+        Ok(events
+            .iter()
+            .map(|ev| {
+                ver.incr();
+                NumberedEvent {
+                    num: ver.event_number().unwrap(),
+                    data: ev.data,
+                }
+            })
+            .collect())
+    }
+}
+
+impl AsRef<Store> for Store {
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl EventSink<Counter, Incremented, ()> for Store {
+    type Err = VersionConflict;
+    type Ok = Vec<NumberedEvent<Incremented>>;
+
+    async fn append_events(
+        &self,
+        _: &u8,
+        expected: Version,
+        events: &[NumberedEvent<Incremented>],
+        _: &(),
+    ) -> Result<Self::Ok, VersionConflict> {
+        let appended = self.append(self.events.lock().unwrap().len(), expected, events)?;
+        self.events.lock().unwrap().extend_from_slice(&appended);
+        Ok(appended)
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl SnapshotSink<Counter> for Store {
+    type Err = SnapshotFailed;
+
+    async fn persist_snapshots(&self, aggs: &[(&Counter, Version)]) -> Result<(), SnapshotFailed> {
+        if self.fail_snapshots {
+            return Err(SnapshotFailed);
+        }
+        self.snapshots
+            .lock()
+            .unwrap()
+            .extend(aggs.iter().map(|(agg, ver)| (**agg, *ver)));
+        Ok(())
+    }
+}
+
+/// [`UnitOfWork`] over the [`Store`], staging all the changes until commit.
+struct Atomic<'s>(&'s Store);
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl<'s> UnitOfWork for Atomic<'s> {
+    type Transaction = Staged<'s>;
+    type Err = Infallible;
+
+    fn atomicity(&self) -> Atomicity {
+        Atomicity::Atomic
+    }
+
+    async fn begin(&self) -> Result<Staged<'s>, Infallible> {
+        Ok(Staged {
+            store: self.0,
+            events: Mutex::default(),
+            snapshots: Mutex::default(),
+        })
+    }
+}
+
+/// [`Transaction`] of the [`Atomic`] [`UnitOfWork`].
+struct Staged<'s> {
+    store: &'s Store,
+    events: Mutex<Vec<NumberedEvent<Incremented>>>,
+    snapshots: Mutex<Vec<(Counter, Version)>>,
+}
+
+impl AsRef<Self> for Staged<'_> {
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl Transaction for Staged<'_> {
+    type Err = Infallible;
+
+    async fn commit(self) -> Result<(), Infallible> {
+        self.store
+            .events
+            .lock()
+            .unwrap()
+            .extend(mem::take(&mut *self.events.lock().unwrap()));
+        self.store
+            .snapshots
+            .lock()
+            .unwrap()
+            .extend(mem::take(&mut *self.snapshots.lock().unwrap()));
+        Ok(())
+    }
+
+    async fn rollback(self) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl EventSink<Counter, Incremented, ()> for Staged<'_> {
+    type Err = VersionConflict;
+    type Ok = Vec<NumberedEvent<Incremented>>;
+
+    async fn append_events(
+        &self,
+        _: &u8,
+        expected: Version,
+        events: &[NumberedEvent<Incremented>],
+        _: &(),
+    ) -> Result<Self::Ok, VersionConflict> {
+        let stored = self.store.events.lock().unwrap().len() + self.events.lock().unwrap().len();
+        let appended = self.store.append(stored, expected, events)?;
+        self.events.lock().unwrap().extend_from_slice(&appended);
+        Ok(appended)
+    }
+}
+
+#[cfg_attr(feature = "send", async_trait)]
+#[cfg_attr(not(feature = "send"), async_trait(?Send))]
+impl SnapshotSink<Counter> for Staged<'_> {
+    type Err = SnapshotFailed;
+
+    async fn persist_snapshots(&self, aggs: &[(&Counter, Version)]) -> Result<(), SnapshotFailed> {
+        if self.store.fail_snapshots {
+            return Err(SnapshotFailed);
+        }
+        self.snapshots
+            .lock()
+            .unwrap()
+            .extend(aggs.iter().map(|(agg, ver)| (**agg, *ver)));
+        Ok(())
+    }
+}
+
+fn increment<UoW, Snk>(
+    uow: &UoW,
+    ctx: &Context<()>,
+) -> Result<HydratedAggregate<Counter>, UnitOfWorkError<Infallible, VersionConflict, SnapshotFailed>>
+where
+    UoW: UnitOfWork<Err = Infallible>,
+    UoW::Transaction: AsRef<Snk>,
+    Snk: EventSink<Counter, Incremented, (), Err = VersionConflict>
+        + SnapshotSink<Counter, Err = SnapshotFailed>,
+{
+    let mut agg = HydratedAggregate::<Counter>::default();
+    block_on(
+        Basic::new(AlwaysSnapshot)
+            .apply_events_and_persist_in_unit_of_work::<UoW, Snk, Snk, _, _, _, _, _>(
+                &mut agg,
+                [NumberedEvent {
+                    num: EventNumber::MIN_VALUE,
+                    data: Incremented,
+                }],
+                &(),
+                uow,
+                Some(ctx),
+            ),
+    )
+    .map(|()| agg)
+}
+
+#[test]
+fn commits_events_and_snapshot_together() {
+    let store = Store::default();
+    let ctx = Context::new(());
+
+    let agg = increment::<_, Staged<'_>>(&Atomic(&store), &ctx).unwrap();
+
+    assert_eq!(agg.version(), Version::new(1u8));
+    assert_eq!(store.events.lock().unwrap().len(), 1);
+    assert_eq!(
+        *store.snapshots.lock().unwrap(),
+        vec![(*agg.state(), agg.version())]
+    );
+    assert_eq!(ctx.take_buffered_events::<Incremented>().len(), 1);
+}
+
+#[test]
+fn rolls_back_events_when_snapshot_fails() {
+    let store = Store::failing_snapshots();
+    let ctx = Context::new(());
+
+    let err = increment::<_, Staged<'_>>(&Atomic(&store), &ctx).unwrap_err();
+
+    assert_eq!(
+        err,
+        UnitOfWorkError::RolledBack(PersistError::Snapshot(SnapshotFailed)),
+    );
+    assert!(store.events.lock().unwrap().is_empty());
+    assert!(ctx.take_buffered_events::<Incremented>().is_empty());
+}
+
+#[test]
+fn reports_partially_persisted_events_of_best_effort() {
+    let store = Store::failing_snapshots();
+    let ctx = Context::new(());
+
+    let err = increment::<_, Store>(&BestEffort(&store), &ctx).unwrap_err();
+
+    assert_eq!(
+        err,
+        UnitOfWorkError::PartiallyPersisted(PersistError::Snapshot(SnapshotFailed)),
+    );
+    assert_eq!(store.events.lock().unwrap().len(), 1);
+    // Persisted events remain persisted, so are handled as usual.
+    assert_eq!(ctx.take_buffered_events::<Incremented>().len(), 1);
+}
+
+#[test]
+fn persists_in_best_effort_unit_of_work_by_default() {
+    let store = Store::failing_snapshots();
+    let ctx = Context::new(());
+    let mut agg = HydratedAggregate::<Counter>::default();
+
+    let err = block_on(
+        Basic::new(AlwaysSnapshot).apply_events_and_persist::<Store, Store, _, _, _, _, _, _>(
+            &mut agg,
+            [NumberedEvent {
+                num: EventNumber::MIN_VALUE,
+                data: Incremented,
+            }],
+            &(),
+            &store,
+            Some(&ctx),
+        ),
+    )
+    .unwrap_err();
+
+    assert_eq!(err, PersistError::Snapshot(SnapshotFailed));
+    assert_eq!(store.events.lock().unwrap().len(), 1);
+    assert_eq!(ctx.take_buffered_events::<Incremented>().len(), 1);
+}